@@ -0,0 +1,56 @@
+use agldt::parser::{Token, Treebank};
+use agldt::utils::{IterTokens, Lexicon};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::fs::read_to_string;
+
+fn fixture() -> Treebank {
+    let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    Treebank::from_xml_str(&src).unwrap()
+}
+
+fn iteration(c: &mut Criterion) {
+    let treebank = fixture();
+    let mut group = c.benchmark_group("tokens");
+    // What the accessors used to do: clone the whole body before iterating.
+    group.bench_function("cloned", |b| {
+        b.iter(|| {
+            let tokens: Vec<Token> = black_box(&treebank)
+                .body()
+                .clone()
+                .into_iter()
+                .flatten()
+                .collect();
+            tokens.iter().filter(|token| token.is_word()).count()
+        });
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            black_box(&treebank)
+                .iter_tokens()
+                .filter(|token| token.is_word())
+                .count()
+        });
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("lemmata");
+    group.bench_function("cloned", |b| {
+        b.iter(|| {
+            black_box(&treebank)
+                .body()
+                .clone()
+                .into_iter()
+                .flatten()
+                .filter(Token::is_word)
+                .filter_map(|token| token.lemma().map(str::to_string))
+                .collect::<Vec<String>>()
+        });
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| black_box(&treebank).get_lemmata());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, iteration);
+criterion_main!(benches);
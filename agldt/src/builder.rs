@@ -0,0 +1,360 @@
+use crate::error::{Error, Location, SUPPORTED_VERSIONS};
+use crate::features::Postag;
+use crate::parser::{Body, Header, PersInfo, Sentence, Token, Treebank};
+use crate::relation::Relation;
+use crate::validate::validate_sentence;
+use std::collections::BTreeMap;
+
+/// Builds a [`Treebank`], e.g. from the output of a tagger.
+///
+/// ```
+/// use agldt::parser::{Header, Monogr, Sentence, Token, Treebank};
+///
+/// let sentence = Sentence::builder(1)
+///     .document_id("urn:cts:greekLit:tlg0012.tlg001")
+///     .subdoc("1.1")
+///     .token(
+///         Token::builder(1, "μῆνιν")
+///             .lemma("μῆνις")
+///             .postag("n-s---fa-")
+///             .relation("OBJ")
+///             .head(2)
+///             .build()
+///             .unwrap(),
+///     )
+///     .token(
+///         Token::builder(2, "ἄειδε")
+///             .lemma("ἀείδω")
+///             .postag("v2spma---")
+///             .relation("PRED")
+///             .build()
+///             .unwrap(),
+///     )
+///     .build()
+///     .unwrap();
+/// let treebank = Treebank::builder(Header::new(Monogr::new("Homer", "Iliad")))
+///     .cts("urn:cts:greekLit:tlg0012.tlg001")
+///     .sentence(sentence)
+///     .build()
+///     .unwrap();
+/// assert_eq!(treebank.count_words(), 2);
+/// assert_eq!(Treebank::from_xml_str(&treebank.to_xml_string()).unwrap(), treebank);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreebankBuilder {
+    version: String,
+    xml_lang: String,
+    cts: String,
+    header: Header,
+    sentences: Vec<Sentence>,
+}
+
+impl TreebankBuilder {
+    /// A builder for an AGLDT 2.1 treebank in Ancient Greek (`grc`).
+    #[must_use]
+    pub fn new(header: Header) -> Self {
+        Self {
+            version: "2.1".to_string(),
+            xml_lang: "grc".to_string(),
+            cts: String::new(),
+            header,
+            sentences: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn xml_lang(mut self, xml_lang: &str) -> Self {
+        self.xml_lang = xml_lang.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn cts(mut self, cts: &str) -> Self {
+        self.cts = cts.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn sentence(mut self, sentence: Sentence) -> Self {
+        self.sentences.push(sentence);
+        self
+    }
+
+    #[must_use]
+    pub fn sentences<I: IntoIterator<Item = Sentence>>(mut self, sentences: I) -> Self {
+        self.sentences.extend(sentences);
+        self
+    }
+
+    /// Builds the treebank.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::UnsupportedVersion`] if the version is not in
+    /// [`SUPPORTED_VERSIONS`], and [`Error::InvalidSentence`] if two sentences share an id.
+    pub fn build(self) -> Result<Treebank, Error> {
+        if !SUPPORTED_VERSIONS.contains(&self.version.as_str()) {
+            return Err(Error::UnsupportedVersion {
+                version: self.version,
+                location: Location::default(),
+            });
+        }
+        for (i, sentence) in self.sentences.iter().enumerate() {
+            if self.sentences[..i].iter().any(|s| s.id == sentence.id) {
+                return Err(Error::InvalidSentence {
+                    reason: "duplicate sentence id".to_string(),
+                    location: Location {
+                        sentence_id: Some(sentence.id),
+                        ..Location::default()
+                    },
+                });
+            }
+        }
+        Ok(Treebank {
+            version: self.version,
+            xml_lang: self.xml_lang,
+            cts: self.cts,
+            header: self.header,
+            body: Body {
+                sentences: self.sentences,
+            },
+        })
+    }
+}
+
+/// Builds a [`Sentence`], checking on [`SentenceBuilder::build`] that its tokens form a
+/// well-formed dependency tree.
+#[derive(Debug, Clone)]
+pub struct SentenceBuilder {
+    id: u32,
+    document_id: String,
+    subdoc: String,
+    primary: Vec<String>,
+    secondary: Vec<String>,
+    annotators: Vec<PersInfo>,
+    words: Vec<Token>,
+}
+
+impl SentenceBuilder {
+    #[must_use]
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            document_id: String::new(),
+            subdoc: String::new(),
+            primary: vec![],
+            secondary: vec![],
+            annotators: vec![],
+            words: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn document_id(mut self, document_id: &str) -> Self {
+        self.document_id = document_id.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn subdoc(mut self, subdoc: &str) -> Self {
+        self.subdoc = subdoc.to_string();
+        self
+    }
+
+    /// Adds a main annotator, by its short name.
+    #[must_use]
+    pub fn primary(mut self, short: &str) -> Self {
+        self.primary.push(short.to_string());
+        self
+    }
+
+    /// Adds a secondary annotator, by its short name.
+    #[must_use]
+    pub fn secondary(mut self, short: &str) -> Self {
+        self.secondary.push(short.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn annotator(mut self, annotator: PersInfo) -> Self {
+        self.annotators.push(annotator);
+        self
+    }
+
+    #[must_use]
+    pub fn token(mut self, token: Token) -> Self {
+        self.words.push(token);
+        self
+    }
+
+    #[must_use]
+    pub fn tokens<I: IntoIterator<Item = Token>>(mut self, tokens: I) -> Self {
+        self.words.extend(tokens);
+        self
+    }
+
+    /// Builds the sentence.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidSentence`] for the first error reported by
+    /// [`validate_sentence`], e.g. a cycle or a head referencing no token. Warnings, such as
+    /// a missing lemma, are accepted.
+    pub fn build(self) -> Result<Sentence, Error> {
+        let sentence = Sentence {
+            id: self.id,
+            document_id: self.document_id,
+            subdoc: self.subdoc,
+            attributes: BTreeMap::new(),
+            primary: self.primary,
+            secondary: self.secondary,
+            annotators: self.annotators,
+            comments: vec![],
+            multiword_tokens: vec![],
+            words: self.words,
+        };
+        match validate_sentence(&sentence)
+            .into_iter()
+            .find(|diagnostic| diagnostic.is_error())
+        {
+            Some(diagnostic) => Err(Error::InvalidSentence {
+                reason: diagnostic.kind.to_string(),
+                location: diagnostic.location,
+            }),
+            None => Ok(sentence),
+        }
+    }
+}
+
+/// Builds a [`Token`], checking on [`TokenBuilder::build`] its id, postag and relation.
+///
+/// The head defaults to `0`, i.e. the root of the sentence, while the relation must be given.
+#[derive(Debug, Clone)]
+pub struct TokenBuilder {
+    id: u32,
+    form: String,
+    lemma: Option<String>,
+    postag: Option<String>,
+    insertion_id: Option<String>,
+    artificial: Option<String>,
+    relation: Option<String>,
+    head: u32,
+    cite: Option<String>,
+}
+
+impl TokenBuilder {
+    #[must_use]
+    pub fn new(id: u32, form: &str) -> Self {
+        Self {
+            id,
+            form: form.to_string(),
+            lemma: None,
+            postag: None,
+            insertion_id: None,
+            artificial: None,
+            relation: None,
+            head: 0,
+            cite: None,
+        }
+    }
+
+    #[must_use]
+    pub fn lemma(mut self, lemma: &str) -> Self {
+        self.lemma = Some(lemma.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn postag(mut self, postag: &str) -> Self {
+        self.postag = Some(postag.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn relation(mut self, relation: &str) -> Self {
+        self.relation = Some(relation.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn head(mut self, head: u32) -> Self {
+        self.head = head;
+        self
+    }
+
+    /// Sets the CTS URN of the passage of the token.
+    #[must_use]
+    pub fn cite(mut self, cite: &str) -> Self {
+        self.cite = Some(cite.to_string());
+        self
+    }
+
+    /// Marks the token as an artificial node of the given kind, e.g. `elliptic`, inserted at
+    /// `insertion_id`, e.g. `0004e`.
+    #[must_use]
+    pub fn artificial(mut self, kind: &str, insertion_id: &str) -> Self {
+        self.artificial = Some(kind.to_string());
+        self.insertion_id = Some(insertion_id.to_string());
+        self
+    }
+
+    /// Builds the token.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidAttribute`] if the id is `0`, which is
+    /// reserved for the root, or if the token is its own head, [`Error::MissingAttribute`] if
+    /// no relation was given, [`Error::InvalidPostag`] if the postag is not a valid AGLDT postag
+    /// and [`Error::InvalidRelation`] if the relation does not follow the AGLDT guidelines.
+    pub fn build(self) -> Result<Token, Error> {
+        let location = Location {
+            word_id: Some(self.id),
+            ..Location::default()
+        };
+        if self.id == 0 || self.head == self.id {
+            let (attribute, value) = if self.id == 0 {
+                ("id", self.id)
+            } else {
+                ("head", self.head)
+            };
+            return Err(Error::InvalidAttribute {
+                element: "word".to_string(),
+                attribute: attribute.to_string(),
+                value: value.to_string(),
+                location,
+            });
+        }
+        if let Some(postag) = &self.postag {
+            postag.parse::<Postag>().map_err(|e| e.in_word(self.id))?;
+        }
+        let Some(relation) = self.relation else {
+            return Err(Error::MissingAttribute {
+                element: "word".to_string(),
+                attribute: "relation".to_string(),
+                location,
+            });
+        };
+        relation
+            .parse::<Relation>()
+            .map_err(|e| e.in_word(self.id))?;
+        Ok(Token {
+            id: self.id,
+            form: self.form,
+            lemma: self.lemma,
+            postag: self.postag,
+            insertion_id: self.insertion_id,
+            artificial: self.artificial,
+            relation,
+            head: self.head,
+            cite: self.cite,
+            attributes: BTreeMap::new(),
+            ud: None,
+        })
+    }
+}
@@ -0,0 +1,1527 @@
+use crate::error::{Error, Location, Position};
+use crate::features::{
+    Case, Degree, Gender, Mood, Number, PartOfSpeech, Person, Postag, Tense, Voice,
+};
+use crate::parser::{
+    format_insertion_id, split_insertion_id, Header, Monogr, PersInfo, Sentence, Token, Treebank,
+};
+use crate::relation::{BaseRelation, Relation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+
+/// Punctuation written without a space before it.
+const CLOSING: [&str; 11] = [",", ".", "·", ";", ":", "!", "?", ")", "]", "»", "”"];
+/// Punctuation written without a space after it.
+const OPENING: [&str; 4] = ["(", "[", "«", "“"];
+
+/// Writes the treebank as CoNLL-U, converting each sentence to Universal Dependencies, see
+/// [`write_sentence`].
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`crate::tree::DependencyTree::new`] if a sentence is not a tree.
+pub fn write_treebank<W: Write>(treebank: &Treebank, writer: &mut W) -> Result<(), Error> {
+    for sentence in treebank.iter_sentences() {
+        write_sentence(sentence, writer)?;
+    }
+    Ok(())
+}
+
+/// Writes the sentence as a CoNLL-U block, followed by an empty line.
+///
+/// The AGLDT annotation is converted to Universal Dependencies:
+///
+/// - the part of speech and the postag features become `UPOS` and `FEATS`, while the postag is
+///   kept as `XPOS`;
+/// - relations become UD `DEPREL`s, chosen with the part of speech of the word, e.g. an `ATR`
+///   article is a `det` and an `ATR` noun a `nmod`;
+/// - the object of an `AuxP` or `AuxC` heads its preposition (`case`) or conjunction (`mark`);
+/// - the first member of a `COORD` or `APOS` heads the other members (`conj` or `appos`) and the
+///   coordinator (`cc`, or `punct`);
+/// - artificial nodes are left out, their first dependent taking their place and the others
+///   becoming its `orphan`s;
+/// - a single word is the `root`, the other words attached to the root of the AGLDT sentence
+///   being attached to it.
+///
+/// The comments give the sentence as `# sent_id = <document_id>:<subdoc>@<id>`, its text,
+/// rebuilt from the forms, as `# text`, and its attribution as `# primary`, `# secondary` and
+/// `# annotator`, each of the annotator's `short`, `uri` and `address` following its name as
+/// `# annotator short`, `# annotator uri` and `# annotator address`. The other attributes of the
+/// sentence are given as `# attribute <name>`. The `cite` of a token is kept in `MISC` as
+/// `Cite`, and its other attributes as `Attribute:<name>`, with `\` and `|` escaped as `\\`
+/// and `\p`.
+///
+/// A sentence read from CoNLL-U, whose tokens keep their [`UdColumns`], is not converted: the
+/// columns are written back as they were read, along with its multiword tokens, but for those
+/// made stale by an edit. An edited postag gives `UPOS`, `XPOS` and `FEATS` again, an edited
+/// relation gives the `DEPREL` of the conversion, and an edited head of an empty node replaces
+/// the head of its first enhanced dependency in `DEPS`. Tokens inserted without UD columns are
+/// written the same way. The comments read with a sentence are written back as well, in place
+/// of the generated `# sent_id` and `# text`; the `# text` is rebuilt, though, once words have
+/// been inserted or removed.
+///
+/// ```
+/// use agldt::conllu::write_sentence;
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut conllu = vec![];
+/// write_sentence(&treebank.sentences()[2], &mut conllu).unwrap();
+/// let conllu = String::from_utf8(conllu).unwrap();
+/// let mut lines = conllu.lines();
+/// assert_eq!(
+///     lines.next(),
+///     Some("# sent_id = urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.2@3")
+/// );
+/// assert!(lines.next().unwrap().starts_with("# text = οἱ δὲ ταῖς διαδοχαῖς"));
+/// // ἐν Σπάρτῃ: the noun heads the preposition.
+/// assert!(conllu.contains("6\tἐν\tἐν\tADP\tr--------\t_\t7\tcase\t"));
+/// assert!(conllu.contains("7\tΣπάρτῃ\tσπάρτη\tNOUN\tn-s---fd-\tCase=Dat|Gender=Fem|Number=Sing\t8\tobl\t"));
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`crate::tree::DependencyTree::new`] if the sentence is not a tree.
+pub fn write_sentence<W: Write>(sentence: &Sentence, writer: &mut W) -> Result<(), Error> {
+    if sentence.words.iter().any(|token| token.ud.is_some()) {
+        return write_columns(sentence, writer);
+    }
+    let nodes = convert(sentence)?;
+    let ids: HashMap<usize, usize> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| !node.removed)
+        .enumerate()
+        .map(|(id, (index, _))| (index + 1, id + 1))
+        .collect();
+    let words: Vec<&Node> = nodes.iter().filter(|node| !node.removed).collect();
+
+    let mut text = String::new();
+    for (i, word) in words.iter().enumerate() {
+        text.push_str(&word.token.form);
+        if space_after(word.token, words.get(i + 1).map(|next| next.token)) {
+            text.push(' ');
+        }
+    }
+    write_comments(sentence, text.trim_end(), writer)?;
+
+    for (i, word) in words.iter().enumerate() {
+        let postag = word.postag();
+        let mut misc = vec![];
+        if !space_after(word.token, words.get(i + 1).map(|next| next.token)) && i + 1 != words.len()
+        {
+            misc.push("SpaceAfter=No".to_string());
+        }
+        misc.extend(misc_entries(word.token));
+        let misc = if misc.is_empty() {
+            "_".to_string()
+        } else {
+            misc.join("|")
+        };
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t_\t{misc}",
+            i + 1,
+            word.token.form,
+            word.token.lemma.as_deref().unwrap_or("_"),
+            upos(word.token, postag.as_ref()),
+            word.token.postag.as_deref().unwrap_or("_"),
+            postag.as_ref().map_or_else(|| "_".to_string(), feats),
+            ids.get(&word.head).copied().unwrap_or(0),
+            word.deprel(),
+        )?;
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Writes the sentence with the [`UdColumns`] of its tokens, brought up to date with the edits
+/// made since they were read, see [`write_sentence`]. Artificial nodes are written as empty
+/// nodes numbered in order after the word they follow, e.g. `8.1` and `8.2`, whatever the
+/// suffixes of their `insertion_id`.
+fn write_columns<W: Write>(sentence: &Sentence, writer: &mut W) -> Result<(), Error> {
+    let tokens = sentence.tokens_in_text_order();
+    let multiword = |id: u32| {
+        sentence
+            .multiword_tokens
+            .iter()
+            .find(move |multiword| multiword.first == id)
+    };
+    let space_after = |misc: Option<&str>| {
+        !misc.is_some_and(|misc| misc.split('|').any(|item| item == "SpaceAfter=No"))
+    };
+    let mut text = String::new();
+    let mut within = 0;
+    for token in tokens.iter().filter(|token| !token.is_artificial()) {
+        if let Some(multiword) = multiword(token.id) {
+            text.push_str(&multiword.form);
+            if space_after(multiword.misc()) {
+                text.push(' ');
+            }
+            within = multiword.last;
+        } else if token.id > within {
+            text.push_str(&token.form);
+            if space_after(token.ud.as_ref().and_then(|ud| ud.misc.as_deref())) {
+                text.push(' ');
+            }
+        }
+    }
+    write_comments(sentence, text.trim_end(), writer)?;
+
+    let mut ids: HashMap<u32, String> = HashMap::from([(0, "0".to_string())]);
+    let mut empty_node = (0, 0);
+    for token in &tokens {
+        let id = match token.insertion_id.as_deref().and_then(split_insertion_id) {
+            Some((position, _)) => {
+                empty_node = match empty_node {
+                    (last, index) if last == position => (position, index + 1),
+                    _ => (position, 1),
+                };
+                format!("{position}.{}", empty_node.1)
+            }
+            None => token.id.to_string(),
+        };
+        ids.insert(token.id, id);
+    }
+    let columns = updated_columns(sentence)?;
+    for token in tokens {
+        if !token.is_artificial() {
+            if let Some(multiword) = multiword(token.id) {
+                writeln!(
+                    writer,
+                    "{}-{}\t{}\t{}",
+                    multiword.first,
+                    multiword.last,
+                    multiword.form,
+                    multiword.columns.join("\t")
+                )?;
+            }
+        }
+        let head = ids.get(&token.head).map_or("0", String::as_str);
+        let ud = &columns[&token.id];
+        let (head, deps) = if token.is_artificial() {
+            let deps = match ud.deps.as_deref() {
+                // Empty nodes are attached in `DEPS` only, their first head being theirs.
+                Some(deps) => {
+                    let (first, others) = match deps.split_once('|') {
+                        Some((first, others)) => (first, Some(others)),
+                        None => (deps, None),
+                    };
+                    match first.split_once(':') {
+                        Some((read, relation)) if read.parse().unwrap_or(0) != token.head => {
+                            let mut deps = format!("{head}:{relation}");
+                            if let Some(others) = others {
+                                deps.push('|');
+                                deps.push_str(others);
+                            }
+                            deps
+                        }
+                        _ => deps.to_string(),
+                    }
+                }
+                None => format!("{head}:dep"),
+            };
+            ("_", deps)
+        } else {
+            (head, ud.deps.clone().unwrap_or_else(|| "_".to_string()))
+        };
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{head}\t{}\t{deps}\t{}",
+            ids[&token.id],
+            token.form,
+            token.lemma.as_deref().unwrap_or("_"),
+            ud.upos,
+            ud.xpos.as_deref().unwrap_or("_"),
+            ud.feats.as_deref().unwrap_or("_"),
+            ud.deprel,
+            ud.misc.as_deref().unwrap_or("_"),
+        )?;
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// The UD columns of every token, by id: those read from CoNLL-U, but for the postag and
+/// relation edited since, and the columns of the tokens inserted without them.
+///
+/// A postag or a relation counts as edited when it is neither the one of the UD columns, with
+/// [`Mapping::Raw`], nor the one they map to, with [`Mapping::Agldt`]. Edited relations are
+/// given the `DEPREL` of the conversion of the sentence, the others keeping the relation they
+/// map to.
+fn updated_columns(sentence: &Sentence) -> Result<HashMap<u32, UdColumns>, Error> {
+    let mut mapped = sentence.words.clone();
+    for token in &mut mapped {
+        if let Some(ud) = &token.ud {
+            token.relation = if token.is_artificial() {
+                ud.deps
+                    .as_deref()
+                    .and_then(|deps| deps.split('|').next())
+                    .and_then(|dep| dep.split_once(':'))
+                    .map_or("_", |(_, relation)| relation)
+                    .to_string()
+            } else {
+                ud.deprel.clone()
+            };
+        }
+    }
+    map_to_agldt(&mut mapped);
+    let edited: Vec<(bool, bool)> = sentence
+        .words
+        .iter()
+        .zip(&mapped)
+        .map(|(token, mapped)| match &token.ud {
+            Some(ud) => (
+                token.postag.is_some() && token.postag != mapped.postag,
+                !token.is_artificial()
+                    && token.relation != ud.deprel
+                    && token.relation != mapped.relation,
+            ),
+            None => (true, !token.is_artificial()),
+        })
+        .collect();
+
+    let mut deprels: HashMap<u32, &'static str> = HashMap::new();
+    if edited.iter().any(|(_, relation)| *relation) {
+        let mut agldt = sentence.clone();
+        for ((token, mapped), (postag, relation)) in agldt.words.iter_mut().zip(mapped).zip(&edited)
+        {
+            if !postag {
+                token.postag = mapped.postag;
+            }
+            if !relation {
+                token.relation = mapped.relation;
+            }
+        }
+        deprels = convert(&agldt)?
+            .iter()
+            .filter(|node| !node.removed)
+            .map(|node| (node.token.id, node.deprel()))
+            .collect();
+    }
+
+    let mut columns = HashMap::new();
+    for (token, (postag_edited, relation_edited)) in sentence.words.iter().zip(edited) {
+        let mut ud = token.ud.clone().unwrap_or_else(|| UdColumns {
+            upos: "_".to_string(),
+            xpos: None,
+            feats: None,
+            deprel: "_".to_string(),
+            deps: None,
+            misc: Some(misc_entries(token).join("|")).filter(|misc| !misc.is_empty()),
+        });
+        if postag_edited {
+            let postag = token.postag().ok().flatten();
+            ud.upos = upos(token, postag.as_ref()).to_string();
+            ud.xpos.clone_from(&token.postag);
+            ud.feats = postag.as_ref().map(feats).filter(|feats| feats != "_");
+        }
+        if relation_edited {
+            ud.deprel = deprels.get(&token.id).copied().unwrap_or("dep").to_string();
+        }
+        columns.insert(token.id, ud);
+    }
+    Ok(columns)
+}
+
+/// The entries of `MISC` keeping the `cite` and the other attributes of the token.
+fn misc_entries(token: &Token) -> Vec<String> {
+    let mut misc = vec![];
+    if let Some(cite) = &token.cite {
+        misc.push(format!("Cite={cite}"));
+    }
+    for (name, value) in &token.attributes {
+        misc.push(format!("Attribute:{name}={}", escape_misc(value)));
+    }
+    misc
+}
+
+/// Writes the comments of the sentence: those read from CoNLL-U as they were found, preceded by
+/// a `# sent_id`, and followed by a `# text` giving `text`, when they lack them, and then the
+/// attribution and the attributes of the sentence.
+fn write_comments<W: Write>(sentence: &Sentence, text: &str, writer: &mut W) -> Result<(), Error> {
+    let mut comments = sentence.comments.clone();
+    if !comments
+        .iter()
+        .any(|comment| comment_key(comment) == "sent_id")
+    {
+        // After `# newdoc` and `# newpar`, which open a document or a paragraph.
+        let at = comments
+            .iter()
+            .take_while(|comment| comment_key(comment).starts_with("new"))
+            .count();
+        comments.insert(at, format!("# sent_id = {}", sentence.citation_id()));
+    }
+    if !comments
+        .iter()
+        .any(|comment| comment_key(comment) == "text")
+    {
+        let at = comments
+            .iter()
+            .position(|comment| comment_key(comment) == "sent_id")
+            .map_or(0, |at| at + 1);
+        comments.insert(at, format!("# text = {text}"));
+    }
+    for comment in comments {
+        writeln!(writer, "{comment}")?;
+    }
+    write_annotators(sentence, writer)?;
+    for (name, value) in &sentence.attributes {
+        write_comment(&format!("attribute {name}"), value, writer)?;
+    }
+    Ok(())
+}
+
+/// Writes `# key = value`, without trailing whitespace when the value is empty.
+fn write_comment<W: Write>(key: &str, value: &str, writer: &mut W) -> Result<(), Error> {
+    writeln!(writer, "{}", format!("# {key} = {value}").trim_end())?;
+    Ok(())
+}
+
+/// The key of a comment, e.g. `sent_id` for `# sent_id = 1`, or the whole comment if it has no
+/// value, e.g. `newpar` for `# newpar`.
+pub(crate) fn comment_key(comment: &str) -> &str {
+    let comment = comment.trim_start_matches('#');
+    comment
+        .split_once('=')
+        .map_or(comment, |(key, _)| key)
+        .trim()
+}
+
+/// Writes a `# primary` and a `# secondary` comment for each of those of the sentence, and an
+/// `# annotator` comment with the name of each annotator, followed by their other fields.
+fn write_annotators<W: Write>(sentence: &Sentence, writer: &mut W) -> Result<(), Error> {
+    for primary in &sentence.primary {
+        write_comment("primary", primary, writer)?;
+    }
+    for secondary in &sentence.secondary {
+        write_comment("secondary", secondary, writer)?;
+    }
+    for annotator in &sentence.annotators {
+        write_comment("annotator", annotator.name(), writer)?;
+        let fields = [
+            ("short", annotator.short()),
+            ("uri", annotator.uri()),
+            ("address", annotator.address()),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                write_comment(&format!("annotator {field}"), value, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Escapes a value for `MISC`, as UD does for `SpacesAfter`.
+fn escape_misc(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\p")
+}
+
+/// Reverses [`escape_misc`].
+fn unescape_misc(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('p') => unescaped.push('|'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Universal part of speech of the token.
+///
+/// Nouns whose lemma starts with an uppercase letter are proper nouns, and conjunctions
+/// introducing a clause (`AuxC`) are subordinating.
+#[must_use]
+pub fn upos(token: &Token, postag: Option<&Postag>) -> &'static str {
+    let Some(pos) = postag.and_then(|postag| postag.pos) else {
+        return "X";
+    };
+    match pos {
+        PartOfSpeech::Noun => {
+            let proper = token
+                .lemma
+                .as_deref()
+                .and_then(|lemma| lemma.chars().next())
+                .is_some_and(char::is_uppercase);
+            if proper {
+                "PROPN"
+            } else {
+                "NOUN"
+            }
+        }
+        PartOfSpeech::Verb | PartOfSpeech::Participle => "VERB",
+        PartOfSpeech::Adjective => "ADJ",
+        PartOfSpeech::Adverb => "ADV",
+        PartOfSpeech::Article => "DET",
+        PartOfSpeech::Particle => "PART",
+        PartOfSpeech::Conjunction => {
+            if token.relation().is_ok_and(|r| r.is(BaseRelation::AuxC)) {
+                "SCONJ"
+            } else {
+                "CCONJ"
+            }
+        }
+        PartOfSpeech::Preposition => "ADP",
+        PartOfSpeech::Pronoun => "PRON",
+        PartOfSpeech::Numeral => "NUM",
+        PartOfSpeech::Interjection | PartOfSpeech::Exclamation => "INTJ",
+        PartOfSpeech::Punctuation => "PUNCT",
+        PartOfSpeech::Irregular => "X",
+    }
+}
+
+/// Universal features of the postag, as the `FEATS` column: sorted by name and joined by `|`,
+/// or `_` if there are none.
+///
+/// ```
+/// use agldt::conllu::feats;
+///
+/// let postag = "v3saia---".parse().unwrap();
+/// assert_eq!(
+///     feats(&postag),
+///     "Aspect=Perf|Mood=Ind|Number=Sing|Person=3|Tense=Past|VerbForm=Fin|Voice=Act"
+/// );
+/// ```
+#[must_use]
+pub fn feats(postag: &Postag) -> String {
+    let mut feats: Vec<(&str, &str)> = vec![];
+    if let Some(person) = postag.person {
+        feats.push((
+            "Person",
+            match person {
+                Person::First => "1",
+                Person::Second => "2",
+                Person::Third => "3",
+            },
+        ));
+    }
+    if let Some(number) = postag.number {
+        feats.push((
+            "Number",
+            match number {
+                Number::Singular => "Sing",
+                Number::Plural => "Plur",
+                Number::Dual => "Dual",
+            },
+        ));
+    }
+    if let Some(tense) = postag.tense {
+        let (tense, aspect) = match tense {
+            Tense::Present => ("Pres", Some("Imp")),
+            Tense::Imperfect => ("Past", Some("Imp")),
+            Tense::Perfect => ("Pres", Some("Perf")),
+            Tense::Pluperfect => ("Pqp", Some("Perf")),
+            Tense::FuturePerfect => ("Fut", Some("Perf")),
+            Tense::Future => ("Fut", None),
+            Tense::Aorist => ("Past", Some("Perf")),
+        };
+        feats.push(("Tense", tense));
+        if let Some(aspect) = aspect {
+            feats.push(("Aspect", aspect));
+        }
+    }
+    if let Some(mood) = postag.mood {
+        let (mood, verb_form) = match mood {
+            Mood::Indicative => (Some("Ind"), "Fin"),
+            Mood::Subjunctive => (Some("Sub"), "Fin"),
+            Mood::Optative => (Some("Opt"), "Fin"),
+            Mood::Imperative => (Some("Imp"), "Fin"),
+            Mood::Infinitive => (None, "Inf"),
+            Mood::Participle => (None, "Part"),
+            Mood::Gerundive => (None, "Gdv"),
+            Mood::Gerund => (None, "Ger"),
+            Mood::Supine => (None, "Sup"),
+        };
+        if let Some(mood) = mood {
+            feats.push(("Mood", mood));
+        }
+        feats.push(("VerbForm", verb_form));
+    }
+    if let Some(voice) = postag.voice {
+        feats.push((
+            "Voice",
+            match voice {
+                Voice::Active => "Act",
+                Voice::Passive => "Pass",
+                Voice::Middle => "Mid",
+                Voice::MedioPassive => "Mid,Pass",
+            },
+        ));
+    }
+    if let Some(gender) = postag.gender {
+        feats.push((
+            "Gender",
+            match gender {
+                Gender::Masculine => "Masc",
+                Gender::Feminine => "Fem",
+                Gender::Neuter => "Neut",
+            },
+        ));
+    }
+    if let Some(case) = postag.case {
+        feats.push((
+            "Case",
+            match case {
+                Case::Nominative => "Nom",
+                Case::Genitive => "Gen",
+                Case::Dative => "Dat",
+                Case::Accusative => "Acc",
+                Case::Vocative => "Voc",
+                Case::Locative => "Loc",
+                Case::Ablative => "Abl",
+            },
+        ));
+    }
+    if let Some(degree) = postag.degree {
+        feats.push((
+            "Degree",
+            match degree {
+                Degree::Comparative => "Cmp",
+                Degree::Superlative => "Sup",
+            },
+        ));
+    }
+    if feats.is_empty() {
+        return "_".to_string();
+    }
+    feats.sort_by_key(|(name, _)| name.to_lowercase());
+    feats
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<String>>()
+        .join("|")
+}
+
+/// Whether the text has a space between `token` and the token that follows it.
+fn space_after(token: &Token, next: Option<&Token>) -> bool {
+    let Some(next) = next else {
+        return false;
+    };
+    !OPENING.contains(&token.form.as_str()) && !CLOSING.contains(&next.form.as_str())
+}
+
+/// A token of the sentence while it is restructured into Universal Dependencies.
+struct Node<'a> {
+    token: &'a Token,
+    /// Position of the head in the sentence, counting from 1, or `0` for the root.
+    head: usize,
+    depth: usize,
+    /// The relation of the token in the AGLDT annotation.
+    original: Option<Relation>,
+    /// The relation the token has in the UD tree, which it may have taken over from an
+    /// artificial node, a preposition or a coordinator.
+    function: Option<Relation>,
+    inherited: bool,
+    orphan: bool,
+    deprel: Option<&'static str>,
+    removed: bool,
+}
+
+impl Node<'_> {
+    fn postag(&self) -> Option<Postag> {
+        self.token.postag().ok().flatten()
+    }
+
+    fn pos(&self) -> Option<PartOfSpeech> {
+        self.postag().and_then(|postag| postag.pos)
+    }
+
+    fn is_punctuation(&self) -> bool {
+        self.pos() == Some(PartOfSpeech::Punctuation)
+    }
+
+    fn was(&self, bases: &[BaseRelation]) -> bool {
+        self.original
+            .as_ref()
+            .is_some_and(|relation| bases.contains(&relation.base))
+    }
+
+    fn deprel(&self) -> &'static str {
+        if self.head == 0 {
+            return "root";
+        }
+        if let Some(deprel) = self.deprel {
+            return deprel;
+        }
+        if self.is_punctuation() {
+            return "punct";
+        }
+        if self.orphan {
+            return "orphan";
+        }
+        let Some(function) = &self.function else {
+            return "dep";
+        };
+        let postag = self.postag().unwrap_or_default();
+        let verb = matches!(
+            postag.pos,
+            Some(PartOfSpeech::Verb | PartOfSpeech::Participle)
+        );
+        match function.base {
+            BaseRelation::Pred => "parataxis",
+            BaseRelation::Sbj if verb => "csubj",
+            BaseRelation::Sbj => "nsubj",
+            BaseRelation::Obj if verb => "ccomp",
+            BaseRelation::Obj if postag.case == Some(Case::Dative) => "iobj",
+            BaseRelation::Obj => "obj",
+            BaseRelation::Atr => match postag.pos {
+                Some(PartOfSpeech::Article | PartOfSpeech::Pronoun) => "det",
+                Some(PartOfSpeech::Adjective) => "amod",
+                Some(PartOfSpeech::Numeral) => "nummod",
+                Some(PartOfSpeech::Adverb) => "advmod",
+                _ if verb => "acl",
+                _ => "nmod",
+            },
+            BaseRelation::Adv => match postag.pos {
+                Some(PartOfSpeech::Noun | PartOfSpeech::Pronoun | PartOfSpeech::Numeral) => "obl",
+                _ if verb => "advcl",
+                _ => "advmod",
+            },
+            BaseRelation::Atv | BaseRelation::AtvV if verb => "advcl",
+            BaseRelation::Atv | BaseRelation::AtvV | BaseRelation::Pnom | BaseRelation::Ocomp => {
+                "xcomp"
+            }
+            BaseRelation::Coord | BaseRelation::Apos => "cc",
+            BaseRelation::AuxP => "case",
+            BaseRelation::AuxC => "mark",
+            BaseRelation::AuxR => "expl",
+            BaseRelation::AuxV => "aux",
+            BaseRelation::AuxX | BaseRelation::AuxG | BaseRelation::AuxK => "punct",
+            BaseRelation::AuxY if postag.pos == Some(PartOfSpeech::Conjunction) => "cc",
+            BaseRelation::AuxY | BaseRelation::AuxZ => "advmod",
+            BaseRelation::ExD => "dep",
+        }
+    }
+}
+
+/// Restructures the tokens of the sentence, in the order of the text, into a UD tree.
+fn convert(sentence: &Sentence) -> Result<Vec<Node<'_>>, Error> {
+    let tree = sentence.tree()?;
+    let tokens = sentence.tokens_in_text_order();
+    let positions: HashMap<u32, usize> = tokens
+        .iter()
+        .enumerate()
+        .map(|(index, token)| (token.id, index + 1))
+        .collect();
+    let mut nodes: Vec<Node> = tokens
+        .iter()
+        .map(|token| {
+            let relation = token.relation().ok();
+            Node {
+                token,
+                head: positions.get(&token.head).copied().unwrap_or(0),
+                depth: tree.depth(token.id).unwrap_or(0),
+                original: relation.clone(),
+                function: relation,
+                inherited: false,
+                orphan: false,
+                deprel: None,
+                removed: false,
+            }
+        })
+        .collect();
+    let deepest_first = |nodes: &[Node], keep: &dyn Fn(&Node) -> bool| {
+        let mut indices: Vec<usize> = (0..nodes.len()).filter(|&i| keep(&nodes[i])).collect();
+        indices.sort_by_key(|&i| std::cmp::Reverse(nodes[i].depth));
+        indices
+    };
+
+    // Artificial nodes give way to their first dependent, which is then the head of the others.
+    for a in deepest_first(&nodes, &|node| node.token.is_artificial()) {
+        nodes[a].removed = true;
+        let children = children(&nodes, a);
+        let Some(&promoted) = children
+            .iter()
+            .find(|&&c| !nodes[c].is_punctuation())
+            .or(children.first())
+        else {
+            continue;
+        };
+        nodes[promoted].head = nodes[a].head;
+        nodes[promoted].function = nodes[a].function.clone();
+        nodes[promoted].inherited = true;
+        nodes[promoted].orphan = nodes[a].orphan;
+        for c in children.into_iter().filter(|&c| c != promoted) {
+            nodes[c].head = promoted + 1;
+            nodes[c].orphan = true;
+        }
+    }
+
+    // The object of a preposition or subordinating conjunction heads it.
+    for p in deepest_first(&nodes, &|node| {
+        !node.removed && node.was(&[BaseRelation::AuxP, BaseRelation::AuxC])
+    }) {
+        let children = children(&nodes, p);
+        let object = children.iter().copied().find(|&c| {
+            !nodes[c].is_punctuation()
+                && !nodes[c].was(&[
+                    BaseRelation::AuxX,
+                    BaseRelation::AuxG,
+                    BaseRelation::AuxK,
+                    BaseRelation::AuxY,
+                    BaseRelation::AuxZ,
+                ])
+        });
+        let Some(object) = object.or(children.first().copied()) else {
+            continue;
+        };
+        take_over(&mut nodes, p, object);
+        nodes[p].head = object + 1;
+        nodes[p].deprel = Some(if nodes[p].was(&[BaseRelation::AuxP]) {
+            "case"
+        } else {
+            "mark"
+        });
+        for c in children.into_iter().filter(|&c| c != object) {
+            nodes[c].head = object + 1;
+        }
+    }
+
+    // The first member of a coordination or apposition heads the others and the coordinator.
+    for c in deepest_first(&nodes, &|node| {
+        !node.removed && node.was(&[BaseRelation::Coord, BaseRelation::Apos])
+    }) {
+        let apposition = nodes[c].was(&[BaseRelation::Apos]);
+        let children = children(&nodes, c);
+        let (members, others): (Vec<usize>, Vec<usize>) = children.into_iter().partition(|&m| {
+            nodes[m].function.as_ref().is_some_and(|relation| {
+                if apposition {
+                    relation.is_apposition()
+                } else {
+                    relation.is_coordinated()
+                }
+            })
+        });
+        let Some(&first) = members.first() else {
+            continue;
+        };
+        take_over(&mut nodes, c, first);
+        let following = |i: usize| {
+            members
+                .iter()
+                .copied()
+                .find(|&m| m > i)
+                .unwrap_or(members[members.len() - 1])
+        };
+        for &m in &members[1..] {
+            nodes[m].head = first + 1;
+            nodes[m].deprel = Some(if apposition { "appos" } else { "conj" });
+        }
+        // Punctuation and further conjunctions go with the member after them, while shared
+        // modifiers go with the first member.
+        for o in others {
+            let separator = nodes[o].is_punctuation()
+                || nodes[o].was(&[BaseRelation::AuxX, BaseRelation::AuxG, BaseRelation::AuxY]);
+            nodes[o].head = if separator {
+                following(o) + 1
+            } else {
+                first + 1
+            };
+        }
+        nodes[c].head = following(c) + 1;
+        nodes[c].orphan = false;
+        nodes[c].deprel = Some(if nodes[c].is_punctuation() {
+            "punct"
+        } else {
+            "cc"
+        });
+    }
+
+    // A single root, to which the other words attached to the root are attached.
+    let roots: Vec<usize> = (0..nodes.len())
+        .filter(|&i| !nodes[i].removed && nodes[i].head == 0)
+        .collect();
+    if let Some(&root) = roots
+        .iter()
+        .find(|&&r| !nodes[r].is_punctuation())
+        .or(roots.first())
+    {
+        for r in roots.into_iter().filter(|&r| r != root) {
+            nodes[r].head = root + 1;
+            if nodes[r].deprel.is_none() && !nodes[r].is_punctuation() {
+                nodes[r].deprel = Some("parataxis");
+            }
+        }
+    }
+    Ok(nodes)
+}
+
+/// Positions of the tokens attached to the token at `index`, in order.
+fn children(nodes: &[Node], index: usize) -> Vec<usize> {
+    (0..nodes.len())
+        .filter(|&i| !nodes[i].removed && nodes[i].head == index + 1)
+        .collect()
+}
+
+/// Moves the token at `to` to the place of the token at `from` in the tree, where it takes over
+/// its part in a coordination, as well as any function `from` took over itself.
+fn take_over(nodes: &mut [Node], from: usize, to: usize) {
+    nodes[to].head = nodes[from].head;
+    nodes[to].orphan = nodes[from].orphan;
+    let inherited = nodes[from].inherited;
+    let Some(source) = nodes[from].function.clone() else {
+        return;
+    };
+    let function = nodes[to]
+        .function
+        .get_or_insert_with(|| Relation::new(source.base));
+    if inherited {
+        function.base = source.base;
+    }
+    function.coordination = source.coordination;
+    function.apposition = source.apposition;
+    nodes[to].inherited |= inherited;
+}
+
+/// The columns of a CoNLL-U token that have no place in the AGLDT annotation, kept as found in
+/// the source. A column left empty (`_`) is `None`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct UdColumns {
+    pub(crate) upos: String,
+    pub(crate) xpos: Option<String>,
+    pub(crate) feats: Option<String>,
+    pub(crate) deprel: String,
+    pub(crate) deps: Option<String>,
+    pub(crate) misc: Option<String>,
+}
+
+impl UdColumns {
+    #[must_use]
+    pub fn upos(&self) -> &str {
+        &self.upos
+    }
+    #[must_use]
+    pub fn xpos(&self) -> Option<&str> {
+        self.xpos.as_deref()
+    }
+    #[must_use]
+    pub fn feats(&self) -> Option<&str> {
+        self.feats.as_deref()
+    }
+    /// The UD relation, e.g. `nsubj` or `obl:arg`, or `_` for an empty node.
+    #[must_use]
+    pub fn deprel(&self) -> &str {
+        &self.deprel
+    }
+    #[must_use]
+    pub fn deps(&self) -> Option<&str> {
+        self.deps.as_deref()
+    }
+    #[must_use]
+    pub fn misc(&self) -> Option<&str> {
+        self.misc.as_deref()
+    }
+}
+
+/// A multiword token of CoNLL-U, e.g. `1-2 populusque`, the form in the text of the words
+/// `first` to `last`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct MultiwordToken {
+    pub(crate) first: u32,
+    pub(crate) last: u32,
+    pub(crate) form: String,
+    /// The columns after `FORM`, from `LEMMA` to `MISC`, as found in the source.
+    pub(crate) columns: Vec<String>,
+}
+
+impl MultiwordToken {
+    /// Id of the first word of the token.
+    #[must_use]
+    pub fn first(&self) -> u32 {
+        self.first
+    }
+    /// Id of the last word of the token.
+    #[must_use]
+    pub fn last(&self) -> u32 {
+        self.last
+    }
+    #[must_use]
+    pub fn form(&self) -> &str {
+        &self.form
+    }
+    /// The `MISC` column, e.g. `SpaceAfter=No`, or `None` if it is empty (`_`).
+    #[must_use]
+    pub fn misc(&self) -> Option<&str> {
+        self.columns
+            .last()
+            .map(String::as_str)
+            .filter(|misc| *misc != "_")
+    }
+}
+
+/// How the columns of a CoNLL-U token are read into a [`Token`].
+///
+/// Either way, the UD columns are kept as [`UdColumns`]. The default is [`Mapping::Agldt`], as
+/// with the `--mapping` option of the command line tool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mapping {
+    /// Keeps the UD annotation as it is: the relation of the token is its `DEPREL`, and it has
+    /// no postag.
+    Raw,
+    /// Maps the UD annotation back to AGLDT postags and relation labels, see
+    /// [`postag_from_ud`] and [`relation_from_ud`]. The tree keeps the shape given by UD.
+    #[default]
+    Agldt,
+}
+
+/// Reads the sentences of a CoNLL-U file one at a time.
+///
+/// The id of a sentence, its `document_id` and its `subdoc` are read from `# sent_id`, when it
+/// has the shape written by [`write_sentence`], `<document_id>:<subdoc>@<id>`. Otherwise,
+/// sentences are numbered in order, the `document_id` is taken from `# newdoc id` and the
+/// `subdoc` is the `sent_id` itself.
+///
+/// Multiword tokens (`1-2`) are kept as [`MultiwordToken`]s, while empty nodes (`8.1`) are read
+/// as artificial nodes, attached to their first head in `DEPS`. The `# primary`, `# secondary`,
+/// `# annotator` and `# attribute` comments and the `Cite` and `Attribute` entries in `MISC`
+/// written by [`write_sentence`] are read back, and every other comment, including `# sent_id`
+/// and `# text`, is kept as it is in [`Sentence::comments`].
+///
+/// ```
+/// use agldt::conllu::{ConlluReader, Mapping};
+///
+/// let src = "\
+/// ## sent_id = 1
+/// ## text = μῆνιν ἄειδε
+/// 1\tμῆνιν\tμῆνις\tNOUN\t_\tCase=Acc|Gender=Fem|Number=Sing\t2\tobj\t_\t_
+/// 2\tἄειδε\tἀείδω\tVERB\t_\tMood=Imp|Number=Sing|Person=2|Tense=Pres|VerbForm=Fin|Voice=Act\t0\troot\t_\t_
+///
+/// ";
+/// let treebank = ConlluReader::new(src.as_bytes(), Mapping::Agldt)
+///     .into_treebank()
+///     .unwrap();
+/// let words = treebank.sentences()[0].words();
+/// assert_eq!(words[0].postag_str(), Some("n-s---fa-"));
+/// assert_eq!(words[0].relation_str(), "OBJ");
+/// assert_eq!(words[0].ud().unwrap().deprel(), "obj");
+/// assert_eq!(words[1].postag_str(), Some("v2spma---"));
+/// assert_eq!(words[1].relation_str(), "PRED");
+/// ```
+pub struct ConlluReader<R: BufRead> {
+    source: R,
+    mapping: Mapping,
+    header: Header,
+    line: u64,
+    document_id: String,
+    sentences: u32,
+    done: bool,
+}
+
+impl<R: BufRead> ConlluReader<R> {
+    #[must_use]
+    pub fn new(source: R, mapping: Mapping) -> Self {
+        Self {
+            source,
+            mapping,
+            header: Header::new(Monogr::new("", ""))
+                .with_annotation_scheme("Universal Dependencies"),
+            line: 0,
+            document_id: String::new(),
+            sentences: 0,
+            done: false,
+        }
+    }
+
+    /// A header for the treebank, which CoNLL-U files lack: it only gives the annotation scheme.
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the remaining sentences into a [`Treebank`] with [`ConlluReader::header`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return the first error met while reading the sentences, and
+    /// [`Error::InvalidSentence`] if two sentences share an id.
+    pub fn into_treebank(mut self) -> Result<Treebank, Error> {
+        let sentences = self.by_ref().collect::<Result<Vec<Sentence>, Error>>()?;
+        Treebank::builder(self.header).sentences(sentences).build()
+    }
+
+    fn malformed(&self, message: String) -> Error {
+        Error::MalformedConllu {
+            message,
+            location: Location {
+                position: Some(Position {
+                    line: self.line,
+                    column: 1,
+                }),
+                ..Location::default()
+            },
+        }
+    }
+
+    /// Reads the comments and token lines of the next sentence, with their line numbers.
+    fn next_block(&mut self) -> Result<Vec<(u64, String)>, Error> {
+        let mut block = vec![];
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.source.read_line(&mut line)? == 0 {
+                self.done = true;
+                return Ok(block);
+            }
+            self.line += 1;
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            if trimmed.trim().is_empty() {
+                if block.is_empty() {
+                    continue;
+                }
+                return Ok(block);
+            }
+            block.push((self.line, trimmed.to_string()));
+        }
+    }
+
+    fn read_sentence(&mut self, block: Vec<(u64, String)>) -> Result<Sentence, Error> {
+        self.sentences += 1;
+        let mut sentence = Sentence {
+            id: self.sentences,
+            document_id: self.document_id.clone(),
+            subdoc: String::new(),
+            attributes: BTreeMap::new(),
+            primary: vec![],
+            secondary: vec![],
+            annotators: vec![],
+            comments: vec![],
+            multiword_tokens: vec![],
+            words: vec![],
+        };
+        let mut empty_nodes: Vec<(u32, u32, Token)> = vec![];
+        for (line, text) in block {
+            self.line = line;
+            if let Some(comment) = text.strip_prefix('#') {
+                let (key, value) = comment.split_once('=').unwrap_or((comment, ""));
+                let value = value.trim();
+                match key.trim() {
+                    "newdoc id" => {
+                        self.document_id = value.to_string();
+                        sentence.document_id.clone_from(&self.document_id);
+                    }
+                    "sent_id" => read_sent_id(value, &mut sentence),
+                    "primary" => {
+                        sentence.primary.push(value.to_string());
+                        continue;
+                    }
+                    "secondary" => {
+                        sentence.secondary.push(value.to_string());
+                        continue;
+                    }
+                    "annotator" => {
+                        sentence.annotators.push(PersInfo::new(value));
+                        continue;
+                    }
+                    key => {
+                        if let Some(name) = key.strip_prefix("attribute ") {
+                            sentence
+                                .attributes
+                                .insert(name.trim().to_string(), value.to_string());
+                            continue;
+                        }
+                        let field = key.strip_prefix("annotator ").and_then(|field| {
+                            let annotator = sentence.annotators.last_mut()?;
+                            match field.trim() {
+                                "short" => Some(&mut annotator.short),
+                                "uri" => Some(&mut annotator.uri),
+                                "address" => Some(&mut annotator.address),
+                                _ => None,
+                            }
+                        });
+                        if let Some(field) = field {
+                            *field = Some(value.to_string());
+                            continue;
+                        }
+                    }
+                }
+                sentence.comments.push(text);
+                continue;
+            }
+            let columns: Vec<&str> = text.split('\t').collect();
+            if columns.len() != 10 {
+                return Err(self.malformed(format!("expected 10 columns, found {}", columns.len())));
+            }
+            let id = columns[0];
+            let number = |value: &str| {
+                value
+                    .parse::<u32>()
+                    .map_err(|_| self.malformed(format!("invalid id `{value}`")))
+            };
+            if let Some((first, last)) = id.split_once('-') {
+                sentence.multiword_tokens.push(MultiwordToken {
+                    first: number(first)?,
+                    last: number(last)?,
+                    form: columns[1].to_string(),
+                    columns: columns[2..]
+                        .iter()
+                        .map(|column| column.to_string())
+                        .collect(),
+                });
+                continue;
+            }
+            let optional = |column: &str| (column != "_").then(|| column.to_string());
+            let mut token = Token {
+                id: 0,
+                form: columns[1].to_string(),
+                lemma: optional(columns[2]),
+                postag: None,
+                insertion_id: None,
+                artificial: None,
+                relation: columns[7].to_string(),
+                head: 0,
+                cite: columns[9]
+                    .split('|')
+                    .find_map(|item| item.strip_prefix("Cite="))
+                    .map(str::to_string),
+                attributes: columns[9]
+                    .split('|')
+                    .filter_map(|item| item.strip_prefix("Attribute:"))
+                    .filter_map(|item| item.split_once('='))
+                    .map(|(name, value)| (name.to_string(), unescape_misc(value)))
+                    .collect(),
+                ud: Some(UdColumns {
+                    upos: columns[3].to_string(),
+                    xpos: optional(columns[4]),
+                    feats: optional(columns[5]),
+                    deprel: columns[7].to_string(),
+                    deps: optional(columns[8]),
+                    misc: optional(columns[9]),
+                }),
+            };
+            if let Some((position, index)) = id.split_once('.') {
+                // An empty node is attached, in the basic tree, to its first enhanced head.
+                let (head, relation) = columns[8]
+                    .split('|')
+                    .next()
+                    .and_then(|dep| dep.split_once(':'))
+                    .unwrap_or(("0", "_"));
+                token.head = head.parse().unwrap_or(0);
+                token.relation = relation.to_string();
+                empty_nodes.push((number(position)?, number(index)?, token));
+            } else {
+                token.id = number(id)?;
+                token.head = number(columns[6])
+                    .map_err(|_| self.malformed(format!("invalid head `{}`", columns[6])))?;
+                sentence.words.push(token);
+            }
+        }
+        let mut next_id = sentence
+            .words
+            .iter()
+            .map(|token| token.id)
+            .max()
+            .unwrap_or(0);
+        for (position, index, mut token) in empty_nodes {
+            next_id += 1;
+            token.id = next_id;
+            token.insertion_id = Some(format_insertion_id(position, index.saturating_sub(1)));
+            token.artificial = Some("elliptic".to_string());
+            sentence.words.push(token);
+        }
+        if self.mapping == Mapping::Agldt {
+            map_to_agldt(&mut sentence.words);
+        }
+        Ok(sentence)
+    }
+}
+
+impl<R: BufRead> Iterator for ConlluReader<R> {
+    type Item = Result<Sentence, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_block() {
+            Ok(block) if block.is_empty() => None,
+            Ok(block) => Some(
+                self.read_sentence(block)
+                    .map_err(|error| error.in_sentence(self.sentences)),
+            ),
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads a CoNLL-U file, see [`ConlluReader`].
+///
+/// # Errors
+///
+/// This function will return [`Error::MalformedConllu`] if a line cannot be read.
+pub fn read_treebank(src: &str, mapping: Mapping) -> Result<Treebank, Error> {
+    ConlluReader::new(src.as_bytes(), mapping).into_treebank()
+}
+
+/// Reads a `sent_id` written as `<document_id>:<subdoc>@<id>`, or `<document_id>@<id>`, keeping
+/// any other `sent_id` as the `subdoc`.
+fn read_sent_id(sent_id: &str, sentence: &mut Sentence) {
+    let Some((rest, id)) = sent_id
+        .rsplit_once('@')
+        .and_then(|(rest, id)| Some((rest, id.parse::<u32>().ok()?)))
+    else {
+        sentence.subdoc = sent_id.to_string();
+        return;
+    };
+    sentence.id = id;
+    match rest.rsplit_once(':') {
+        Some((document_id, subdoc)) if document_id.starts_with("urn:") => {
+            sentence.document_id = document_id.to_string();
+            sentence.subdoc = subdoc.to_string();
+        }
+        _ => sentence.document_id = rest.to_string(),
+    }
+}
+
+/// Fills in the postag and the AGLDT relation of every token from its UD columns.
+///
+/// A copula (`cop`) takes the relation of the nominal predicate, which becomes its `PNOM`.
+/// Conjuncts (`conj`) take the relation of the first conjunct, which both get the suffix `_CO`,
+/// as do appositions (`appos`) with `_AP`. Tokens whose label has no AGLDT counterpart keep it,
+/// see [`relation_from_ud`].
+fn map_to_agldt(words: &mut [Token]) {
+    let last = words
+        .iter()
+        .filter(|token| !token.is_artificial())
+        .map(|token| token.id)
+        .max();
+    let mut relations: Vec<Option<Relation>> = words
+        .iter()
+        .map(|token| {
+            let ud = token.ud.as_ref();
+            relation_from_ud(
+                &token.relation,
+                ud.map_or("_", |ud| ud.upos.as_str()),
+                &token.form,
+                Some(token.id) == last,
+            )
+        })
+        .collect();
+    let positions: HashMap<u32, usize> = words
+        .iter()
+        .enumerate()
+        .map(|(position, token)| (token.id, position))
+        .collect();
+    let kind = |token: &Token| {
+        token
+            .relation
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    for (position, token) in words.iter().enumerate() {
+        if kind(token) != "cop" {
+            continue;
+        }
+        if let Some(&head) = positions.get(&token.head) {
+            relations[position] = relations[head].take();
+            relations[head] = Some(Relation::new(BaseRelation::Pnom));
+        }
+    }
+    for (position, token) in words.iter().enumerate() {
+        let label = kind(token);
+        if label != "conj" && label != "appos" {
+            continue;
+        }
+        let Some(mut relation) = positions
+            .get(&token.head)
+            .and_then(|&head| relations[head].clone())
+        else {
+            continue;
+        };
+        if label == "conj" {
+            relation.coordination = true;
+        } else {
+            relation.apposition = true;
+        }
+        let head = positions[&token.head];
+        relations[head] = Some(relation.clone());
+        relations[position] = Some(relation);
+    }
+    for (token, relation) in words.iter_mut().zip(relations) {
+        if let Some(relation) = relation {
+            token.relation = relation.to_string();
+        }
+        let Some(ud) = &token.ud else {
+            continue;
+        };
+        token.postag = ud
+            .xpos
+            .as_deref()
+            .filter(|xpos| xpos.parse::<Postag>().is_ok())
+            .map(str::to_string)
+            .or_else(|| {
+                (ud.upos != "_")
+                    .then(|| postag_from_ud(&ud.upos, ud.feats.as_deref().unwrap_or("_")))
+                    .map(|postag| postag.to_string())
+            });
+    }
+}
+
+/// The AGLDT postag closest to a UD part of speech and features, reversing [`upos`] and
+/// [`feats`].
+///
+/// ```
+/// use agldt::conllu::postag_from_ud;
+///
+/// let postag = postag_from_ud(
+///     "VERB",
+///     "Aspect=Perf|Case=Gen|Gender=Masc|Number=Plur|Tense=Pres|VerbForm=Part|Voice=Act",
+/// );
+/// assert_eq!(postag.to_string(), "v-prpamg-");
+/// ```
+#[must_use]
+pub fn postag_from_ud(upos: &str, feats: &str) -> Postag {
+    let feats: HashMap<&str, &str> = feats
+        .split('|')
+        .filter_map(|feat| feat.split_once('='))
+        .collect();
+    let feat = |name: &str| feats.get(name).copied();
+    Postag {
+        pos: match upos {
+            "NOUN" | "PROPN" => Some(PartOfSpeech::Noun),
+            "VERB" | "AUX" => Some(PartOfSpeech::Verb),
+            "ADJ" => Some(PartOfSpeech::Adjective),
+            "ADV" => Some(PartOfSpeech::Adverb),
+            "DET" => Some(PartOfSpeech::Article),
+            "PART" => Some(PartOfSpeech::Particle),
+            "CCONJ" | "SCONJ" => Some(PartOfSpeech::Conjunction),
+            "ADP" => Some(PartOfSpeech::Preposition),
+            "PRON" => Some(PartOfSpeech::Pronoun),
+            "NUM" => Some(PartOfSpeech::Numeral),
+            "INTJ" => Some(PartOfSpeech::Interjection),
+            "PUNCT" => Some(PartOfSpeech::Punctuation),
+            "X" | "SYM" => Some(PartOfSpeech::Irregular),
+            _ => None,
+        },
+        person: match feat("Person") {
+            Some("1") => Some(Person::First),
+            Some("2") => Some(Person::Second),
+            Some("3") => Some(Person::Third),
+            _ => None,
+        },
+        number: match feat("Number") {
+            Some("Sing") => Some(Number::Singular),
+            Some("Plur") => Some(Number::Plural),
+            Some("Dual") => Some(Number::Dual),
+            _ => None,
+        },
+        tense: match (feat("Tense"), feat("Aspect")) {
+            (Some("Pres"), Some("Perf")) => Some(Tense::Perfect),
+            (Some("Pres"), _) => Some(Tense::Present),
+            (Some("Past"), Some("Imp")) => Some(Tense::Imperfect),
+            (Some("Past"), _) => Some(Tense::Aorist),
+            (Some("Pqp"), _) => Some(Tense::Pluperfect),
+            (Some("Fut"), Some("Perf")) => Some(Tense::FuturePerfect),
+            (Some("Fut"), _) => Some(Tense::Future),
+            _ => None,
+        },
+        mood: match (feat("Mood"), feat("VerbForm")) {
+            (Some("Ind"), _) => Some(Mood::Indicative),
+            (Some("Sub"), _) => Some(Mood::Subjunctive),
+            (Some("Opt"), _) => Some(Mood::Optative),
+            (Some("Imp"), _) => Some(Mood::Imperative),
+            (_, Some("Inf")) => Some(Mood::Infinitive),
+            (_, Some("Part")) => Some(Mood::Participle),
+            (_, Some("Gdv")) => Some(Mood::Gerundive),
+            (_, Some("Ger")) => Some(Mood::Gerund),
+            (_, Some("Sup")) => Some(Mood::Supine),
+            _ => None,
+        },
+        voice: match feat("Voice") {
+            Some("Act") => Some(Voice::Active),
+            Some("Pass") => Some(Voice::Passive),
+            Some("Mid") => Some(Voice::Middle),
+            Some("Mid,Pass") => Some(Voice::MedioPassive),
+            _ => None,
+        },
+        gender: match feat("Gender") {
+            Some("Masc") => Some(Gender::Masculine),
+            Some("Fem") => Some(Gender::Feminine),
+            Some("Neut") => Some(Gender::Neuter),
+            _ => None,
+        },
+        case: match feat("Case") {
+            Some("Nom") => Some(Case::Nominative),
+            Some("Gen") => Some(Case::Genitive),
+            Some("Dat") => Some(Case::Dative),
+            Some("Acc") => Some(Case::Accusative),
+            Some("Voc") => Some(Case::Vocative),
+            Some("Loc") => Some(Case::Locative),
+            Some("Abl") => Some(Case::Ablative),
+            _ => None,
+        },
+        degree: match feat("Degree") {
+            Some("Cmp") => Some(Degree::Comparative),
+            Some("Sup") => Some(Degree::Superlative),
+            _ => None,
+        },
+    }
+}
+
+/// The AGLDT relation closest to a UD relation, given the `UPOS` and the form of the token and
+/// whether it is the last word of the sentence, which tells final punctuation (`AuxK`) apart.
+///
+/// Only the label is mapped: conjuncts and appositions are given the relation of their head by
+/// [`ConlluReader`] with [`Mapping::Agldt`], and so is a copula (`cop`), whose head, the nominal
+/// predicate, becomes its `PNOM`.
+///
+/// Vocatives (`vocative`) are `ExD`, which AGLDT gives to words outside the clause, as are the
+/// dependents of an elided governor (`orphan`). The labels AGLDT has no counterpart for, `dep`,
+/// `dislocated` and `reparandum`, as well as `cop` and unknown labels, give `None`: the reader
+/// then keeps the UD label as the relation of the token.
+///
+/// ```
+/// use agldt::conllu::relation_from_ud;
+///
+/// let relation = |deprel, upos, form, last| {
+///     relation_from_ud(deprel, upos, form, last).map(|relation| relation.to_string())
+/// };
+/// assert_eq!(relation("nsubj:pass", "NOUN", "λόγος", false).as_deref(), Some("SBJ"));
+/// assert_eq!(relation("case", "ADP", "ἐν", false).as_deref(), Some("AuxP"));
+/// assert_eq!(relation("punct", "PUNCT", ".", true).as_deref(), Some("AuxK"));
+/// assert_eq!(relation("vocative", "NOUN", "ἄνδρες", false).as_deref(), Some("ExD"));
+/// assert_eq!(relation("dep", "NOUN", "λόγος", false), None);
+/// ```
+#[must_use]
+pub fn relation_from_ud(deprel: &str, upos: &str, form: &str, last: bool) -> Option<Relation> {
+    let base = match deprel.split(':').next().unwrap_or_default() {
+        "root" | "parataxis" => BaseRelation::Pred,
+        "nsubj" | "csubj" => BaseRelation::Sbj,
+        "obj" | "iobj" | "ccomp" => BaseRelation::Obj,
+        "xcomp" => BaseRelation::Pnom,
+        "obl" | "advcl" | "advmod" if upos == "PART" => BaseRelation::AuxZ,
+        "obl" | "advcl" | "advmod" => BaseRelation::Adv,
+        "nmod" | "amod" | "det" | "nummod" | "acl" | "fixed" | "flat" | "compound" | "list"
+        | "clf" | "goeswith" => BaseRelation::Atr,
+        "appos" => BaseRelation::Apos,
+        "conj" | "cc" => BaseRelation::Coord,
+        "case" => BaseRelation::AuxP,
+        "mark" => BaseRelation::AuxC,
+        "aux" => BaseRelation::AuxV,
+        "expl" => BaseRelation::AuxR,
+        "discourse" => BaseRelation::AuxY,
+        "punct" if last => BaseRelation::AuxK,
+        "punct" if form == "," => BaseRelation::AuxX,
+        "punct" => BaseRelation::AuxG,
+        "vocative" | "orphan" => BaseRelation::ExD,
+        _ => return None,
+    };
+    Some(Relation::new(base))
+}
@@ -0,0 +1,692 @@
+use crate::conllu::{comment_key, MultiwordToken};
+use crate::error::{Error, Location};
+use crate::features::Postag;
+use crate::parser::{
+    format_insertion_id, split_insertion_id, suffix_index, PersInfo, Sentence, Token, Treebank,
+};
+use crate::relation::Relation;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+impl Token {
+    pub fn set_form(&mut self, form: &str) {
+        self.form = form.to_string();
+    }
+
+    pub fn set_lemma(&mut self, lemma: Option<&str>) {
+        self.lemma = lemma.map(str::to_string);
+    }
+
+    pub fn set_cite(&mut self, cite: Option<&str>) {
+        self.cite = cite.map(str::to_string);
+    }
+
+    /// Replaces the postag of the token.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidPostag`] if the postag is not a valid AGLDT
+    /// postag, leaving the token unchanged.
+    pub fn set_postag(&mut self, postag: Option<&str>) -> Result<(), Error> {
+        if let Some(postag) = postag {
+            postag.parse::<Postag>().map_err(|e| e.in_word(self.id))?;
+        }
+        self.postag = postag.map(str::to_string);
+        Ok(())
+    }
+
+    /// Replaces the relation label of the token.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidRelation`] if the label does not follow the
+    /// AGLDT guidelines, leaving the token unchanged.
+    pub fn set_relation(&mut self, relation: &str) -> Result<(), Error> {
+        relation
+            .parse::<Relation>()
+            .map_err(|e| e.in_word(self.id))?;
+        self.relation = relation.to_string();
+        Ok(())
+    }
+}
+
+/// Editing of the tokens of a sentence.
+///
+/// Tokens are addressed by their `id`. Inserting or removing a token renumbers the tokens that
+/// follow it, together with every `head` and `insertion_id` referencing them, so that the ids
+/// stay consecutive and the sentence stays a tree.
+///
+/// ```
+/// use agldt::parser::{Token, Treebank};
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let mut treebank = Treebank::from_xml_str(&src).unwrap();
+/// let sentence = treebank.sentence_mut(3).unwrap();
+/// let artificial = sentence.token(27).unwrap().clone();
+///
+/// // A new article before `διαδοχαῖς` (4) becomes word 4, pushing the rest one place further.
+/// let article = Token::builder(1, "ταῖς")
+///     .lemma("ὁ")
+///     .postag("l-p---fd-")
+///     .relation("ATR")
+///     .head(5)
+///     .build()
+///     .unwrap();
+/// sentence.insert_token(4, article).unwrap();
+/// assert_eq!(sentence.token(5).unwrap().form(), "διαδοχαῖς");
+/// assert_eq!(sentence.token(28).unwrap().insertion_id(), Some("0027e"));
+///
+/// sentence.remove_token(4).unwrap();
+/// assert_eq!(sentence.token(27), Some(&artificial));
+/// assert!(sentence.tree().is_ok());
+/// ```
+impl Sentence {
+    /// Credits `annotator` with the annotation of the sentence, unless they already are. When
+    /// `primary`, their short name, or else their name, is also added to the main annotators,
+    /// otherwise to the secondary ones.
+    pub fn add_annotator(&mut self, annotator: PersInfo, primary: bool) {
+        let short = annotator.short().unwrap_or(annotator.name()).to_string();
+        let list = if primary {
+            &mut self.primary
+        } else {
+            &mut self.secondary
+        };
+        if !list.contains(&short) {
+            list.push(short);
+        }
+        if !self.annotators.contains(&annotator) {
+            self.annotators.push(annotator);
+        }
+    }
+
+    /// The token with the given `id`.
+    #[must_use]
+    pub fn token(&self, id: u32) -> Option<&Token> {
+        self.words.iter().find(|token| token.id == id)
+    }
+
+    /// The token with the given `id`, to be edited with the setters of [`Token`].
+    pub fn token_mut(&mut self, id: u32) -> Option<&mut Token> {
+        self.words.iter_mut().find(|token| token.id == id)
+    }
+
+    pub fn iter_tokens_mut(&mut self) -> std::slice::IterMut<'_, Token> {
+        self.words.iter_mut()
+    }
+
+    /// Attaches the token `id` to the token `head`, or to the root if `head` is `0`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NotFound`] if there is no token `id`,
+    /// [`Error::InvalidHead`] if there is no token `head`, and [`Error::HeadCycle`] if `head` is
+    /// the token itself or one of its descendants. The sentence is then left unchanged.
+    pub fn set_head(&mut self, id: u32, head: u32) -> Result<(), Error> {
+        if self.token(id).is_none() {
+            return Err(self.missing_token(id));
+        }
+        self.check_head(id, head)?;
+        let heads: HashMap<u32, u32> = self.words.iter().map(|t| (t.id, t.head)).collect();
+        let mut ancestor = head;
+        while ancestor != 0 {
+            if ancestor == id {
+                return Err(Error::HeadCycle {
+                    location: self.location(id),
+                });
+            }
+            ancestor = heads.get(&ancestor).copied().unwrap_or(0);
+        }
+        if let Some(token) = self.token_mut(id) {
+            token.head = head;
+        }
+        Ok(())
+    }
+
+    /// Inserts `token` as the word `id`, moving the word found there, and every token after it,
+    /// one id further.
+    ///
+    /// The `id` of `token` is replaced, while its `head` is read after the renumbering.
+    /// Artificial nodes are inserted with [`Sentence::insert_artificial`] instead.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidAttribute`] if `token` is an artificial node or
+    /// `id` is neither a word of the sentence nor the one after the last, and
+    /// [`Error::InvalidHead`] if the `head` of `token` references no token.
+    pub fn insert_token(&mut self, id: u32, mut token: Token) -> Result<(), Error> {
+        let last_word = self
+            .words
+            .iter()
+            .filter(|token| !token.is_artificial())
+            .map(|token| token.id)
+            .max()
+            .unwrap_or(0);
+        if token.is_artificial() || id == 0 || id > last_word + 1 {
+            let (attribute, value) = if token.is_artificial() {
+                ("artificial", token.artificial.clone().unwrap_or_default())
+            } else {
+                ("id", id.to_string())
+            };
+            return Err(Error::InvalidAttribute {
+                element: "word".to_string(),
+                attribute: attribute.to_string(),
+                value,
+                location: self.location(id),
+            });
+        }
+        let head = token.head;
+        let index = self
+            .words
+            .iter()
+            .position(|token| token.id >= id)
+            .unwrap_or(self.words.len());
+        renumber(&mut self.words, |k| if k >= id { k + 1 } else { k });
+        token.id = id;
+        token.head = 0;
+        self.words.insert(index, token);
+        if let Err(error) = self.check_head(id, head) {
+            self.words.remove(index);
+            renumber(&mut self.words, |k| if k > id { k - 1 } else { k });
+            return Err(error);
+        }
+        self.words[index].head = head;
+        renumber_multiword(
+            &mut self.multiword_tokens,
+            |k| if k >= id { k + 1 } else { k },
+        );
+        self.forget_text();
+        Ok(())
+    }
+
+    /// Inserts `token` as an artificial node right after the word `after`, or before the first
+    /// word if `after` is `0`, and returns its new id.
+    ///
+    /// The node takes the id after the last one of the sentence and the next free
+    /// `insertion_id`, e.g. `0026e`, then `0026f`. Its kind of artificial node is kept, if set,
+    /// and is otherwise `elliptic`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NotFound`] if there is no word `after`, and
+    /// [`Error::InvalidHead`] if the `head` of `token` references no token.
+    pub fn insert_artificial(&mut self, after: u32, mut token: Token) -> Result<u32, Error> {
+        if after != 0 && self.token(after).is_none_or(Token::is_artificial) {
+            return Err(self.missing_token(after));
+        }
+        let id = self.words.iter().map(|token| token.id).max().unwrap_or(0) + 1;
+        token.id = id;
+        self.check_head(id, token.head)?;
+        let index = self
+            .words
+            .iter()
+            .filter_map(|token| token.insertion_id.as_deref().and_then(split_insertion_id))
+            .filter(|(position, _)| *position == after)
+            .filter_map(|(_, suffix)| suffix_index(suffix))
+            .max()
+            .map_or(0, |last| last + 1);
+        token.insertion_id = Some(format_insertion_id(after, index));
+        token
+            .artificial
+            .get_or_insert_with(|| "elliptic".to_string());
+        self.words.push(token);
+        Ok(id)
+    }
+
+    /// Removes the token `id` and returns it, moving every token after it one id back.
+    ///
+    /// Artificial nodes inserted after a removed word are moved after the word before it, and
+    /// the enhanced dependencies on it, in `DEPS`, are dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NotFound`] if there is no token `id`, and
+    /// [`Error::InvalidSentence`] if another token is attached to it: those have to be attached
+    /// elsewhere first, e.g. with [`Sentence::set_head`].
+    pub fn remove_token(&mut self, id: u32) -> Result<Token, Error> {
+        let Some(index) = self.words.iter().position(|token| token.id == id) else {
+            return Err(self.missing_token(id));
+        };
+        if let Some(dependent) = self.words.iter().find(|token| token.head == id) {
+            return Err(Error::InvalidSentence {
+                reason: format!("word {} is attached to word {id}", dependent.id),
+                location: self.location(id),
+            });
+        }
+        let token = self.words.remove(index);
+        forget_deps(&mut self.words, id);
+        let moved = !token.is_artificial()
+            && self.words.iter().any(|token| {
+                token
+                    .insertion_id
+                    .as_deref()
+                    .and_then(split_insertion_id)
+                    .is_some_and(|(position, _)| position == id)
+            });
+        renumber(&mut self.words, |k| if k >= id { k - 1 } else { k });
+        if moved {
+            reletter(&mut self.words, id - 1);
+        }
+        if !token.is_artificial() {
+            self.multiword_tokens
+                .retain(|multiword| !(multiword.first..=multiword.last).contains(&id));
+            self.forget_text();
+        }
+        renumber_multiword(
+            &mut self.multiword_tokens,
+            |k| if k >= id { k - 1 } else { k },
+        );
+        Ok(token)
+    }
+
+    fn check_head(&self, id: u32, head: u32) -> Result<(), Error> {
+        if head == id {
+            return Err(Error::HeadCycle {
+                location: self.location(id),
+            });
+        }
+        if head != 0 && self.token(head).is_none() {
+            return Err(Error::InvalidHead {
+                head: head.to_string(),
+                location: self.location(id),
+            });
+        }
+        Ok(())
+    }
+
+    fn missing_token(&self, id: u32) -> Error {
+        Error::NotFound {
+            element: "word".to_string(),
+            id,
+            location: Location {
+                sentence_id: Some(self.id),
+                ..Location::default()
+            },
+        }
+    }
+
+    /// Drops the `# text` comment read from CoNLL-U once the words no longer match it, so that
+    /// it is rebuilt from them.
+    fn forget_text(&mut self) {
+        self.comments
+            .retain(|comment| comment_key(comment) != "text");
+    }
+
+    fn location(&self, word_id: u32) -> Location {
+        Location {
+            sentence_id: Some(self.id),
+            word_id: Some(word_id),
+            position: None,
+        }
+    }
+}
+
+/// Editing of the sentences of a treebank.
+///
+/// Sentences are addressed by their `id`. Splitting or merging sentences renumbers the tokens of
+/// the sentences involved, but leaves the ids of the other sentences as they are.
+///
+/// ```
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let original = Treebank::from_xml_str(&src).unwrap();
+/// let mut treebank = original.clone();
+///
+/// treebank.merge_sentences(3).unwrap();
+/// assert_eq!(treebank.sentences().len(), original.sentences().len() - 1);
+/// assert_eq!(treebank.sentence(3).unwrap().token(27).unwrap().form(), "Τίμαιος");
+/// assert!(treebank.sentence(4).is_none());
+///
+/// // The words split off form a new sentence, in the place of the one merged away.
+/// let id = treebank.split_sentence(3, 27).unwrap();
+/// let last = original.sentences().iter().map(|sentence| sentence.id()).max().unwrap();
+/// assert_eq!(id, last + 1);
+/// assert_eq!(treebank.sentences()[3].id(), id);
+/// assert_eq!(treebank.sentence(id).unwrap().words(), original.sentence(4).unwrap().words());
+/// assert_eq!(treebank.sentence(3), original.sentence(3));
+/// assert_eq!(treebank.sentence(5), original.sentence(5));
+///
+/// // Fixing a lemma across the whole treebank.
+/// for token in treebank.iter_tokens_mut() {
+///     if token.lemma() == Some("ὀ") {
+///         token.set_lemma(Some("ὁ"));
+///     }
+/// }
+/// assert!(treebank.iter_tokens_mut().all(|token| token.lemma() != Some("ὀ")));
+/// ```
+impl Treebank {
+    /// The sentence with the given `id`.
+    #[must_use]
+    pub fn sentence(&self, id: u32) -> Option<&Sentence> {
+        self.body
+            .sentences
+            .iter()
+            .find(|sentence| sentence.id == id)
+    }
+
+    /// The sentence with the given `id`, to be edited with the methods of [`Sentence`].
+    pub fn sentence_mut(&mut self, id: u32) -> Option<&mut Sentence> {
+        self.body
+            .sentences
+            .iter_mut()
+            .find(|sentence| sentence.id == id)
+    }
+
+    pub fn iter_tokens_mut(&mut self) -> impl Iterator<Item = &mut Token> {
+        self.body
+            .sentences
+            .iter_mut()
+            .flat_map(Sentence::iter_tokens_mut)
+    }
+
+    /// Splits the sentence `id` before its word `at` and returns the id of the new sentence,
+    /// the one after the last id of the treebank.
+    ///
+    /// The new sentence follows the sentence `id`. Artificial nodes go with the word they were
+    /// inserted after. Both sentences keep the annotators and the attributes of the original
+    /// one, but for the Arethusa `span`, which no longer gives their first and last words.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NotFound`] if there is no sentence `id` or no word
+    /// `at` in it, and [`Error::InvalidSentence`] if `at` is the first word or if a token is
+    /// attached to a token on the other side of the split.
+    pub fn split_sentence(&mut self, id: u32, at: u32) -> Result<u32, Error> {
+        let Some(index) = self.body.sentences.iter().position(|s| s.id == id) else {
+            return Err(missing_sentence(id));
+        };
+        let new_id = self.body.sentences.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        let sentence = &self.body.sentences[index];
+        if sentence.token(at).is_none_or(Token::is_artificial) {
+            return Err(sentence.missing_token(at));
+        }
+        let first_part: HashSet<u32> = sentence
+            .words
+            .iter()
+            .filter(|token| token.order_key().0 < at)
+            .map(|token| token.id)
+            .collect();
+        if first_part.is_empty() {
+            return Err(Error::InvalidSentence {
+                reason: "cannot split before the first word".to_string(),
+                location: sentence.location(at),
+            });
+        }
+        if let Some(multiword) = sentence
+            .multiword_tokens
+            .iter()
+            .find(|multiword| multiword.first < at && at <= multiword.last)
+        {
+            return Err(Error::InvalidSentence {
+                reason: format!(
+                    "the split falls within the multiword token {}-{}",
+                    multiword.first, multiword.last
+                ),
+                location: sentence.location(at),
+            });
+        }
+        if let Some(token) = sentence.words.iter().find(|token| {
+            token.head != 0 && first_part.contains(&token.id) != first_part.contains(&token.head)
+        }) {
+            return Err(Error::InvalidSentence {
+                reason: format!(
+                    "word {} is attached to word {} across the split",
+                    token.id, token.head
+                ),
+                location: sentence.location(token.id),
+            });
+        }
+
+        let sentence = &mut self.body.sentences[index];
+        let (first, second): (Vec<Token>, Vec<Token>) = std::mem::take(&mut sentence.words)
+            .into_iter()
+            .partition(|token| first_part.contains(&token.id));
+        let (second_multiword, first_multiword): (Vec<MultiwordToken>, Vec<MultiwordToken>) =
+            std::mem::take(&mut sentence.multiword_tokens)
+                .into_iter()
+                .partition(|multiword| multiword.first >= at);
+        let (words, ids) = consecutive(vec![first]);
+        sentence.words = words;
+        sentence.multiword_tokens = first_multiword;
+        renumber_multiword(&mut sentence.multiword_tokens, |k| {
+            ids[0].get(&k).copied().unwrap_or(k)
+        });
+        sentence.forget_text();
+        sentence.attributes.remove(SPAN);
+        let (words, ids) = consecutive(vec![second]);
+        let mut new = Sentence {
+            id: new_id,
+            document_id: sentence.document_id.clone(),
+            subdoc: sentence.subdoc.clone(),
+            attributes: sentence.attributes.clone(),
+            primary: sentence.primary.clone(),
+            secondary: sentence.secondary.clone(),
+            annotators: sentence.annotators.clone(),
+            comments: vec![],
+            multiword_tokens: second_multiword,
+            words,
+        };
+        renumber_multiword(&mut new.multiword_tokens, |k| {
+            ids[0].get(&k).copied().unwrap_or(k)
+        });
+        self.body.sentences.insert(index + 1, new);
+        Ok(new_id)
+    }
+
+    /// Merges the sentence `id` with the sentence that follows it, whose words are appended to
+    /// its own.
+    ///
+    /// The id of the following sentence is left unused. If the two sentences have different
+    /// `subdoc`s, the merged one spans both, e.g. `1.2-1.3`. The annotators of both sentences
+    /// are kept, and so are their other attributes, but for those with different values in
+    /// each sentence, which are dropped. The Arethusa `span`, e.g. `περὶ0:Λυκούργου0`, goes
+    /// from the first word of the sentence to the last word of the following one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::NotFound`] if there is no sentence `id`, and
+    /// [`Error::InvalidSentence`] if it is the last sentence of the treebank.
+    pub fn merge_sentences(&mut self, id: u32) -> Result<(), Error> {
+        let Some(index) = self.body.sentences.iter().position(|s| s.id == id) else {
+            return Err(missing_sentence(id));
+        };
+        if index + 1 == self.body.sentences.len() {
+            return Err(Error::InvalidSentence {
+                reason: "no sentence follows to merge with".to_string(),
+                location: Location {
+                    sentence_id: Some(id),
+                    ..Location::default()
+                },
+            });
+        }
+        let next = self.body.sentences.remove(index + 1);
+        let sentence = &mut self.body.sentences[index];
+        merge_attributes(&mut sentence.attributes, next.attributes);
+        if sentence.subdoc != next.subdoc {
+            let start = sentence.subdoc.split('-').next().unwrap_or_default();
+            let end = next.subdoc.rsplit('-').next().unwrap_or_default();
+            sentence.subdoc = format!("{start}-{end}");
+        }
+        let words = std::mem::take(&mut sentence.words);
+        let (words, ids) = consecutive(vec![words, next.words]);
+        sentence.words = words;
+        renumber_multiword(&mut sentence.multiword_tokens, |k| {
+            ids[0].get(&k).copied().unwrap_or(k)
+        });
+        let mut multiword_tokens = next.multiword_tokens;
+        renumber_multiword(&mut multiword_tokens, |k| {
+            ids[1].get(&k).copied().unwrap_or(k)
+        });
+        sentence.multiword_tokens.extend(multiword_tokens);
+        sentence.forget_text();
+        sentence.comments.extend(
+            next.comments
+                .into_iter()
+                .filter(|comment| !matches!(comment_key(comment), "sent_id" | "text")),
+        );
+        for (list, others) in [
+            (&mut sentence.primary, next.primary),
+            (&mut sentence.secondary, next.secondary),
+        ] {
+            for short in others {
+                if !list.contains(&short) {
+                    list.push(short);
+                }
+            }
+        }
+        for annotator in next.annotators {
+            if !sentence.annotators.contains(&annotator) {
+                sentence.annotators.push(annotator);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The attribute of an Arethusa sentence giving its first and last words, each followed by
+/// its number of occurrences before it, e.g. `περὶ0:Λυκούργου0`.
+const SPAN: &str = "span";
+
+/// Adds the attributes of the sentence following `attributes`, dropping those whose values
+/// differ and joining both spans.
+fn merge_attributes(attributes: &mut BTreeMap<String, String>, mut next: BTreeMap<String, String>) {
+    let span = attributes
+        .remove(SPAN)
+        .zip(next.remove(SPAN))
+        .and_then(|(first, last)| {
+            let (start, _) = first.split_once(':')?;
+            let (_, end) = last.rsplit_once(':')?;
+            Some(format!("{start}:{end}"))
+        });
+    for (name, value) in next {
+        match attributes.get(&name) {
+            Some(own) if *own != value => {
+                attributes.remove(&name);
+            }
+            Some(_) => {}
+            None => {
+                attributes.insert(name, value);
+            }
+        }
+    }
+    if let Some(span) = span {
+        attributes.insert(SPAN.to_string(), span);
+    }
+}
+
+fn missing_sentence(id: u32) -> Error {
+    Error::NotFound {
+        element: "sentence".to_string(),
+        id,
+        location: Location::default(),
+    }
+}
+
+/// Replaces the ids of the tokens, as well as the `head`, `insertion_id` and enhanced
+/// dependencies referencing them.
+fn renumber(words: &mut [Token], new_id: impl Fn(u32) -> u32) {
+    for token in words {
+        token.id = new_id(token.id);
+        if token.head != 0 {
+            token.head = new_id(token.head);
+        }
+        if let Some(ud) = &mut token.ud {
+            ud.deps = ud.deps.as_deref().map(|deps| renumber_deps(deps, &new_id));
+        }
+        if let Some(insertion_id) = token.insertion_id.as_deref() {
+            if let Some((position, suffix)) = split_insertion_id(insertion_id) {
+                let width = insertion_id.len() - suffix.len();
+                token.insertion_id = Some(format!("{:0width$}{suffix}", new_id(position)));
+            }
+        }
+    }
+}
+
+/// Replaces the heads of the enhanced dependencies of `DEPS`, e.g. `2:nsubj|4.1:obj`, an empty
+/// node such as `4.1` moving with the word it follows.
+fn renumber_deps(deps: &str, new_id: impl Fn(u32) -> u32) -> String {
+    deps.split('|')
+        .map(|dep| {
+            let Some((head, relation)) = dep.split_once(':') else {
+                return dep.to_string();
+            };
+            let (word, node) = head.split_once('.').unwrap_or((head, ""));
+            match word.parse::<u32>() {
+                Ok(word) if word != 0 => {
+                    let dot = if node.is_empty() { "" } else { "." };
+                    format!("{}{dot}{node}:{relation}", new_id(word))
+                }
+                _ => dep.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("|")
+}
+
+/// Drops the enhanced dependencies of `DEPS` on the word `id`.
+fn forget_deps(words: &mut [Token], id: u32) {
+    let head = id.to_string();
+    for ud in words.iter_mut().filter_map(|token| token.ud.as_mut()) {
+        let Some(deps) = &ud.deps else { continue };
+        let kept: Vec<&str> = deps
+            .split('|')
+            .filter(|dep| dep.split_once(':').is_none_or(|(word, _)| word != head))
+            .collect();
+        ud.deps = (!kept.is_empty()).then(|| kept.join("|"));
+    }
+}
+
+/// Replaces the ids of the first and last words of multiword tokens.
+fn renumber_multiword(multiword_tokens: &mut [MultiwordToken], new_id: impl Fn(u32) -> u32) {
+    for multiword in multiword_tokens {
+        multiword.first = new_id(multiword.first);
+        multiword.last = new_id(multiword.last);
+    }
+}
+
+/// Numbers the tokens of each group from 1 on, in a single sentence: the words of every group
+/// first, then their artificial nodes. Returns the tokens with the new id of every token of each
+/// group.
+fn consecutive(groups: Vec<Vec<Token>>) -> (Vec<Token>, Vec<HashMap<u32, u32>>) {
+    let mut next = 0;
+    let mut renumbered: Vec<Vec<Token>> = vec![vec![]; 2];
+    let mut groups: Vec<(Vec<Token>, HashMap<u32, u32>)> = groups
+        .into_iter()
+        .map(|group| (group, HashMap::new()))
+        .collect();
+    for artificial in [false, true] {
+        for (group, ids) in &mut groups {
+            for token in group.iter().filter(|t| t.is_artificial() == artificial) {
+                next += 1;
+                ids.insert(token.id, next);
+            }
+        }
+    }
+    let mut all_ids = vec![];
+    for (mut group, ids) in groups {
+        renumber(&mut group, |k| ids.get(&k).copied().unwrap_or(k));
+        for token in group {
+            renumbered[usize::from(token.is_artificial())].push(token);
+        }
+        all_ids.push(ids);
+    }
+    (renumbered.concat(), all_ids)
+}
+
+/// Gives the artificial nodes inserted after the word `position` the suffixes `e`, `f`, …, in
+/// their current order, see [`format_insertion_id`].
+fn reletter(words: &mut [Token], position: u32) {
+    let mut nodes: Vec<&mut Token> = words
+        .iter_mut()
+        .filter(|token| {
+            token
+                .insertion_id
+                .as_deref()
+                .and_then(split_insertion_id)
+                .is_some_and(|(p, _)| p == position)
+        })
+        .collect();
+    nodes.sort_by_key(|token| token.order_key());
+    for (index, node) in (0..).zip(nodes) {
+        node.insertion_id = Some(format_insertion_id(position, index));
+    }
+}
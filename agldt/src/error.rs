@@ -0,0 +1,266 @@
+use std::fmt::Display;
+
+/// Versions of the AGLDT `xml` format that can be read.
+pub const SUPPORTED_VERSIONS: [&str; 2] = ["2.0", "2.1"];
+
+/// Line and column in the source file, both counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: u64,
+    pub column: u64,
+}
+
+impl From<xml::common::TextPosition> for Position {
+    fn from(position: xml::common::TextPosition) -> Self {
+        Self {
+            line: position.row + 1,
+            column: position.column + 1,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Where in a treebank an error occurred.
+///
+/// Every field is optional, as not every error can be traced to a sentence, a word or a
+/// position in the source, e.g. when a [`crate::features::POSFeature`] is built by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+    pub sentence_id: Option<u32>,
+    pub word_id: Option<u32>,
+    pub position: Option<Position>,
+}
+
+impl Location {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sentence_id.is_none() && self.word_id.is_none() && self.position.is_none()
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = vec![];
+        if let Some(sentence_id) = &self.sentence_id {
+            parts.push(format!("sentence {sentence_id}"));
+        }
+        if let Some(word_id) = &self.word_id {
+            parts.push(format!("word {word_id}"));
+        }
+        if let Some(position) = &self.position {
+            parts.push(position.to_string());
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Errors raised while reading AGLDT data, or CoNLL-U and JSON data into the AGLDT model.
+#[derive(Debug)]
+pub enum Error {
+    /// The source could not be read.
+    Io(std::io::Error),
+    /// The source is not well formed `xml`.
+    MalformedXml { message: String, location: Location },
+    /// A line of a CoNLL-U file cannot be read, e.g. it does not have ten columns.
+    MalformedConllu { message: String, location: Location },
+    /// A JSON document, or a line of a JSON Lines file, does not follow the schema of
+    /// [`crate::json`].
+    MalformedJson { message: String, location: Location },
+    /// An element was found where another one was expected.
+    UnexpectedElement {
+        expected: String,
+        found: String,
+        location: Location,
+    },
+    /// A required child element is missing.
+    MissingElement {
+        parent: String,
+        element: String,
+        location: Location,
+    },
+    /// A required attribute is missing.
+    MissingAttribute {
+        element: String,
+        attribute: String,
+        location: Location,
+    },
+    /// An attribute has a value that cannot be read, e.g. a non-numeric `id`.
+    InvalidAttribute {
+        element: String,
+        attribute: String,
+        value: String,
+        location: Location,
+    },
+    /// A postag string, or a single postag feature, is not valid.
+    InvalidPostag {
+        postag: String,
+        reason: String,
+        location: Location,
+    },
+    /// A relation label does not follow the AGLDT guidelines.
+    InvalidRelation {
+        relation: String,
+        reason: String,
+        location: Location,
+    },
+    /// The `head` of a word does not reference a word.
+    InvalidHead { head: String, location: Location },
+    /// Following the `head` of a word never reaches the root of the sentence.
+    HeadCycle { location: Location },
+    /// Two words of a sentence share the same `id`.
+    DuplicateId { location: Location },
+    /// No sentence or word has the `id` given to an editing method, see [`crate::edit`].
+    NotFound {
+        element: String,
+        id: u32,
+        location: Location,
+    },
+    /// A sentence built by hand is not a well-formed dependency tree, or shares its id with
+    /// another sentence.
+    InvalidSentence { reason: String, location: Location },
+    /// The treebank declares a version not in [`SUPPORTED_VERSIONS`].
+    UnsupportedVersion { version: String, location: Location },
+}
+
+/// Matches the `location` of every variant but [`Error::Io`], by reference or by mutable
+/// reference depending on `$error`, so that variants are listed only once.
+macro_rules! match_location {
+    ($error:expr) => {
+        match $error {
+            Error::Io(_) => None,
+            Error::MalformedXml { location, .. }
+            | Error::MalformedConllu { location, .. }
+            | Error::MalformedJson { location, .. }
+            | Error::UnexpectedElement { location, .. }
+            | Error::MissingElement { location, .. }
+            | Error::MissingAttribute { location, .. }
+            | Error::InvalidAttribute { location, .. }
+            | Error::InvalidPostag { location, .. }
+            | Error::InvalidRelation { location, .. }
+            | Error::InvalidHead { location, .. }
+            | Error::HeadCycle { location }
+            | Error::DuplicateId { location }
+            | Error::NotFound { location, .. }
+            | Error::InvalidSentence { location, .. }
+            | Error::UnsupportedVersion { location, .. } => Some(location),
+        }
+    };
+}
+
+impl Error {
+    /// Location where the error occurred, if any.
+    #[must_use]
+    pub fn location(&self) -> Option<&Location> {
+        match_location!(self)
+    }
+
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match_location!(self)
+    }
+
+    /// Attaches the id of the sentence where the error occurred, unless one is already set.
+    #[must_use]
+    pub fn in_sentence(mut self, sentence_id: u32) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.sentence_id.get_or_insert(sentence_id);
+        }
+        self
+    }
+
+    /// Attaches the id of the word where the error occurred, unless one is already set.
+    #[must_use]
+    pub fn in_word(mut self, word_id: u32) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.word_id.get_or_insert(word_id);
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "unable to read source: {error}")?,
+            Self::MalformedXml { message, .. } => write!(f, "malformed xml: {message}")?,
+            Self::MalformedConllu { message, .. } => write!(f, "malformed CoNLL-U: {message}")?,
+            Self::MalformedJson { message, .. } => write!(f, "malformed JSON: {message}")?,
+            Self::UnexpectedElement {
+                expected, found, ..
+            } => write!(f, "expected <{expected}>, found <{found}>")?,
+            Self::MissingElement {
+                parent, element, ..
+            } => write!(f, "missing element <{element}> in <{parent}>")?,
+            Self::MissingAttribute {
+                element, attribute, ..
+            } => write!(f, "missing attribute `{attribute}` in <{element}>")?,
+            Self::InvalidAttribute {
+                element,
+                attribute,
+                value,
+                ..
+            } => write!(
+                f,
+                "invalid value `{value}` for attribute `{attribute}` in <{element}>"
+            )?,
+            Self::InvalidPostag { postag, reason, .. } => {
+                write!(f, "invalid postag `{postag}`: {reason}")?;
+            }
+            Self::InvalidRelation {
+                relation, reason, ..
+            } => write!(f, "invalid relation `{relation}`: {reason}")?,
+            Self::InvalidHead { head, .. } => write!(f, "invalid head `{head}`")?,
+            Self::HeadCycle { .. } => write!(f, "the heads of the word form a cycle")?,
+            Self::DuplicateId { .. } => write!(f, "another word has the same id")?,
+            Self::NotFound { element, id, .. } => write!(f, "no <{element}> with id {id}")?,
+            Self::InvalidSentence { reason, .. } => write!(f, "invalid sentence: {reason}")?,
+            Self::UnsupportedVersion { version, .. } => write!(
+                f,
+                "unsupported version `{version}`, expected one of {SUPPORTED_VERSIONS:?}"
+            )?,
+        }
+        match self.location() {
+            Some(location) if !location.is_empty() => write!(f, " ({location})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<xml::reader::Error> for Error {
+    fn from(error: xml::reader::Error) -> Self {
+        use xml::common::Position as _;
+        use xml::reader::ErrorKind;
+        let message = match error.kind() {
+            ErrorKind::Io(io) => return Self::Io(std::io::Error::new(io.kind(), io.to_string())),
+            ErrorKind::Syntax(message) => message.to_string(),
+            ErrorKind::Utf8(utf8) => utf8.to_string(),
+            ErrorKind::UnexpectedEof => "unexpected end of file".to_string(),
+        };
+        Self::MalformedXml {
+            message,
+            location: Location {
+                position: Some(error.position().into()),
+                ..Location::default()
+            },
+        }
+    }
+}
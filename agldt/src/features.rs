@@ -1,24 +1,27 @@
-use std::error::Error;
+use crate::error::{Error, Location};
+use agldt_derive::PostagFeature;
 use std::fmt::Display;
+use std::str::FromStr;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct POSFeature {
     index: u8,
     char: char,
 }
 
 impl POSFeature {
-    /// .
-    ///
-    /// # Panics
-    ///
-    /// Panics if index > 8.
+    /// Builds the feature found at position `index` of a postag string.
     ///
     /// # Errors
     ///
-    /// This function will return an error if TODO.
-    pub fn new(index: u8, char: char) -> Result<Self, Box<dyn Error>> {
-        if index > 9 {
-            panic!("Index out of bounds, max = 8");
+    /// This function will return [`Error::InvalidPostag`] if `index` > 8.
+    pub fn new(index: u8, char: char) -> Result<Self, Error> {
+        if index > 8 {
+            Err(Error::InvalidPostag {
+                postag: char.to_string(),
+                reason: format!("index {index} out of bounds, max = 8"),
+                location: Location::default(),
+            })
         } else {
             Ok(Self { index, char })
         }
@@ -39,7 +42,398 @@ impl Display for POSFeature {
     }
 }
 
-pub trait PostagFeature {
+pub trait PostagFeature: Sized {
+    /// Position of the feature in the postag string.
+    const INDEX: u8;
+
     fn to_agldt_postag(&self) -> POSFeature;
     fn to_string(&self) -> String;
+
+    /// Reads the feature from the character used for it in the postag string.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidPostag`] if no variant is represented by
+    /// `char`.
+    fn from_agldt_postag(char: char) -> Result<Self, Error>;
+
+    /// Reads the feature at the position [`PostagFeature::INDEX`] of a full postag string,
+    /// returning `None` when the position is empty (`-`) and no variant is represented by `-`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidPostag`] if the postag is too short or if
+    /// its character at [`PostagFeature::INDEX`] represents no variant.
+    fn from_postag_str(postag: &str) -> Result<Option<Self>, Error> {
+        let Some(char) = postag.chars().nth(usize::from(Self::INDEX)) else {
+            return Err(Error::InvalidPostag {
+                postag: postag.to_string(),
+                reason: format!("expected at least {} positions", Self::INDEX + 1),
+                location: Location::default(),
+            });
+        };
+        match Self::from_agldt_postag(char) {
+            Ok(feature) => Ok(Some(feature)),
+            Err(_) if char == '-' => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// A feature spread over more than one position of the postag, derived with
+/// `#[derive(PostagFeature)]` and `#[complexfeature(true)]`.
+pub trait ComplexPostagFeature: Sized {
+    /// Positions of the postag used by any of the variants.
+    const INDICES: &'static [u8];
+
+    /// Characters required by the variant, with their positions.
+    fn to_agldt_postags(&self) -> Vec<POSFeature>;
+
+    /// Finds the variant whose rules all hold for `features`, trying the most specific
+    /// variants first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidPostag`] if no variant matches `features`.
+    fn from_agldt_postags(features: &[POSFeature]) -> Result<Self, Error>;
+
+    /// Writes the variant as a postag string, leaving the positions it does not use empty.
+    fn to_postag_string(&self) -> String {
+        let mut chars = ['-'; 9];
+        for feature in self.to_agldt_postags() {
+            chars[usize::from(feature.index())] = feature.char();
+        }
+        chars.iter().collect()
+    }
+
+    /// Reads the feature from a full postag string, returning `None` if no variant matches it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::InvalidPostag`] if the postag does not have exactly
+    /// nine positions.
+    fn from_postag_str(postag: &str) -> Result<Option<Self>, Error> {
+        let chars: Vec<char> = postag.chars().collect();
+        if chars.len() != 9 {
+            return Err(Error::InvalidPostag {
+                postag: postag.to_string(),
+                reason: format!("expected 9 positions, found {}", chars.len()),
+                location: Location::default(),
+            });
+        }
+        let features = (0u8..)
+            .zip(chars)
+            .map(|(index, char)| POSFeature { index, char })
+            .collect::<Vec<POSFeature>>();
+        Ok(Self::from_agldt_postags(&features).ok())
+    }
+}
+
+/// Error for a character that represents no variant of the feature `name`.
+///
+/// Used by the code generated by `#[derive(PostagFeature)]`.
+#[doc(hidden)]
+#[must_use]
+pub fn unknown_feature(name: &str, index: u8, char: char) -> Error {
+    Error::InvalidPostag {
+        postag: char.to_string(),
+        reason: format!("unknown character for {name} at position {index}"),
+        location: Location::default(),
+    }
+}
+
+/// Error for a set of features matching no variant of the complex feature `name`.
+///
+/// Used by the code generated by `#[derive(PostagFeature)]`.
+#[doc(hidden)]
+#[must_use]
+pub fn unmatched_features(name: &str, features: &[POSFeature]) -> Error {
+    let mut chars = ['-'; 9];
+    for feature in features {
+        if let Some(c) = chars.get_mut(usize::from(feature.index())) {
+            *c = feature.char();
+        }
+    }
+    Error::InvalidPostag {
+        postag: chars.iter().collect(),
+        reason: format!("no variant of {name} matches"),
+        location: Location::default(),
+    }
+}
+
+/// Error for a [`POSFeature`] found at another position than the one of the feature `name`.
+///
+/// Used by the code generated by `#[derive(PostagFeature)]`.
+#[doc(hidden)]
+#[must_use]
+pub fn misplaced_feature(name: &str, index: u8, feature: POSFeature) -> Error {
+    Error::InvalidPostag {
+        postag: feature.char().to_string(),
+        reason: format!(
+            "{name} is found at position {index}, not {}",
+            feature.index()
+        ),
+        location: Location::default(),
+    }
+}
+
+// The feature enums below follow the tagset of AGLDT 2.1 (`TAGSETS.xml`, distributed with the
+// treebank), with the additions of the Latin Dependency Treebank (gerund, gerundive, supine
+// and ablative). Each variant maps to the character used at the position `#[postagindex]` of
+// the postag string.
+
+/// Part of speech, position 0 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(0)]
+pub enum PartOfSpeech {
+    Noun,
+    Verb,
+    Adjective,
+    #[postag('d')]
+    Adverb,
+    #[postag('l')]
+    Article,
+    #[postag('g')]
+    Particle,
+    Conjunction,
+    #[postag('r')]
+    Preposition,
+    Pronoun,
+    #[postag('m')]
+    Numeral,
+    Interjection,
+    Exclamation,
+    /// Used for participles by older releases, which now tag them as verbs with the mood `p`.
+    #[postag('t')]
+    Participle,
+    #[postag('u')]
+    Punctuation,
+    #[postag('x')]
+    Irregular,
+}
+
+/// Person, position 1 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(1)]
+pub enum Person {
+    #[postag('1')]
+    First,
+    #[postag('2')]
+    Second,
+    #[postag('3')]
+    Third,
+}
+
+/// Number, position 2 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(2)]
+pub enum Number {
+    Singular,
+    Plural,
+    Dual,
+}
+
+/// Tense, position 3 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(3)]
+pub enum Tense {
+    Present,
+    Imperfect,
+    #[postag('r')]
+    Perfect,
+    #[postag('l')]
+    Pluperfect,
+    #[postag('t')]
+    FuturePerfect,
+    Future,
+    Aorist,
+}
+
+/// Mood, position 4 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(4)]
+pub enum Mood {
+    Indicative,
+    Subjunctive,
+    Optative,
+    #[postag('n')]
+    Infinitive,
+    #[postag('m')]
+    Imperative,
+    Participle,
+    /// Latin only.
+    Gerundive,
+    /// Latin only.
+    #[postag('d')]
+    Gerund,
+    /// Latin only.
+    #[postag('u')]
+    Supine,
+}
+
+/// Voice, position 5 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(5)]
+pub enum Voice {
+    Active,
+    Passive,
+    Middle,
+    #[postag('e')]
+    MedioPassive,
+}
+
+/// Gender, position 6 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(6)]
+pub enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+}
+
+/// Case, position 7 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(7)]
+pub enum Case {
+    Nominative,
+    Genitive,
+    Dative,
+    Accusative,
+    Vocative,
+    Locative,
+    /// Latin only.
+    #[postag('b')]
+    Ablative,
+}
+
+/// Degree, position 8 of the postag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PostagFeature)]
+#[postagindex(8)]
+pub enum Degree {
+    Comparative,
+    Superlative,
+}
+
+/// The nine positions of an AGLDT postag, e.g. `v-prpemg-` for λελυμένων.
+///
+/// Each position holds `None` when it is empty (`-`) in the postag string.
+///
+/// ```
+/// use agldt::features::{Case, Gender, Mood, PartOfSpeech, Postag};
+///
+/// let postag: Postag = "v-prpemg-".parse().unwrap();
+/// assert_eq!(postag.pos, Some(PartOfSpeech::Verb));
+/// assert_eq!(postag.mood, Some(Mood::Participle));
+/// assert_eq!(postag.gender, Some(Gender::Masculine));
+/// assert_eq!(postag.case, Some(Case::Genitive));
+/// assert_eq!(postag.person, None);
+/// assert_eq!(postag.to_string(), "v-prpemg-");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Postag {
+    pub pos: Option<PartOfSpeech>,
+    pub person: Option<Person>,
+    pub number: Option<Number>,
+    pub tense: Option<Tense>,
+    pub mood: Option<Mood>,
+    pub voice: Option<Voice>,
+    pub gender: Option<Gender>,
+    pub case: Option<Case>,
+    pub degree: Option<Degree>,
+}
+
+/// Names of the nine positions of the postag, as used in error messages.
+pub const POSITIONS: [&str; 9] = [
+    "part of speech",
+    "person",
+    "number",
+    "tense",
+    "mood",
+    "voice",
+    "gender",
+    "case",
+    "degree",
+];
+
+impl Postag {
+    /// Features set in the postag, in the order of their positions.
+    #[must_use]
+    pub fn features(&self) -> Vec<POSFeature> {
+        let features: [Option<POSFeature>; 9] = [
+            self.pos.map(|f| f.to_agldt_postag()),
+            self.person.map(|f| f.to_agldt_postag()),
+            self.number.map(|f| f.to_agldt_postag()),
+            self.tense.map(|f| f.to_agldt_postag()),
+            self.mood.map(|f| f.to_agldt_postag()),
+            self.voice.map(|f| f.to_agldt_postag()),
+            self.gender.map(|f| f.to_agldt_postag()),
+            self.case.map(|f| f.to_agldt_postag()),
+            self.degree.map(|f| f.to_agldt_postag()),
+        ];
+        features.into_iter().flatten().collect()
+    }
+
+    /// Feature at position `index`, if set.
+    #[must_use]
+    pub fn feature(&self, index: u8) -> Option<POSFeature> {
+        self.features().into_iter().find(|f| f.index() == index)
+    }
+}
+
+impl FromStr for Postag {
+    type Err = Error;
+
+    /// Parses a nine character postag, where `-` marks an empty position.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        let invalid = |reason: String| Error::InvalidPostag {
+            postag: s.to_string(),
+            reason,
+            location: Location::default(),
+        };
+        if chars.len() != 9 {
+            return Err(invalid(format!(
+                "expected 9 positions, found {}",
+                chars.len()
+            )));
+        }
+
+        fn position<T: PostagFeature>(chars: &[char]) -> Result<Option<T>, (usize, char)> {
+            let index = usize::from(T::INDEX);
+            match chars[index] {
+                '-' => Ok(None),
+                c => T::from_agldt_postag(c).map(Some).map_err(|_| (index, c)),
+            }
+        }
+
+        let parse = || -> Result<Self, (usize, char)> {
+            Ok(Self {
+                pos: position(&chars)?,
+                person: position(&chars)?,
+                number: position(&chars)?,
+                tense: position(&chars)?,
+                mood: position(&chars)?,
+                voice: position(&chars)?,
+                gender: position(&chars)?,
+                case: position(&chars)?,
+                degree: position(&chars)?,
+            })
+        };
+        parse().map_err(|(index, c)| {
+            invalid(format!(
+                "unknown character `{c}` for {} at position {index}",
+                POSITIONS[index]
+            ))
+        })
+    }
+}
+
+impl Display for Postag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut chars = ['-'; 9];
+        for feature in self.features() {
+            chars[usize::from(feature.index())] = feature.char();
+        }
+        write!(f, "{}", chars.iter().collect::<String>())
+    }
 }
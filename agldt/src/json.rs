@@ -0,0 +1,638 @@
+use crate::conllu::{MultiwordToken, UdColumns};
+use crate::error::{Error, Location, Position};
+use crate::parser::{
+    BiblScope, BiblStruct, EditionStmt, FileDesc, Header, Imprint, Monogr, PersInfo, Publication,
+    RespStmt, Sentence, Token, Treebank,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+/// Version of the JSON schema written by [`write_treebank`], kept in its `schema` field.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The treebank, as written to and read from JSON.
+#[derive(Serialize, Deserialize)]
+struct TreebankJson {
+    schema: u32,
+    version: String,
+    xml_lang: String,
+    cts: String,
+    header: HeaderJson,
+    sentences: Vec<SentenceJson>,
+}
+
+/// The `<header>`, without the `<fileDesc>`, `<editionStmt>` and `<biblStruct>` wrappers.
+#[derive(Serialize, Deserialize)]
+struct HeaderJson {
+    release_date: String,
+    annotation_date: String,
+    annotation_scheme: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resp_stmts: Vec<RespStmtJson>,
+    monogr: MonogrJson,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encoding_desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile_desc: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RespStmtJson {
+    resp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pers_name: Option<PersonJson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersonJson {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    short: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MonogrJson {
+    author: String,
+    title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resp_stmts: Vec<RespStmtJson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    imprint: Option<ImprintJson>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    bibl_scopes: Vec<BiblScopeJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ImprintJson {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    publications: Vec<PublicationJson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PublicationJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub_place: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    publisher: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BiblScopeJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope_type: Option<String>,
+    value: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SentenceJson {
+    id: u32,
+    document_id: String,
+    subdoc: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    primary: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    secondary: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotators: Vec<PersonJson>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    comments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    multiword_tokens: Vec<MultiwordTokenJson>,
+    words: Vec<WordJson>,
+}
+
+/// A multiword token of a sentence read from CoNLL-U, see [`MultiwordToken`].
+#[derive(Serialize, Deserialize)]
+struct MultiwordTokenJson {
+    first: u32,
+    last: u32,
+    form: String,
+    columns: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WordJson {
+    id: u32,
+    form: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lemma: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    postag: Option<String>,
+    relation: String,
+    head: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    insertion_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artificial: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cite: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ud: Option<UdJson>,
+}
+
+/// The UD columns of a word read from CoNLL-U, see [`UdColumns`].
+#[derive(Serialize, Deserialize)]
+struct UdJson {
+    upos: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    xpos: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    feats: Option<String>,
+    deprel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deps: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    misc: Option<String>,
+}
+
+impl From<&Header> for HeaderJson {
+    fn from(header: &Header) -> Self {
+        Self {
+            release_date: header.release_date.clone(),
+            annotation_date: header.annotation_date.clone(),
+            annotation_scheme: header.annotation_scheme.clone(),
+            resp_stmts: header
+                .file_desc
+                .edition_stmt
+                .resp_stmts
+                .iter()
+                .map(RespStmtJson::from)
+                .collect(),
+            monogr: MonogrJson::from(&header.file_desc.bibl_struct.monogr),
+            encoding_desc: header.encoding_desc.clone(),
+            profile_desc: header.profile_desc.clone(),
+        }
+    }
+}
+
+impl From<HeaderJson> for Header {
+    fn from(header: HeaderJson) -> Self {
+        Self {
+            release_date: header.release_date,
+            annotation_date: header.annotation_date,
+            annotation_scheme: header.annotation_scheme,
+            file_desc: FileDesc {
+                edition_stmt: EditionStmt {
+                    resp_stmts: header.resp_stmts.into_iter().map(RespStmt::from).collect(),
+                },
+                bibl_struct: BiblStruct {
+                    monogr: header.monogr.into(),
+                },
+            },
+            encoding_desc: header.encoding_desc,
+            profile_desc: header.profile_desc,
+        }
+    }
+}
+
+impl From<&RespStmt> for RespStmtJson {
+    fn from(resp_stmt: &RespStmt) -> Self {
+        Self {
+            resp: resp_stmt.resp.clone(),
+            pers_name: resp_stmt.pers_name.as_ref().map(PersonJson::from),
+            name: resp_stmt.name.clone(),
+        }
+    }
+}
+
+impl From<RespStmtJson> for RespStmt {
+    fn from(resp_stmt: RespStmtJson) -> Self {
+        Self {
+            pers_name: resp_stmt.pers_name.map(PersInfo::from),
+            resp: resp_stmt.resp,
+            name: resp_stmt.name,
+        }
+    }
+}
+
+impl From<&PersInfo> for PersonJson {
+    fn from(person: &PersInfo) -> Self {
+        Self {
+            name: person.name.clone(),
+            short: person.short.clone(),
+            uri: person.uri.clone(),
+            address: person.address.clone(),
+        }
+    }
+}
+
+impl From<PersonJson> for PersInfo {
+    fn from(person: PersonJson) -> Self {
+        Self {
+            name: person.name,
+            short: person.short,
+            uri: person.uri,
+            address: person.address,
+        }
+    }
+}
+
+impl From<&Monogr> for MonogrJson {
+    fn from(monogr: &Monogr) -> Self {
+        Self {
+            author: monogr.author.clone(),
+            title: monogr.title.clone(),
+            resp_stmts: monogr.resp_stmts.iter().map(RespStmtJson::from).collect(),
+            imprint: monogr.imprint.as_ref().map(|imprint| ImprintJson {
+                publications: imprint
+                    .publications
+                    .iter()
+                    .map(|publication| PublicationJson {
+                        pub_place: publication.pub_place.clone(),
+                        publisher: publication.publisher.clone(),
+                    })
+                    .collect(),
+                date: imprint.date.clone(),
+            }),
+            bibl_scopes: monogr
+                .bibl_scopes
+                .iter()
+                .map(|scope| BiblScopeJson {
+                    scope_type: scope.scope_type.clone(),
+                    value: scope.value.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<MonogrJson> for Monogr {
+    fn from(monogr: MonogrJson) -> Self {
+        Self {
+            author: monogr.author,
+            title: monogr.title,
+            resp_stmts: monogr.resp_stmts.into_iter().map(RespStmt::from).collect(),
+            imprint: monogr.imprint.map(|imprint| Imprint {
+                publications: imprint
+                    .publications
+                    .into_iter()
+                    .map(|publication| Publication {
+                        pub_place: publication.pub_place,
+                        publisher: publication.publisher,
+                    })
+                    .collect(),
+                date: imprint.date,
+            }),
+            bibl_scopes: monogr
+                .bibl_scopes
+                .into_iter()
+                .map(|scope| BiblScope {
+                    scope_type: scope.scope_type,
+                    value: scope.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Sentence> for SentenceJson {
+    fn from(sentence: &Sentence) -> Self {
+        Self {
+            id: sentence.id,
+            document_id: sentence.document_id.clone(),
+            subdoc: sentence.subdoc.clone(),
+            attributes: sentence.attributes.clone(),
+            primary: sentence.primary.clone(),
+            secondary: sentence.secondary.clone(),
+            annotators: sentence.annotators.iter().map(PersonJson::from).collect(),
+            comments: sentence.comments.clone(),
+            multiword_tokens: sentence
+                .multiword_tokens
+                .iter()
+                .map(|multiword| MultiwordTokenJson {
+                    first: multiword.first,
+                    last: multiword.last,
+                    form: multiword.form.clone(),
+                    columns: multiword.columns.clone(),
+                })
+                .collect(),
+            words: sentence.words.iter().map(WordJson::from).collect(),
+        }
+    }
+}
+
+impl From<SentenceJson> for Sentence {
+    fn from(sentence: SentenceJson) -> Self {
+        Self {
+            id: sentence.id,
+            document_id: sentence.document_id,
+            subdoc: sentence.subdoc,
+            attributes: sentence.attributes,
+            primary: sentence.primary,
+            secondary: sentence.secondary,
+            annotators: sentence
+                .annotators
+                .into_iter()
+                .map(PersInfo::from)
+                .collect(),
+            comments: sentence.comments,
+            multiword_tokens: sentence
+                .multiword_tokens
+                .into_iter()
+                .map(|multiword| MultiwordToken {
+                    first: multiword.first,
+                    last: multiword.last,
+                    form: multiword.form,
+                    columns: multiword.columns,
+                })
+                .collect(),
+            words: sentence.words.into_iter().map(Token::from).collect(),
+        }
+    }
+}
+
+impl From<&Token> for WordJson {
+    fn from(token: &Token) -> Self {
+        Self {
+            id: token.id,
+            form: token.form.clone(),
+            lemma: token.lemma.clone(),
+            postag: token.postag.clone(),
+            relation: token.relation.clone(),
+            head: token.head,
+            insertion_id: token.insertion_id.clone(),
+            artificial: token.artificial.clone(),
+            cite: token.cite.clone(),
+            attributes: token.attributes.clone(),
+            ud: token.ud.as_ref().map(|ud| UdJson {
+                upos: ud.upos.clone(),
+                xpos: ud.xpos.clone(),
+                feats: ud.feats.clone(),
+                deprel: ud.deprel.clone(),
+                deps: ud.deps.clone(),
+                misc: ud.misc.clone(),
+            }),
+        }
+    }
+}
+
+impl From<WordJson> for Token {
+    fn from(word: WordJson) -> Self {
+        Self {
+            id: word.id,
+            form: word.form,
+            lemma: word.lemma,
+            postag: word.postag,
+            insertion_id: word.insertion_id,
+            artificial: word.artificial,
+            relation: word.relation,
+            head: word.head,
+            cite: word.cite,
+            attributes: word.attributes,
+            ud: word.ud.map(|ud| UdColumns {
+                upos: ud.upos,
+                xpos: ud.xpos,
+                feats: ud.feats,
+                deprel: ud.deprel,
+                deps: ud.deps,
+                misc: ud.misc,
+            }),
+        }
+    }
+}
+
+/// Writes the treebank as a single JSON document.
+///
+/// The document holds the version of the schema, the attributes of `<treebank>`, the header
+/// and the sentences:
+///
+/// ```json
+/// {
+///   "schema": 1,
+///   "version": "2.1",
+///   "xml_lang": "grc",
+///   "cts": "urn:cts:greekLit:tlg0007.tlg004.perseus-grc1.tb",
+///   "header": {
+///     "release_date": "...",
+///     "annotation_date": "...",
+///     "annotation_scheme": "...",
+///     "resp_stmts": [{ "resp": "...", "pers_name": { "name": "...", "short": "..." } }],
+///     "monogr": { "author": "...", "title": "...", "imprint": {...}, "bibl_scopes": [...] },
+///     "encoding_desc": "...",
+///     "profile_desc": "..."
+///   },
+///   "sentences": [
+///     {
+///       "id": 1,
+///       "document_id": "urn:cts:greekLit:tlg0007.tlg004.perseus-grc1",
+///       "subdoc": "1.1",
+///       "words": [
+///         { "id": 1, "form": "περὶ", "lemma": "περί", "postag": "r--------", "relation": "AuxP", "head": 9 }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Every name is in snake case. The `resp_stmts` of the header are those of `<editionStmt>`,
+/// and `monogr` is the one of `<biblStruct>`. Fields that are not set, e.g. the `lemma` or
+/// `insertion_id` of a word, or empty, e.g. the `attributes` of a sentence, are left out.
+///
+/// ```
+/// use agldt::json::{read_treebank, write_treebank};
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut json = vec![];
+/// write_treebank(&treebank, &mut json).unwrap();
+/// assert_eq!(read_treebank(std::str::from_utf8(&json).unwrap()).unwrap(), treebank);
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails.
+pub fn write_treebank<W: Write>(treebank: &Treebank, writer: &mut W) -> Result<(), Error> {
+    let json = TreebankJson {
+        schema: SCHEMA_VERSION,
+        version: treebank.version.clone(),
+        xml_lang: treebank.xml_lang.clone(),
+        cts: treebank.cts.clone(),
+        header: HeaderJson::from(&treebank.header),
+        sentences: treebank.iter_sentences().map(SentenceJson::from).collect(),
+    };
+    serde_json::to_writer(&mut *writer, &json).map_err(std::io::Error::from)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Reads a treebank written by [`write_treebank`].
+///
+/// # Errors
+///
+/// This function will return [`Error::MalformedJson`] if `src` does not follow the schema, or was
+/// written with a newer version of it, [`Error::UnsupportedVersion`] if the treebank declares a
+/// version not in [`crate::error::SUPPORTED_VERSIONS`], and [`Error::InvalidSentence`] if two
+/// sentences share an id.
+pub fn read_treebank(src: &str) -> Result<Treebank, Error> {
+    let json: TreebankJson = serde_json::from_str(src).map_err(|error| malformed(&error, 0))?;
+    if json.schema > SCHEMA_VERSION {
+        return Err(Error::MalformedJson {
+            message: format!(
+                "unsupported schema version {}, expected at most {SCHEMA_VERSION}",
+                json.schema
+            ),
+            location: Location::default(),
+        });
+    }
+    Treebank::builder(json.header.into())
+        .version(&json.version)
+        .xml_lang(&json.xml_lang)
+        .cts(&json.cts)
+        .sentences(json.sentences.into_iter().map(Sentence::from))
+        .build()
+}
+
+/// Writes the sentence as a line of JSON Lines: a JSON object with the `id`, `document_id`,
+/// `subdoc` and `words` of the sentence, as in [`write_treebank`].
+///
+/// ```
+/// use agldt::json::write_sentence;
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut line = vec![];
+/// write_sentence(&treebank.sentences()[0], &mut line).unwrap();
+/// let line = String::from_utf8(line).unwrap();
+/// assert!(line.starts_with(
+///     r#"{"id":1,"document_id":"urn:cts:greekLit:tlg0007.tlg004.perseus-grc1","subdoc":"1.1","#
+/// ));
+/// assert_eq!(line.lines().count(), 1);
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails.
+pub fn write_sentence<W: Write>(sentence: &Sentence, writer: &mut W) -> Result<(), Error> {
+    serde_json::to_writer(&mut *writer, &SentenceJson::from(sentence))
+        .map_err(std::io::Error::from)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Writes the sentences of the treebank as JSON Lines, one sentence per line, see
+/// [`write_sentence`]. The header is left out.
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails.
+pub fn write_lines<W: Write>(treebank: &Treebank, writer: &mut W) -> Result<(), Error> {
+    for sentence in treebank.iter_sentences() {
+        write_sentence(sentence, writer)?;
+    }
+    Ok(())
+}
+
+/// Reads the sentences of a JSON Lines file one at a time. Empty lines are skipped.
+pub struct JsonLinesReader<R: BufRead> {
+    source: R,
+    header: Header,
+    line: u64,
+    done: bool,
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    #[must_use]
+    pub fn new(source: R) -> Self {
+        Self {
+            source,
+            header: Header::new(Monogr::new("", "")),
+            line: 0,
+            done: false,
+        }
+    }
+
+    /// A header for the treebank, which JSON Lines files lack: every field is empty.
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the remaining sentences into a [`Treebank`] with [`JsonLinesReader::header`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return the first error met while reading the sentences, and
+    /// [`Error::InvalidSentence`] if two sentences share an id.
+    pub fn into_treebank(mut self) -> Result<Treebank, Error> {
+        let sentences = self.by_ref().collect::<Result<Vec<Sentence>, Error>>()?;
+        Treebank::builder(self.header).sentences(sentences).build()
+    }
+
+    fn next_sentence(&mut self) -> Result<Option<Sentence>, Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.source.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            if !line.trim().is_empty() {
+                return serde_json::from_str::<SentenceJson>(&line)
+                    .map(|sentence| Some(sentence.into()))
+                    .map_err(|error| malformed(&error, self.line));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLinesReader<R> {
+    type Item = Result<Sentence, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let sentence = self.next_sentence();
+        if !matches!(sentence, Ok(Some(_))) {
+            self.done = true;
+        }
+        sentence.transpose()
+    }
+}
+
+/// Reads a JSON Lines file, see [`JsonLinesReader`].
+///
+/// # Errors
+///
+/// This function will return [`Error::MalformedJson`] if a line is not a sentence.
+pub fn read_lines(src: &str) -> Result<Treebank, Error> {
+    JsonLinesReader::new(src.as_bytes()).into_treebank()
+}
+
+/// Turns an error of `serde_json` into [`Error::MalformedJson`], at `line` of a JSON Lines
+/// file, or at the position given by the error when `line` is `0`.
+fn malformed(error: &serde_json::Error, line: u64) -> Error {
+    let position = if line == 0 {
+        Position {
+            line: error.line() as u64,
+            column: error.column() as u64,
+        }
+    } else {
+        Position {
+            line,
+            column: error.column() as u64,
+        }
+    };
+    Error::MalformedJson {
+        message: error.to_string(),
+        location: Location {
+            position: Some(position),
+            ..Location::default()
+        },
+    }
+}
@@ -0,0 +1,394 @@
+use crate::error::Error;
+use crate::features::{Case, Degree, Gender, Mood, Number, Person, Postag, Tense, Voice};
+use crate::parser::{Header, Sentence, Token};
+use crate::tree::DependencyTree;
+use std::io::Write;
+
+/// LaTeX packages drawing the trees of sentences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Arcs above the words in the order of the text, with `tikz-dependency`.
+    Dependency,
+    /// Trees in the Prague style, dependents below their head, with `forest`.
+    Forest,
+}
+
+/// Writes the sentence as a `dependency` environment of `tikz-dependency`.
+///
+/// The words keep the order of the text, artificial nodes after the word they were inserted
+/// after, in grey. Arcs go from the head to the dependent, labelled with the relation, and the
+/// main verb hangs from the root with `\deproot`. With `gloss`, two rows go below the forms:
+/// the lemmata, in italics, and the postags parsed into glosses, see [`gloss`].
+///
+/// ```
+/// use agldt::latex::write_dependency;
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut tex = vec![];
+/// write_dependency(&treebank.sentences()[2], true, &mut tex).unwrap();
+/// let tex = String::from_utf8(tex).unwrap();
+/// assert!(tex.starts_with("\\begin{dependency}\n"));
+/// assert!(tex.contains(" \\& ἐν \\& Σπάρτῃ \\& "));
+/// assert!(tex.contains(" \\& \\textit{ἐν} \\& \\textit{σπάρτη} \\& "));
+/// assert!(tex.contains(" \\& \\textsc{sg.f.dat} \\& "));
+/// assert!(tex.contains("\\depedge{8}{6}{AuxP}\n"));
+/// assert!(tex.trim_end().ends_with("\\end{dependency}"));
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`DependencyTree::new`] if the sentence is not a tree.
+pub fn write_dependency<W: Write>(
+    sentence: &Sentence,
+    gloss: bool,
+    writer: &mut W,
+) -> Result<(), Error> {
+    sentence.tree()?;
+    let tokens = sentence.tokens_in_text_order();
+    // Arcs refer to the words by their column, starting from 1.
+    let column = |id: u32| {
+        tokens
+            .iter()
+            .position(|token| token.id == id)
+            .map_or(0, |index| index + 1)
+    };
+    let row = |cell: &dyn Fn(&Token) -> String| {
+        tokens
+            .iter()
+            .map(|token| cell(token))
+            .collect::<Vec<String>>()
+            .join(" \\& ")
+    };
+
+    writeln!(writer, "\\begin{{dependency}}")?;
+    writeln!(writer, "  \\begin{{deptext}}[column sep=0.6em]")?;
+    writeln!(writer, "    {} \\\\", row(&form))?;
+    if gloss {
+        writeln!(writer, "    {} \\\\", row(&lemma))?;
+        writeln!(writer, "    {} \\\\", row(&postag))?;
+    }
+    writeln!(writer, "  \\end{{deptext}}")?;
+    for token in &tokens {
+        if token.head == 0 {
+            writeln!(
+                writer,
+                "  \\deproot{{{}}}{{{}}}",
+                column(token.id),
+                escape(&token.relation)
+            )?;
+        } else {
+            writeln!(
+                writer,
+                "  \\depedge{{{}}}{{{}}}{{{}}}",
+                column(token.head),
+                column(token.id),
+                escape(&token.relation)
+            )?;
+        }
+    }
+    writeln!(writer, "\\end{{dependency}}")?;
+    Ok(())
+}
+
+/// Writes the sentence as a `forest` environment, a tree in the Prague style.
+///
+/// The root of the tree is labelled with the `subdoc` of the sentence. Every other node shows
+/// the form of the token above its relation, and hangs below its head, dependents in the order
+/// of the text. Artificial nodes are greyed out. With `gloss`, the lemma and the parsed postag
+/// go between the form and the relation, as in [`write_dependency`].
+///
+/// ```
+/// use agldt::latex::write_forest;
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut tex = vec![];
+/// write_forest(&treebank.sentences()[2], false, &mut tex).unwrap();
+/// let tex = String::from_utf8(tex).unwrap();
+/// assert!(tex.starts_with("\\begin{forest}\n"));
+/// assert!(tex.contains("  [{1.2}\n"));
+/// assert!(tex.contains("[{ἐν\\\\\\textsf{AuxP}}\n"));
+/// assert!(tex.contains("[{Σπάρτῃ\\\\\\textsf{ADV}}]"));
+/// assert!(tex.contains("[{\\textcolor{gray}{{[}0{]}}\\\\\\textsf{"));
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`DependencyTree::new`] if the sentence is not a tree.
+pub fn write_forest<W: Write>(
+    sentence: &Sentence,
+    gloss: bool,
+    writer: &mut W,
+) -> Result<(), Error> {
+    let tree = sentence.tree()?;
+    writeln!(writer, "\\begin{{forest}}")?;
+    writeln!(
+        writer,
+        "  for tree={{align=center, parent anchor=south, child anchor=north, l sep=1.5em, s sep=0.8em}}"
+    )?;
+    writeln!(writer, "  [{{{}}}", escape(&sentence.subdoc))?;
+    let mut roots = tree.roots();
+    roots.sort_by_key(|token| token.order_key());
+    for root in roots {
+        write_node(&tree, root, gloss, writer, 2)?;
+    }
+    writeln!(writer, "  ]")?;
+    writeln!(writer, "\\end{{forest}}")?;
+    Ok(())
+}
+
+/// Writes the subtree of `token` as a bracketed `forest` node, indented by `depth`.
+fn write_node<W: Write>(
+    tree: &DependencyTree,
+    token: &Token,
+    gloss: bool,
+    writer: &mut W,
+    depth: usize,
+) -> Result<(), Error> {
+    let mut lines = vec![form(token)];
+    if gloss {
+        lines.push(lemma(token));
+        lines.push(postag(token));
+    }
+    lines.push(format!("\\textsf{{{}}}", escape(&token.relation)));
+    let indent = "  ".repeat(depth);
+    let label = format!("{indent}[{{{}}}", lines.join("\\\\"));
+    let mut children: Vec<&Token> = tree.children(token.id).collect();
+    if children.is_empty() {
+        writeln!(writer, "{label}]")?;
+        return Ok(());
+    }
+    children.sort_by_key(|child| child.order_key());
+    writeln!(writer, "{label}")?;
+    for child in children {
+        write_node(tree, child, gloss, writer, depth + 1)?;
+    }
+    writeln!(writer, "{indent}]")?;
+    Ok(())
+}
+
+/// Writes a standalone LaTeX document with the trees of `sentences` in `style`, to be compiled
+/// with LuaLaTeX or XeLaTeX, which read the Greek forms as they are.
+///
+/// The document is titled after the author and the title of the treebank in `header`. Each
+/// tree comes under a heading with the `subdoc` and the id of its sentence, and is shrunk to
+/// the width of the page when wider.
+///
+/// ```
+/// use agldt::latex::{write_document, Style};
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut tex = vec![];
+/// write_document(treebank.header(), &treebank.sentences()[..3], Style::Forest, false, &mut tex)
+///     .unwrap();
+/// let tex = String::from_utf8(tex).unwrap();
+/// assert!(tex.starts_with("\\documentclass{article}\n"));
+/// assert!(tex.contains("\\usepackage{forest}\n"));
+/// assert_eq!(tex.matches("\\begin{forest}").count(), 3);
+/// assert!(tex.trim_end().ends_with("\\end{document}"));
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`DependencyTree::new`] if a sentence is not a tree.
+pub fn write_document<'a, W: Write>(
+    header: &Header,
+    sentences: impl IntoIterator<Item = &'a Sentence>,
+    style: Style,
+    gloss: bool,
+    writer: &mut W,
+) -> Result<(), Error> {
+    writeln!(writer, "\\documentclass{{article}}")?;
+    writeln!(writer, "% Compile with LuaLaTeX or XeLaTeX.")?;
+    writeln!(writer, "\\usepackage{{fontspec}}")?;
+    writeln!(writer, "\\setmainfont{{New Computer Modern}}")?;
+    writeln!(writer, "\\usepackage{{xcolor}}")?;
+    writeln!(writer, "\\usepackage{{adjustbox}}")?;
+    match style {
+        Style::Dependency => writeln!(writer, "\\usepackage{{tikz-dependency}}")?,
+        Style::Forest => writeln!(writer, "\\usepackage{{forest}}")?,
+    }
+    let monogr = header.file_desc.bibl_struct.monogr();
+    writeln!(
+        writer,
+        "\\title{{{}}}",
+        [monogr.author(), monogr.title()]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| escape(part))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )?;
+    writeln!(writer, "\\date{{}}")?;
+    writeln!(writer, "\\begin{{document}}")?;
+    writeln!(writer, "\\maketitle")?;
+    for sentence in sentences {
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "\\section*{{{} \\normalfont\\small({})}}",
+            escape(&sentence.subdoc),
+            sentence.id
+        )?;
+        writeln!(writer, "\\begin{{adjustbox}}{{max width=\\linewidth}}")?;
+        match style {
+            Style::Dependency => write_dependency(sentence, gloss, writer)?,
+            Style::Forest => write_forest(sentence, gloss, writer)?,
+        }
+        writeln!(writer, "\\end{{adjustbox}}")?;
+    }
+    writeln!(writer)?;
+    writeln!(writer, "\\end{{document}}")?;
+    Ok(())
+}
+
+/// Escapes the characters of `text` that LaTeX reads as commands, and the brackets that
+/// `forest` reads as nodes. Greek letters, breathings and accents are kept as they are.
+///
+/// The ano teleia and the Greek question mark, which Unicode turns into `·` and `;`, are
+/// written as such, since they are the same characters.
+///
+/// ```
+/// use agldt::latex::escape;
+///
+/// assert_eq!(escape("Λυκοῦργος"), "Λυκοῦργος");
+/// assert_eq!(escape("PRED_CO"), "PRED\\_CO");
+/// assert_eq!(escape("[0]"), "{[}0{]}");
+/// assert_eq!(escape("\u{387}"), "\u{b7}");
+/// ```
+#[must_use]
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '[' => escaped.push_str("{[}"),
+            ']' => escaped.push_str("{]}"),
+            '\u{387}' => escaped.push('\u{b7}'),
+            '\u{37e}' => escaped.push(';'),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The postag as a gloss in the abbreviations of the Leipzig Glossing Rules, set in small
+/// capitals: person and number, tense, mood, voice, gender, case and degree, e.g.
+/// `3\textsc{sg.aor.ind.mid}` for `v3saim---`. The part of speech is left out.
+///
+/// ```
+/// use agldt::latex::gloss;
+///
+/// assert_eq!(gloss(&"v3saim---".parse().unwrap()), "3\\textsc{sg.aor.ind.mid}");
+/// assert_eq!(gloss(&"n-s---mg-".parse().unwrap()), "\\textsc{sg.m.gen}");
+/// assert_eq!(gloss(&"v-prpemg-".parse().unwrap()), "\\textsc{pl.prf.ptcp.mp.m.gen}");
+/// assert_eq!(gloss(&"r--------".parse().unwrap()), "");
+/// ```
+#[must_use]
+pub fn gloss(postag: &Postag) -> String {
+    let person = postag.person.map_or("", |person| match person {
+        Person::First => "1",
+        Person::Second => "2",
+        Person::Third => "3",
+    });
+    let abbreviations: Vec<&str> = [
+        postag.number.map(|number| match number {
+            Number::Singular => "sg",
+            Number::Plural => "pl",
+            Number::Dual => "du",
+        }),
+        postag.tense.map(|tense| match tense {
+            Tense::Present => "prs",
+            Tense::Imperfect => "ipf",
+            Tense::Perfect => "prf",
+            Tense::Pluperfect => "plprf",
+            Tense::FuturePerfect => "futprf",
+            Tense::Future => "fut",
+            Tense::Aorist => "aor",
+        }),
+        postag.mood.map(|mood| match mood {
+            Mood::Indicative => "ind",
+            Mood::Subjunctive => "sbjv",
+            Mood::Optative => "opt",
+            Mood::Infinitive => "inf",
+            Mood::Imperative => "imp",
+            Mood::Participle => "ptcp",
+            Mood::Gerundive => "gerv",
+            Mood::Gerund => "ger",
+            Mood::Supine => "sup",
+        }),
+        postag.voice.map(|voice| match voice {
+            Voice::Active => "act",
+            Voice::Passive => "pass",
+            Voice::Middle => "mid",
+            Voice::MedioPassive => "mp",
+        }),
+        postag.gender.map(|gender| match gender {
+            Gender::Masculine => "m",
+            Gender::Feminine => "f",
+            Gender::Neuter => "n",
+        }),
+        postag.case.map(|case| match case {
+            Case::Nominative => "nom",
+            Case::Genitive => "gen",
+            Case::Dative => "dat",
+            Case::Accusative => "acc",
+            Case::Vocative => "voc",
+            Case::Locative => "loc",
+            Case::Ablative => "abl",
+        }),
+        postag.degree.map(|degree| match degree {
+            Degree::Comparative => "cmpr",
+            Degree::Superlative => "supl",
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if abbreviations.is_empty() {
+        person.to_string()
+    } else {
+        format!("{person}\\textsc{{{}}}", abbreviations.join("."))
+    }
+}
+
+/// The form of the token, greyed out for artificial nodes.
+fn form(token: &Token) -> String {
+    if token.is_artificial() {
+        format!("\\textcolor{{gray}}{{{}}}", escape(&token.form))
+    } else {
+        escape(&token.form)
+    }
+}
+
+/// The lemma of the token in italics, if it has one.
+fn lemma(token: &Token) -> String {
+    token
+        .lemma
+        .as_deref()
+        .map(|lemma| format!("\\textit{{{}}}", escape(lemma)))
+        .unwrap_or_default()
+}
+
+/// The postag of the token as a [`gloss`], or as it is if it cannot be parsed.
+fn postag(token: &Token) -> String {
+    match token.postag() {
+        Ok(Some(postag)) => gloss(&postag),
+        Ok(None) => String::new(),
+        Err(_) => escape(token.postag.as_deref().unwrap_or_default()),
+    }
+}
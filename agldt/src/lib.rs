@@ -2,13 +2,13 @@
 ///
 /// # Stages
 ///
-/// ## Preprocessing
+/// ## Reading
 ///
-/// Preprocesses the source `.xml` code to allow for serialization of the treebank.
+/// The source `.xml` is read with [`reader`], which walks the structure of the document instead
+/// of patching its text, so it does not depend on line breaks or indentation.
 ///
 /// There are some oddities in the scheme used in AGLDT's `xml` header and body, that otherwise make
 /// serializing it to a `struct` quite messy.
-/// This is kind of a bodge, but should do the trick.
 ///
 /// ### Oddities
 ///
@@ -31,26 +31,87 @@
 ///   <resp>annotator of the text</resp>
 /// </respStmt>
 /// ```
-/// To solve this oddity, we apply two regex replacements so as to move the
-/// `<name>` and `<address>` tags inside `<persName>`.
+/// Both shapes are read into the same [`parser::PersInfo`]: in the first one, the `<address>`
+/// sibling of `<persName>` is taken as the address of the person.
 ///
-/// A handful of other oddities concearn the use of the tags `<primary>`, `<secondary>` and
-/// `<annotator>` inside the tag `<sentence>`. Those are also removed by the regex in the current
-/// version.
+/// A handful of other oddities concern the use of the tags `<primary>`, `<secondary>` and
+/// `<annotator>` inside the tag `<sentence>`, written by the Arethusa annotation environment.
+/// Those are read into [`parser::Sentence`], as are the `cite` attributes of `<word>`, and any
+/// other attribute of `<sentence>` and `<word>` is kept, so that writing the treebank back
+/// loses none of them.
 ///
-///
-/// Finally, the `head` value is sometimes an empty string, which is still an issue for me to
-/// serialize. As `0` is not used anywhere else, I replace empty strings for `"0"`.
+/// Finally, the `head` value is sometimes an empty string or missing altogether. As `0` is not
+/// used anywhere else, it is read as `0`.
 ///
 /// ## Serialization
 ///
-/// Uses [`serde`] for serializing the data. I did my best to keep the metadata acessible, but
-/// there are still some missing fields that will later be included.
+/// The parsed types also derive [`serde`]'s traits. I did my best to keep the metadata acessible,
+/// but there are still some missing fields that will later be included.
 ///
 pub mod parser;
 
+// Lets the code generated by `agldt_derive`, which refers to `::agldt`, be used inside this crate.
+extern crate self as agldt;
+
+/// Structure-aware reader for AGLDT `xml`
+pub mod reader;
+
+/// Tools for writing treebanks back to AGLDT `xml`
+///
+/// The writer reproduces the `<treebank>`/`<header>`/`<body>` layout of AGLDT 2.1, so that a
+/// treebank read with [`parser::Treebank::from_xml_str`] and written back is read again into an
+/// equal value.
+pub mod writer;
+
+/// CoNLL-U input and output, converting the annotation to and from Universal Dependencies
+pub mod conllu;
+
+/// JSON and JSON Lines input and output, following a versioned schema
+pub mod json;
+
+/// PROIEL `xml` input, mapping its morphology and relations to the AGLDT annotation
+pub mod proiel;
+
+/// Pictures of the dependency trees of sentences, as Graphviz DOT or SVG
+pub mod render;
+
+/// LaTeX export of the trees of sentences, with `tikz-dependency` or `forest`
+pub mod latex;
+
+/// Builders for creating treebanks programmatically
+pub mod builder;
+
+/// Editing of annotations
+///
+/// Setters on [`parser::Token`], and insertion, removal, splitting and merging on
+/// [`parser::Sentence`] and [`parser::Treebank`], which keep every sentence a tree or return an
+/// error.
+pub mod edit;
+
+/// Errors raised while reading AGLDT data
+///
+/// Every error carries, where possible, the id of the sentence and of the word where it
+/// occurred, as well as its line and column in the source file.
+pub mod error;
+pub use error::Error;
+
 /// Definitions for parsing and building AGLDT postag data.
 pub mod features;
 
+/// Dependency relation labels and their suffixes
+pub mod relation;
+
+/// Dependency trees built from the `head` of each token
+pub mod tree;
+
+/// Structural validation of treebanks
+///
+/// Reports, as [`validate::Diagnostic`]s, the problems that keep a sentence from being a
+/// well-formed dependency tree, instead of stopping at the first one.
+pub mod validate;
+
+/// Conformance of postags and relation labels to the AGLDT tagset
+pub mod tagset;
+
 /// Utils
 pub mod utils;
@@ -1,98 +1,163 @@
-use regex::Regex;
+use crate::builder::{SentenceBuilder, TokenBuilder, TreebankBuilder};
+use crate::conllu::{MultiwordToken, UdColumns};
+use crate::features::{Postag, PostagFeature};
+use crate::relation::Relation;
+use crate::tree::DependencyTree;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
-/// Preprocesses the source `.xml` code to allow for serialization of the treebank.
-///
-/// There are some oddities in the scheme used in AGLDT's `xml` header and body, that otherwise make
-/// serializing it to a `struct` quite messy.
-/// This is kind of a bodge, but should do the trick.
-///
-/// # Oddities
-///
-/// The main oddity on AGLDT use of `xml` occurs inside the  tag `<respStmt>`, where the
-/// tag `<persName>` might contain either a single string value or a series of tags:
-///
-/// ```xml
-/// <respStmt>
-///   <persName>Bridget Almas</persName>
-///   <resp>responsible for the annotation environment and cts:urn technology</resp>
-///   <address>Tufts University</address>
-/// </respStmt>
-/// <respStmt>
-///   <persName>
-///     <short>Vanessa Gorman</short>
-///     <name>Vanessa Gorman</name>
-///     <address>vbgorman@gmail.com</address>
-///     <uri>http://data.perseus.org/sosol/users/Vanessa%20Gorman</uri>
-///   </persName>
-///   <resp>annotator of the text</resp>
-/// </respStmt>
-/// ```
-/// To solve this oddity, we apply two regex replacements so as to move the
-/// `<name>` and `<address>` tags inside `<persName>`.
-///
-/// A handful of other oddities concearn the use of the tags `<primary>`, `<secondary>` and
-/// `<annotator>` inside the tag `<sentence>`. Those are also removed by the regex in the current
-/// version.
-///
-///
-/// Finally, the `head` value is sometimes an empty string, which is still an issue for me to
-/// serialize. As `0` is not used anywhere else, I replace empty strings for `"0"`.
-//
-/// # Panics
-///
-/// Panics if it is unable to process Regexes
-#[must_use]
-pub fn preprocess(src: &str) -> String {
-    let re_xmlcolon = Regex::new(r"xml:").unwrap();
-    let src = re_xmlcolon.replace_all(src, "xml_");
-    let re_persname = Regex::new(r"<persName>(.*)</persName>\n").unwrap();
-    let src = re_persname.replace_all(&src, r"<persName><name>$1</name></persName>");
-    let re_address = Regex::new(r"(<resp>.*</resp>)\n\s*(<address>.*</address>)").unwrap();
-    let src = re_address.replace_all(&src, r"$2$1");
-    let re_address_persname = Regex::new(r"(</persName>)\s*(<address>.*</address>)").unwrap();
-    let src = re_address_persname.replace_all(&src, r"$2$1");
-    let re_primary = Regex::new("<primary>.*</primary>").unwrap();
-    let src = re_primary.replace_all(&src, r"");
-    let re_secondary = Regex::new("<secondary>.*</secondary>").unwrap();
-    let src = re_secondary.replace_all(&src, r"");
-    let re_annotator = Regex::new("<annotator>.*</annotator>").unwrap();
-    let src = re_annotator.replace_all(&src, r"");
-    let re_head = Regex::new("head=\"\"").unwrap();
-    let src = re_head.replace_all(&src, "head=\"0\"");
-
-    src.clone().to_string()
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Treebank {
-    version: String,
-    xml_lang: String,
-    cts: String,
-    header: Header,
-    body: Body,
+    pub(crate) version: String,
+    pub(crate) xml_lang: String,
+    pub(crate) cts: String,
+    pub(crate) header: Header,
+    pub(crate) body: Body,
 }
 
 impl Treebank {
-    /// .
+    /// Reads a treebank from the source of an AGLDT `xml` file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`crate::Error`] if the source is not well formed `xml`,
+    /// lacks any of the required elements and attributes or has invalid values for them.
+    pub fn from_xml_str(string: &str) -> Result<Self, crate::Error> {
+        crate::reader::from_str::<Treebank>(string)
+    }
+
+    /// Reads a treebank from the source of a CoNLL-U file, see [`crate::conllu::ConlluReader`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`crate::Error::MalformedConllu`] if a line cannot be read.
+    pub fn from_conllu_str(
+        string: &str,
+        mapping: crate::conllu::Mapping,
+    ) -> Result<Self, crate::Error> {
+        crate::conllu::read_treebank(string, mapping)
+    }
+
+    /// Reads a treebank from the source of a PROIEL `xml` file, see
+    /// [`crate::proiel::ProielReader`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`crate::Error`] if the source is not well formed `xml` or
+    /// if a token cannot be read.
+    pub fn from_proiel_str(string: &str) -> Result<Self, crate::Error> {
+        crate::proiel::read_treebank(string)
+    }
+
+    /// Reads a treebank from a JSON document, see [`crate::json::write_treebank`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`crate::Error::MalformedJson`] if the document does not follow
+    /// the schema.
+    pub fn from_json_str(string: &str) -> Result<Self, crate::Error> {
+        crate::json::read_treebank(string)
+    }
+
+    /// Returns the treebank as a JSON document, see [`crate::json::write_treebank`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the written JSON is not valid UTF-8, which should never happen.
+    #[must_use]
+    pub fn to_json_string(&self) -> String {
+        let mut buffer: Vec<u8> = vec![];
+        crate::json::write_treebank(self, &mut buffer).expect("writing to a Vec<u8> does not fail");
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Writes the treebank as AGLDT `xml`. See [`crate::writer::write_treebank`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if writing to `writer` fails.
+    pub fn write_xml<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        crate::writer::write_treebank(self, writer)
+    }
+
+    /// Returns the treebank as an AGLDT `xml` string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the written `xml` is not valid UTF-8, which should never happen.
+    #[must_use]
+    pub fn to_xml_string(&self) -> String {
+        let mut buffer: Vec<u8> = vec![];
+        self.write_xml(&mut buffer)
+            .expect("writing to a Vec<u8> does not fail");
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Writes the treebank as CoNLL-U, converted to Universal Dependencies. See
+    /// [`crate::conllu::write_sentence`].
     ///
     /// # Errors
     ///
-    /// This function will return an error if unable to serialize
-    /// preprocessed source string.
-    pub fn from_xml_str(string: &str) -> Result<Self, serde_xml_rs::Error> {
-        serde_xml_rs::from_str::<Treebank>(&preprocess(string))
+    /// This function will return an error if writing to `writer` fails or a sentence is not a
+    /// tree.
+    pub fn write_conllu<W: std::io::Write>(&self, writer: &mut W) -> Result<(), crate::Error> {
+        crate::conllu::write_treebank(self, writer)
+    }
+
+    /// Returns the treebank as a CoNLL-U string.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a sentence is not a tree.
+    pub fn to_conllu_string(&self) -> Result<String, crate::Error> {
+        let mut buffer: Vec<u8> = vec![];
+        self.write_conllu(&mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+
+    /// Starts building a treebank with the given header, see [`TreebankBuilder`].
+    #[must_use]
+    pub fn builder(header: Header) -> TreebankBuilder {
+        TreebankBuilder::new(header)
+    }
+
+    /// Version of the AGLDT format, e.g. `2.1`.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Language of the text, e.g. `grc` or `lat`.
+    #[must_use]
+    pub fn xml_lang(&self) -> &str {
+        &self.xml_lang
+    }
+
+    /// CTS URN of the text.
+    #[must_use]
+    pub fn cts(&self) -> &str {
+        &self.cts
+    }
+
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
     }
 
     #[must_use]
-    pub fn body(&self) -> Body {
-        self.body.clone()
+    pub fn body(&self) -> &Body {
+        &self.body
     }
 
     #[must_use]
-    pub fn sentences(&self) -> Vec<Sentence> {
-        self.body.sentences.clone()
+    pub fn sentences(&self) -> &[Sentence] {
+        &self.body.sentences
+    }
+
+    /// Iterates over the sentences without cloning them.
+    pub fn iter_sentences(&self) -> std::slice::Iter<'_, Sentence> {
+        self.body.sentences.iter()
     }
 
     #[must_use]
@@ -116,16 +181,98 @@ impl Display for Treebank {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Header {
     #[serde(rename = "releaseDate")]
-    release_date: String,
+    pub(crate) release_date: String,
     #[serde(rename = "annotationDate")]
-    annotation_date: String,
+    pub(crate) annotation_date: String,
     #[serde(rename = "annotationScheme")]
-    annotation_scheme: String,
+    pub(crate) annotation_scheme: String,
     #[serde(rename = "fileDesc")]
-    file_desc: FileDesc,
+    pub(crate) file_desc: FileDesc,
+    #[serde(rename = "encodingDesc")]
+    pub(crate) encoding_desc: Option<String>,
+    #[serde(rename = "profileDesc")]
+    pub(crate) profile_desc: Option<String>,
+}
+
+impl Header {
+    /// A header describing the edition `monogr`, with every other field empty.
+    #[must_use]
+    pub fn new(monogr: Monogr) -> Self {
+        Self {
+            release_date: String::new(),
+            annotation_date: String::new(),
+            annotation_scheme: String::new(),
+            file_desc: FileDesc {
+                edition_stmt: EditionStmt { resp_stmts: vec![] },
+                bibl_struct: BiblStruct { monogr },
+            },
+            encoding_desc: None,
+            profile_desc: None,
+        }
+    }
+    #[must_use]
+    pub fn with_release_date(mut self, release_date: &str) -> Self {
+        self.release_date = release_date.to_string();
+        self
+    }
+    #[must_use]
+    pub fn with_annotation_date(mut self, annotation_date: &str) -> Self {
+        self.annotation_date = annotation_date.to_string();
+        self
+    }
+    #[must_use]
+    pub fn with_annotation_scheme(mut self, annotation_scheme: &str) -> Self {
+        self.annotation_scheme = annotation_scheme.to_string();
+        self
+    }
+    /// Adds a statement of responsibility to the `<editionStmt>`, e.g. for an annotator.
+    #[must_use]
+    pub fn with_resp_stmt(mut self, resp_stmt: RespStmt) -> Self {
+        self.file_desc.edition_stmt.resp_stmts.push(resp_stmt);
+        self
+    }
+    #[must_use]
+    pub fn with_encoding_desc(mut self, encoding_desc: &str) -> Self {
+        self.encoding_desc = Some(encoding_desc.to_string());
+        self
+    }
+    #[must_use]
+    pub fn with_profile_desc(mut self, profile_desc: &str) -> Self {
+        self.profile_desc = Some(profile_desc.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn release_date(&self) -> &str {
+        &self.release_date
+    }
+    #[must_use]
+    pub fn annotation_date(&self) -> &str {
+        &self.annotation_date
+    }
+    #[must_use]
+    pub fn annotation_scheme(&self) -> &str {
+        &self.annotation_scheme
+    }
+    #[must_use]
+    pub fn file_desc(&self) -> &FileDesc {
+        &self.file_desc
+    }
+    /// Description of the source of the text, kept verbatim as it is marked with
+    /// `xml:space="preserve"`.
+    #[must_use]
+    pub fn encoding_desc(&self) -> Option<&str> {
+        self.encoding_desc.as_deref()
+    }
+    /// Description of the normalization of the text, kept verbatim as it is marked with
+    /// `xml:space="preserve"`.
+    #[must_use]
+    pub fn profile_desc(&self) -> Option<&str> {
+        self.profile_desc.as_deref()
+    }
 }
 
 impl Display for Header {
@@ -138,12 +285,23 @@ impl Display for Header {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct FileDesc {
     #[serde(rename = "editionStmt")]
-    edition_stmt: EditionStmt,
+    pub(crate) edition_stmt: EditionStmt,
     #[serde(rename = "biblStruct")]
-    bibl_struct: BiblStruct,
+    pub(crate) bibl_struct: BiblStruct,
+}
+
+impl FileDesc {
+    #[must_use]
+    pub fn edition_stmt(&self) -> &EditionStmt {
+        &self.edition_stmt
+    }
+    #[must_use]
+    pub fn bibl_struct(&self) -> &BiblStruct {
+        &self.bibl_struct
+    }
 }
 
 impl Display for FileDesc {
@@ -152,30 +310,206 @@ impl Display for FileDesc {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct EditionStmt {
     #[serde(rename = "$value")]
-    resp_stmts: Vec<RespStmt>,
+    pub(crate) resp_stmts: Vec<RespStmt>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+impl EditionStmt {
+    #[must_use]
+    pub fn resp_stmts(&self) -> &[RespStmt] {
+        &self.resp_stmts
+    }
+}
+
+/// A statement of responsibility.
+///
+/// Inside `<editionStmt>` the person is given by `<persName>`, while inside `<monogr>` it is
+/// given by a plain `<name>` following `<resp>`, e.g. the translator of the edition.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct RespStmt {
     #[serde(rename = "persName")]
-    pers_name: Option<PersInfo>,
-    resp: String,
+    pub(crate) pers_name: Option<PersInfo>,
+    pub(crate) resp: String,
+    pub(crate) name: Option<String>,
+}
+
+impl RespStmt {
+    #[must_use]
+    pub fn new(resp: &str) -> Self {
+        Self {
+            pers_name: None,
+            resp: resp.to_string(),
+            name: None,
+        }
+    }
+    #[must_use]
+    pub fn with_pers_name(mut self, pers_name: PersInfo) -> Self {
+        self.pers_name = Some(pers_name);
+        self
+    }
+    #[must_use]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn pers_name(&self) -> Option<&PersInfo> {
+        self.pers_name.as_ref()
+    }
+    #[must_use]
+    pub fn resp(&self) -> &str {
+        &self.resp
+    }
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct PersInfo {
-    name: String,
-    short: Option<String>,
-    uri: Option<String>,
-    address: Option<String>,
+    pub(crate) name: String,
+    pub(crate) short: Option<String>,
+    pub(crate) uri: Option<String>,
+    pub(crate) address: Option<String>,
+}
+
+impl PersInfo {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            short: None,
+            uri: None,
+            address: None,
+        }
+    }
+    #[must_use]
+    pub fn with_short(mut self, short: &str) -> Self {
+        self.short = Some(short.to_string());
+        self
+    }
+    #[must_use]
+    pub fn with_uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri.to_string());
+        self
+    }
+    #[must_use]
+    pub fn with_address(mut self, address: &str) -> Self {
+        self.address = Some(address.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    #[must_use]
+    pub fn short(&self) -> Option<&str> {
+        self.short.as_deref()
+    }
+    #[must_use]
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_deref()
+    }
+    #[must_use]
+    pub fn address(&self) -> Option<&str> {
+        self.address.as_deref()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct BiblStruct {
-    monogr: Monogr,
+    pub(crate) monogr: Monogr,
+}
+
+impl BiblStruct {
+    #[must_use]
+    pub fn monogr(&self) -> &Monogr {
+        &self.monogr
+    }
+
+    /// A bibliographic citation of the edition of the text.
+    ///
+    /// ```
+    /// use agldt::parser::Treebank;
+    ///
+    /// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    /// let treebank = Treebank::from_xml_str(&src).unwrap();
+    /// assert_eq!(
+    ///     treebank.header().file_desc().bibl_struct().citation(),
+    ///     "Plutarch. Plutarch's Lives. With an English Translation by Bernadotte Perrin. \
+    ///      Cambridge, MA: Harvard University Press; London: William Heinemann Ltd., 1914. \
+    ///      Volume 1, Lycurgus."
+    /// );
+    /// ```
+    #[must_use]
+    pub fn citation(&self) -> String {
+        let monogr = &self.monogr;
+        let mut parts: Vec<String> = vec![monogr.author.clone(), monogr.title.clone()];
+        for resp_stmt in &monogr.resp_stmts {
+            let name = resp_stmt
+                .name
+                .as_deref()
+                .or(resp_stmt.pers_name.as_ref().map(|p| p.name.as_str()))
+                .unwrap_or_default();
+            parts.push(capitalize(&format!("{} {name}", resp_stmt.resp)));
+        }
+        if let Some(imprint) = &monogr.imprint {
+            let publications = imprint
+                .publications
+                .iter()
+                .map(
+                    |publication| match (&publication.pub_place, &publication.publisher) {
+                        (Some(place), Some(publisher)) => format!("{place}: {publisher}"),
+                        (Some(place), None) => place.clone(),
+                        (None, Some(publisher)) => publisher.clone(),
+                        (None, None) => String::new(),
+                    },
+                )
+                .filter(|publication| !publication.is_empty())
+                .collect::<Vec<String>>()
+                .join("; ");
+            let imprint = match &imprint.date {
+                Some(date) if publications.is_empty() => date.clone(),
+                Some(date) => format!("{publications}, {date}"),
+                None => publications,
+            };
+            if !imprint.is_empty() {
+                parts.push(imprint);
+            }
+        }
+        if !monogr.bibl_scopes.is_empty() {
+            let scopes = monogr
+                .bibl_scopes
+                .iter()
+                .map(|scope| match &scope.scope_type {
+                    Some(scope_type) => format!("{scope_type} {}", scope.value),
+                    None => scope.value.clone(),
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            parts.push(capitalize(&scopes));
+        }
+        parts
+            .iter()
+            .map(|part| part.trim_end_matches('.'))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join(". ")
+            + "."
+    }
+}
+
+fn capitalize(src: &str) -> String {
+    let mut chars = src.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 impl Display for BiblStruct {
@@ -184,13 +518,64 @@ impl Display for BiblStruct {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Monogr {
-    author: String,
-    title: String,
-    // #[serde(rename = "respStmt")]
-    // resp_stmts: Vec<RespStmt>,
-    // imprint: Imprint,
+    pub(crate) author: String,
+    pub(crate) title: String,
+    #[serde(rename = "respStmt")]
+    pub(crate) resp_stmts: Vec<RespStmt>,
+    pub(crate) imprint: Option<Imprint>,
+    #[serde(rename = "biblScope")]
+    pub(crate) bibl_scopes: Vec<BiblScope>,
+}
+
+impl Monogr {
+    #[must_use]
+    pub fn new(author: &str, title: &str) -> Self {
+        Self {
+            author: author.to_string(),
+            title: title.to_string(),
+            resp_stmts: vec![],
+            imprint: None,
+            bibl_scopes: vec![],
+        }
+    }
+    #[must_use]
+    pub fn with_resp_stmt(mut self, resp_stmt: RespStmt) -> Self {
+        self.resp_stmts.push(resp_stmt);
+        self
+    }
+    #[must_use]
+    pub fn with_imprint(mut self, imprint: Imprint) -> Self {
+        self.imprint = Some(imprint);
+        self
+    }
+    #[must_use]
+    pub fn with_bibl_scope(mut self, bibl_scope: BiblScope) -> Self {
+        self.bibl_scopes.push(bibl_scope);
+        self
+    }
+
+    #[must_use]
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    #[must_use]
+    pub fn resp_stmts(&self) -> &[RespStmt] {
+        &self.resp_stmts
+    }
+    #[must_use]
+    pub fn imprint(&self) -> Option<&Imprint> {
+        self.imprint.as_ref()
+    }
+    #[must_use]
+    pub fn bibl_scopes(&self) -> &[BiblScope] {
+        &self.bibl_scopes
+    }
 }
 
 impl Display for Monogr {
@@ -199,21 +584,103 @@ impl Display for Monogr {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Publication data of the edition: an edition may be published by several publishers, each
+/// in its own place, e.g. in Cambridge, MA and in London.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Imprint {
+    pub(crate) publications: Vec<Publication>,
+    pub(crate) date: Option<String>,
+}
+
+impl Imprint {
+    #[must_use]
+    pub fn new(date: Option<&str>) -> Self {
+        Self {
+            publications: vec![],
+            date: date.map(str::to_string),
+        }
+    }
+    #[must_use]
+    pub fn with_publication(mut self, pub_place: Option<&str>, publisher: Option<&str>) -> Self {
+        self.publications.push(Publication {
+            pub_place: pub_place.map(str::to_string),
+            publisher: publisher.map(str::to_string),
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn publications(&self) -> &[Publication] {
+        &self.publications
+    }
+    #[must_use]
+    pub fn date(&self) -> Option<&str> {
+        self.date.as_deref()
+    }
+}
+
+/// A `<pubPlace>` and the `<publisher>` that follows it.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Publication {
     #[serde(rename = "pubPlace")]
-    pub_place: Vec<String>,
-    publisher: Vec<String>,
-    date: Vec<String>,
+    pub(crate) pub_place: Option<String>,
+    pub(crate) publisher: Option<String>,
+}
+
+impl Publication {
+    #[must_use]
+    pub fn pub_place(&self) -> Option<&str> {
+        self.pub_place.as_deref()
+    }
+    #[must_use]
+    pub fn publisher(&self) -> Option<&str> {
+        self.publisher.as_deref()
+    }
+}
+
+/// The part of the edition covered by the treebank, e.g. `<biblScope type="volume">1</biblScope>`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct BiblScope {
+    #[serde(rename = "type")]
+    pub(crate) scope_type: Option<String>,
+    pub(crate) value: String,
+}
+
+impl BiblScope {
+    #[must_use]
+    pub fn new(scope_type: Option<&str>, value: &str) -> Self {
+        Self {
+            scope_type: scope_type.map(str::to_string),
+            value: value.to_string(),
+        }
+    }
+
+    /// The `type` attribute, e.g. `volume`, `chapter` or `pp`.
+    #[must_use]
+    pub fn scope_type(&self) -> Option<&str> {
+        self.scope_type.as_deref()
+    }
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Body {
     #[serde(rename = "$value")]
-    sentences: Vec<Sentence>,
+    pub(crate) sentences: Vec<Sentence>,
 }
 
 impl Body {
+    #[must_use]
+    pub fn sentences(&self) -> &[Sentence] {
+        &self.sentences
+    }
+    /// Iterates over the sentences without cloning them.
+    pub fn iter_sentences(&self) -> std::slice::Iter<'_, Sentence> {
+        self.sentences.iter()
+    }
     #[must_use]
     pub fn count_tokens(&self) -> usize {
         let mut c = 0;
@@ -253,19 +720,126 @@ impl IntoIterator for Body {
     }
 }
 
+impl<'a> IntoIterator for &'a Body {
+    type Item = &'a Sentence;
+    type IntoIter = std::slice::Iter<'a, Sentence>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sentences.iter()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Sentence {
     pub(crate) id: u32,
-    document_id: String,
-    subdoc: String,
+    pub(crate) document_id: String,
+    pub(crate) subdoc: String,
+    /// Other attributes of `<sentence>`, e.g. `span`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) primary: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) secondary: Vec<String>,
+    #[serde(default, rename = "annotator", skip_serializing_if = "Vec::is_empty")]
+    pub(crate) annotators: Vec<PersInfo>,
+    /// Comments of a sentence read from CoNLL-U, e.g. `# sent_id = train-s1`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) comments: Vec<String>,
+    /// Multiword tokens of a sentence read from CoNLL-U, e.g. `1-2 populusque`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) multiword_tokens: Vec<MultiwordToken>,
     #[serde(rename = "$value")]
-    words: Vec<Token>,
+    pub(crate) words: Vec<Token>,
 }
 
 impl Sentence {
+    /// Starts building the sentence `id`, see [`SentenceBuilder`].
     #[must_use]
-    pub fn words(&self) -> Vec<Token> {
-        self.words.clone()
+    pub fn builder(id: u32) -> SentenceBuilder {
+        SentenceBuilder::new(id)
+    }
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    #[must_use]
+    pub fn document_id(&self) -> &str {
+        &self.document_id
+    }
+    #[must_use]
+    pub fn subdoc(&self) -> &str {
+        &self.subdoc
+    }
+    #[must_use]
+    pub fn words(&self) -> &[Token] {
+        &self.words
+    }
+    /// Attributes of `<sentence>` other than `id`, `document_id` and `subdoc`, such as the
+    /// `span` written by Arethusa.
+    #[must_use]
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+    /// Short names of the main annotators of the sentence, from `<primary>`.
+    #[must_use]
+    pub fn primary(&self) -> &[String] {
+        &self.primary
+    }
+    /// Short names of the other annotators of the sentence, from `<secondary>`.
+    #[must_use]
+    pub fn secondary(&self) -> &[String] {
+        &self.secondary
+    }
+    /// Annotators of the sentence, from `<annotator>`.
+    #[must_use]
+    pub fn annotators(&self) -> &[PersInfo] {
+        &self.annotators
+    }
+    /// Comments of a sentence read from CoNLL-U, as they were found, with their `#`, e.g.
+    /// `# sent_id = train-s1` or `# newdoc id = ittb-scg`. Those read into another field, such
+    /// as `# annotator`, are left out.
+    #[must_use]
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+    /// Multiword tokens of a sentence read from CoNLL-U, which have no place in the AGLDT
+    /// annotation.
+    #[must_use]
+    pub fn multiword_tokens(&self) -> &[MultiwordToken] {
+        &self.multiword_tokens
+    }
+
+    /// The sentence as `<document_id>:<subdoc>@<id>`, e.g.
+    /// `urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.2@3`, which tells it apart from the
+    /// sentences of every other treebank. It is the `sent_id` written to CoNLL-U.
+    #[must_use]
+    pub fn citation_id(&self) -> String {
+        format!("{}:{}@{}", self.document_id, self.subdoc, self.id)
+    }
+
+    /// The tokens in the order of the text, artificial nodes coming right after the word they
+    /// were inserted after, see [`Token::order_key`].
+    ///
+    /// ```
+    /// use agldt::parser::Treebank;
+    ///
+    /// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    /// let treebank = Treebank::from_xml_str(&src).unwrap();
+    /// let sentence = &treebank.sentences()[2];
+    /// assert_eq!(
+    ///     sentence.citation_id(),
+    ///     "urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.2@3"
+    /// );
+    /// let tokens = sentence.tokens_in_text_order();
+    /// assert_eq!((tokens[25].id(), tokens[26].form()), (26, "[0]"));
+    /// assert_eq!(tokens.len(), sentence.count_tokens());
+    /// ```
+    #[must_use]
+    pub fn tokens_in_text_order(&self) -> Vec<&Token> {
+        let mut tokens: Vec<&Token> = self.words.iter().collect();
+        tokens.sort_by_key(|token| token.order_key());
+        tokens
     }
 
     #[must_use]
@@ -273,6 +847,16 @@ impl Sentence {
         self.words.len()
     }
 
+    /// Builds the [`DependencyTree`] of the sentence.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `head` links of the tokens do not form a tree,
+    /// see [`DependencyTree::new`].
+    pub fn tree(&self) -> Result<DependencyTree<'_>, crate::Error> {
+        DependencyTree::new(self)
+    }
+
     #[must_use]
     pub fn count_words(&self) -> usize {
         let mut c = 0;
@@ -294,25 +878,56 @@ impl IntoIterator for Sentence {
     }
 }
 
+impl<'a> IntoIterator for &'a Sentence {
+    type Item = &'a Token;
+    type IntoIter = std::slice::Iter<'a, Token>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.words.iter()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Token {
-    id: u32,
-    form: String,
-    lemma: Option<String>,
-    postag: Option<String>,
-    artificial: Option<String>,
-    relation: String,
-    head: u32,
+    pub(crate) id: u32,
+    pub(crate) form: String,
+    pub(crate) lemma: Option<String>,
+    pub(crate) postag: Option<String>,
+    pub(crate) insertion_id: Option<String>,
+    pub(crate) artificial: Option<String>,
+    pub(crate) relation: String,
+    pub(crate) head: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cite: Option<String>,
+    /// Other attributes of `<word>`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ud: Option<UdColumns>,
 }
 
 impl Token {
+    /// Starts building the token `id`, see [`TokenBuilder`].
+    #[must_use]
+    pub fn builder(id: u32, form: &str) -> TokenBuilder {
+        TokenBuilder::new(id, form)
+    }
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    /// Id of the head of the token, `0` for the root of the sentence.
+    #[must_use]
+    pub fn head(&self) -> u32 {
+        self.head
+    }
     #[must_use]
     pub fn form(&self) -> &str {
         self.form.as_ref()
     }
     #[must_use]
-    pub fn lemma(&self) -> Option<String> {
-        self.lemma.as_ref().cloned()
+    pub fn lemma(&self) -> Option<&str> {
+        self.lemma.as_deref()
     }
 
     #[must_use]
@@ -320,12 +935,203 @@ impl Token {
         self.postag.is_some()
     }
 
+    /// The postag of the token, as found in the source.
+    #[must_use]
+    pub fn postag_str(&self) -> Option<&str> {
+        self.postag.as_deref()
+    }
+
+    /// Parses the postag of the token into a [`Postag`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`crate::Error::InvalidPostag`] if the postag of the token
+    /// is not a valid AGLDT postag.
+    pub fn postag(&self) -> Result<Option<Postag>, crate::Error> {
+        self.postag
+            .as_deref()
+            .map(|postag| {
+                postag
+                    .parse::<Postag>()
+                    .map_err(|error| error.in_word(self.id))
+            })
+            .transpose()
+    }
+
+    /// The relation label of the token, as found in the source.
+    #[must_use]
+    pub fn relation_str(&self) -> &str {
+        &self.relation
+    }
+
+    /// Parses the relation label of the token into a [`Relation`].
+    ///
+    /// ```
+    /// use agldt::parser::Treebank;
+    /// use agldt::relation::BaseRelation;
+    ///
+    /// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    /// let treebank = Treebank::from_xml_str(&src).unwrap();
+    /// let coordinated_subjects = treebank.sentences()[0]
+    ///     .words()
+    ///     .iter()
+    ///     .filter_map(|token| token.relation().ok())
+    ///     .filter(|relation| relation.is(BaseRelation::Sbj) && relation.is_coordinated())
+    ///     .count();
+    /// assert_eq!(coordinated_subjects, 4);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`crate::Error::InvalidRelation`] if the label does not follow
+    /// the AGLDT guidelines.
+    pub fn relation(&self) -> Result<Relation, crate::Error> {
+        self.relation
+            .parse::<Relation>()
+            .map_err(|error| error.in_word(self.id))
+    }
+
+    /// Reads a single feature from the postag of the token, e.g. its [`crate::features::Case`].
+    ///
+    /// Returns `None` if the token has no postag, if the position of the feature is empty or if
+    /// it holds an invalid character.
+    ///
+    /// ```
+    /// use agldt::features::Case;
+    /// use agldt::parser::Treebank;
+    ///
+    /// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    /// let treebank = Treebank::from_xml_str(&src).unwrap();
+    /// let genitives = treebank.sentences()[0]
+    ///     .words()
+    ///     .iter()
+    ///     .filter(|token| token.feature::<Case>() == Some(Case::Genitive))
+    ///     .count();
+    /// assert_eq!(genitives, 5);
+    /// ```
+    #[must_use]
+    pub fn feature<T: PostagFeature>(&self) -> Option<T> {
+        self.postag
+            .as_deref()
+            .and_then(|postag| T::from_postag_str(postag).ok().flatten())
+    }
+
+    /// Whether the token is a word of the text: it has a postag, is not punctuation and is not
+    /// an artificial node.
+    ///
+    /// Tokens read from CoNLL-U without a postag are words unless their `UPOS` is `PUNCT`.
     #[must_use]
     pub fn is_word(&self) -> bool {
-        if let Some(pos) = &self.postag {
-            !pos.starts_with("u-")
-        } else {
-            false
+        if self.is_artificial() {
+            return false;
+        }
+        match (&self.postag, &self.ud) {
+            (Some(pos), _) => !pos.starts_with("u-"),
+            (None, Some(ud)) => ud.upos != "PUNCT" && ud.upos != "_",
+            (None, None) => false,
         }
     }
+
+    /// CTS URN of the passage the token belongs to, e.g.
+    /// `urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.1`.
+    #[must_use]
+    pub fn cite(&self) -> Option<&str> {
+        self.cite.as_deref()
+    }
+
+    /// Attributes of `<word>` that have no field of their own, kept so that they are written
+    /// back.
+    #[must_use]
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    /// The columns of the token as read from a CoNLL-U file, see [`crate::conllu`].
+    #[must_use]
+    pub fn ud(&self) -> Option<&UdColumns> {
+        self.ud.as_ref()
+    }
+
+    /// Whether the token is an artificial node, inserted by the annotators for an elided word,
+    /// e.g. `artificial="elliptic"`.
+    #[must_use]
+    pub fn is_artificial(&self) -> bool {
+        self.artificial.is_some()
+    }
+
+    /// The kind of artificial node, as found in the source, e.g. `elliptic`.
+    #[must_use]
+    pub fn artificial(&self) -> Option<&str> {
+        self.artificial.as_deref()
+    }
+
+    /// Where an artificial node was inserted, e.g. `0077e` for the first node inserted after
+    /// the word `77`.
+    #[must_use]
+    pub fn insertion_id(&self) -> Option<&str> {
+        self.insertion_id.as_deref()
+    }
+
+    /// Key placing the token in the order of the text, with artificial nodes right after the
+    /// word given by their `insertion_id`, in the order of its suffix.
+    ///
+    /// Tokens without an `insertion_id`, or with one that cannot be read, are placed by their
+    /// `id`.
+    ///
+    /// ```
+    /// use agldt::parser::Treebank;
+    ///
+    /// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    /// let treebank = Treebank::from_xml_str(&src).unwrap();
+    /// let mut words = treebank.sentences()[2].words().to_vec();
+    /// words.sort_by_key(|token| token.order_key());
+    /// let artificial = words.iter().position(|token| token.is_artificial()).unwrap();
+    /// assert_eq!(words[artificial].insertion_id(), Some("0026e"));
+    /// assert_eq!(words[artificial - 1].id(), 26);
+    /// ```
+    #[must_use]
+    pub fn order_key(&self) -> (u32, String) {
+        self.insertion_id
+            .as_deref()
+            .and_then(split_insertion_id)
+            .map(|(position, suffix)| (position, suffix.to_string()))
+            .unwrap_or_else(|| (self.id, String::new()))
+    }
+}
+
+/// Letters of the suffixes of `insertion_id`s, from `e` for the first node inserted after a word.
+const SUFFIX_LETTERS: std::ops::RangeInclusive<u8> = b'e'..=b'z';
+
+/// The `insertion_id` of the artificial node numbered `index`, from 0, among those inserted
+/// after the word `position`, e.g. `0026e` for the first one after the word 26.
+///
+/// Past `z`, suffixes go on as `ze`, `zf`, …, `zz`, `zze`, so that they keep their order when
+/// compared as strings, see [`Token::order_key`].
+pub(crate) fn format_insertion_id(position: u32, index: u32) -> String {
+    let letters = u32::from(SUFFIX_LETTERS.end() - SUFFIX_LETTERS.start() + 1);
+    let z = "z".repeat((index / letters) as usize);
+    let letter = char::from(*SUFFIX_LETTERS.start() + (index % letters) as u8);
+    format!("{position:04}{z}{letter}")
+}
+
+/// Reverses the suffix of [`format_insertion_id`], giving the index of the node, if the
+/// suffix follows that scheme.
+pub(crate) fn suffix_index(suffix: &str) -> Option<u32> {
+    let letters = u32::from(SUFFIX_LETTERS.end() - SUFFIX_LETTERS.start() + 1);
+    let last = *suffix.as_bytes().last()?;
+    let z = &suffix[..suffix.len() - 1];
+    if !SUFFIX_LETTERS.contains(&last) || z.bytes().any(|c| c != b'z') {
+        return None;
+    }
+    let z = u32::try_from(z.len()).ok()?;
+    Some(z * letters + u32::from(last - SUFFIX_LETTERS.start()))
+}
+
+/// Splits an `insertion_id`, e.g. `0026e`, into the id of the word it follows and its suffix.
+pub(crate) fn split_insertion_id(insertion_id: &str) -> Option<(u32, &str)> {
+    let digits = insertion_id
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(insertion_id.len());
+    let position = insertion_id[..digits].parse::<u32>().ok()?;
+    Some((position, &insertion_id[digits..]))
 }
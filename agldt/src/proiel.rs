@@ -0,0 +1,538 @@
+use crate::error::Error;
+use crate::features::{
+    Case, Degree, Gender, Mood, Number, PartOfSpeech, Person, Postag, Tense, Voice,
+};
+use crate::parser::{format_insertion_id, Header, Monogr, RespStmt, Sentence, Token, Treebank};
+use crate::reader::{
+    empty_element, event_reader, no_root_element, parse_u32, qualified_name, read_element, Element,
+};
+use crate::relation::{BaseRelation, Relation};
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use xml::reader::{EventReader, XmlEvent};
+
+/// Reads the sentences of a PROIEL `xml` file one at a time, mapping them to the AGLDT
+/// annotation.
+///
+/// The header is built from the metadata of the first `<source>`: its title, author, annotators
+/// and reviewers. The language and the id of the source become the `xml:lang` and the `cts` of
+/// the treebank, and the `document_id` of its sentences, whose `subdoc` is the `citation-part`
+/// of their first token.
+///
+/// Tokens are mapped as follows:
+///
+/// - the part of speech and the morphology become a postag, see [`postag_from_proiel`];
+/// - relations become AGLDT relations, see [`relation_from_proiel`], and are moved from the
+///   prepositions and subjunctions to their objects, which get `AuxP` and `AuxC`;
+/// - a conjunction whose dependents share its relation is a `COORD`, its dependents taking its
+///   relation with the suffix `_CO`;
+/// - empty tokens become artificial nodes, numbered after the words and inserted after the
+///   word that precedes them in the source;
+/// - tokens are renumbered from `1` within their sentence.
+///
+/// Secondary dependencies (`<slash>`) have no place in an AGLDT tree and are left out, as is
+/// the punctuation kept by PROIEL in `presentation-before` and `presentation-after`.
+///
+/// ```
+/// use agldt::proiel::ProielReader;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = File::open("./tests/proiel-john.xml").unwrap();
+/// let mut reader = ProielReader::new(BufReader::new(file)).unwrap();
+/// assert_eq!(reader.xml_lang(), "grc");
+/// assert_eq!(reader.header().file_desc().bibl_struct().monogr().title(), "The Greek New Testament");
+///
+/// let first = reader.next().unwrap().unwrap();
+/// assert_eq!(first.subdoc(), "JOHN 1.1");
+/// // Ἐν ἀρχῇ: the noun takes the relation of the preposition.
+/// assert_eq!(first.words()[0].relation_str(), "AuxP");
+/// assert_eq!(first.words()[1].relation_str(), "ADV");
+/// assert_eq!(first.words()[1].postag_str(), Some("n-s---fd-"));
+/// assert_eq!(reader.count(), 2);
+/// ```
+pub struct ProielReader<R: BufRead> {
+    events: EventReader<R>,
+    xml_lang: String,
+    cts: String,
+    header: Header,
+    document_id: String,
+    finished: bool,
+}
+
+impl<R: BufRead> ProielReader<R> {
+    /// Reads the opening `<proiel>` tag and the metadata of the first `<source>`, skipping the
+    /// `<annotation>` and stopping at the first `<div>`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the source is not well formed `xml`, if the root
+    /// element is not `<proiel>` or if it has no `<source>`.
+    pub fn new(source: R) -> Result<Self, Error> {
+        let mut events = event_reader(source);
+        let root = loop {
+            match events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => break empty_element(&events, &name, attributes),
+                XmlEvent::EndDocument => return Err(no_root_element(&events)),
+                _ => {}
+            }
+        };
+        root.expect_name("proiel")?;
+
+        let mut source = loop {
+            match events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    if qualified_name(&name) == "source" {
+                        break empty_element(&events, &name, attributes);
+                    }
+                    read_element(&mut events, &name, attributes)?;
+                }
+                XmlEvent::EndElement { .. } | XmlEvent::EndDocument => {
+                    return Err(Error::MissingElement {
+                        parent: root.name.clone(),
+                        element: "source".to_string(),
+                        location: root.location(),
+                    })
+                }
+                _ => {}
+            }
+        };
+        loop {
+            match events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    if qualified_name(&name) == "div" {
+                        break;
+                    }
+                    let child = read_element(&mut events, &name, attributes)?;
+                    source.children.push(child);
+                }
+                XmlEvent::EndElement { .. } | XmlEvent::EndDocument => break,
+                _ => {}
+            }
+        }
+
+        let id = source.required_attribute("id")?.to_string();
+        Ok(Self {
+            events,
+            xml_lang: source.required_attribute("language")?.to_string(),
+            cts: id.clone(),
+            header: header(&source),
+            document_id: id,
+            finished: false,
+        })
+    }
+
+    #[must_use]
+    pub fn xml_lang(&self) -> &str {
+        self.xml_lang.as_ref()
+    }
+
+    #[must_use]
+    pub fn cts(&self) -> &str {
+        self.cts.as_ref()
+    }
+
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the remaining sentences into a whole [`Treebank`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return the first error met while reading the sentences, and
+    /// [`Error::InvalidSentence`] if two sentences share an id.
+    pub fn into_treebank(mut self) -> Result<Treebank, Error> {
+        let sentences = self.by_ref().collect::<Result<Vec<Sentence>, Error>>()?;
+        Treebank::builder(self.header)
+            .xml_lang(&self.xml_lang)
+            .cts(&self.cts)
+            .sentences(sentences)
+            .build()
+    }
+
+    fn next_sentence(&mut self) -> Result<Option<Sentence>, Error> {
+        loop {
+            match self.events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => match qualified_name(&name).as_str() {
+                    "div" => {}
+                    "source" => {
+                        let source = empty_element(&self.events, &name, attributes);
+                        self.document_id = source.required_attribute("id")?.to_string();
+                    }
+                    "sentence" => {
+                        let element = read_element(&mut self.events, &name, attributes)?;
+                        return read_sentence(&element, &self.document_id).map(Some);
+                    }
+                    _ => {
+                        read_element(&mut self.events, &name, attributes)?;
+                    }
+                },
+                XmlEvent::EndDocument => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ProielReader<R> {
+    type Item = Result<Sentence, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.next_sentence() {
+            Ok(Some(sentence)) => Some(Ok(sentence)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads a PROIEL `xml` file, see [`ProielReader`].
+///
+/// # Errors
+///
+/// This function will return an error if the source is not well formed `xml` or if a token
+/// cannot be read.
+pub fn read_treebank(src: &str) -> Result<Treebank, Error> {
+    ProielReader::new(src.as_bytes())?.into_treebank()
+}
+
+/// Header built from the metadata of a `<source>`.
+fn header(source: &Element) -> Header {
+    let mut header = Header::new(Monogr::new(
+        &source.child_text("author").unwrap_or_default(),
+        &source.child_text("title").unwrap_or_default(),
+    ))
+    .with_annotation_scheme("PROIEL");
+    for (element, resp) in [
+        ("annotator", "annotator"),
+        ("reviewer", "reviewer"),
+        ("electronic-text-editor", "editor of the electronic text"),
+    ] {
+        for child in source.children(element) {
+            if !child.text().is_empty() {
+                header = header.with_resp_stmt(RespStmt::new(resp).with_name(child.text()));
+            }
+        }
+    }
+    header
+}
+
+/// Whether the `<token>` is an empty token, i.e. has an `empty-token-sort`.
+fn is_empty(token: &Element) -> bool {
+    token.attribute("empty-token-sort").is_some()
+}
+
+/// Reads a `<sentence>` into a [`Sentence`] of the document `document_id`.
+fn read_sentence(element: &Element, document_id: &str) -> Result<Sentence, Error> {
+    let id = parse_u32(element.required_attribute("id")?, "id", element)?;
+    read_tokens(element)
+        .map(|words| Sentence {
+            id,
+            document_id: document_id.to_string(),
+            subdoc: element
+                .children("token")
+                .find_map(|token| token.attribute("citation-part"))
+                .unwrap_or_default()
+                .to_string(),
+            attributes: BTreeMap::new(),
+            primary: vec![],
+            secondary: vec![],
+            annotators: vec![],
+            comments: vec![],
+            multiword_tokens: vec![],
+            words,
+        })
+        .map_err(|error| error.in_sentence(id))
+}
+
+fn read_tokens(element: &Element) -> Result<Vec<Token>, Error> {
+    let elements: Vec<&Element> = element.children("token").collect();
+    // Words are numbered first, in order, then empty tokens.
+    let mut ids: HashMap<&str, u32> = HashMap::new();
+    for token in elements
+        .iter()
+        .filter(|token| !is_empty(token))
+        .chain(elements.iter().filter(|token| is_empty(token)))
+    {
+        let next = u32::try_from(ids.len()).unwrap_or(u32::MAX) + 1;
+        ids.insert(token.required_attribute("id")?.trim(), next);
+    }
+
+    let mut words = Vec::with_capacity(elements.len());
+    let mut parts = Vec::with_capacity(elements.len());
+    let mut labels = Vec::with_capacity(elements.len());
+    let mut after = 0;
+    let mut suffixes: HashMap<u32, u32> = HashMap::new();
+    let mut artificial = 0;
+    for token in &elements {
+        let id = ids[token.required_attribute("id")?.trim()];
+        let head = match token.attribute("head-id") {
+            Some(head) => *ids.get(head.trim()).ok_or_else(|| Error::InvalidHead {
+                head: head.to_string(),
+                location: token.location(),
+            })?,
+            None => 0,
+        };
+        let part = token.attribute("part-of-speech");
+        let mut word = Token {
+            id,
+            form: token.attribute("form").unwrap_or_default().to_string(),
+            lemma: token.attribute("lemma").map(str::to_string),
+            postag: part.map(|part| {
+                postag_from_proiel(part, token.attribute("morphology").unwrap_or_default())
+                    .to_string()
+            }),
+            insertion_id: None,
+            artificial: None,
+            relation: String::new(),
+            head,
+            cite: None,
+            attributes: BTreeMap::new(),
+            ud: None,
+        };
+        match token.attribute("empty-token-sort") {
+            Some(sort) => {
+                let count = suffixes.entry(after).or_default();
+                word.form = format!("[{artificial}]");
+                word.insertion_id = Some(format_insertion_id(after, *count));
+                *count += 1;
+                word.artificial = Some("elliptic".to_string());
+                artificial += 1;
+                parts.push(match sort {
+                    "C" => "C-",
+                    "V" => "V-",
+                    _ => "",
+                });
+            }
+            None => {
+                after = id;
+                parts.push(part.unwrap_or_default());
+            }
+        }
+        labels.push(token.attribute("relation").unwrap_or_default());
+        words.push(word);
+    }
+
+    let heads: Vec<Option<usize>> = words
+        .iter()
+        .map(|word| words.iter().position(|head| head.id == word.head))
+        .collect();
+    for (position, word) in words.iter_mut().enumerate() {
+        word.relation = if labels[position].is_empty() {
+            "_".to_string()
+        } else {
+            relation(position, &labels, &parts, &heads).to_string()
+        };
+    }
+    words.sort_by_key(|word| word.id);
+    Ok(words)
+}
+
+/// Whether the token at `index` is a preposition or a subjunction whose object is marked by
+/// the relation `label`.
+fn is_marker(index: usize, label: &str, parts: &[&str]) -> bool {
+    matches!((parts[index], label), ("R-", "obl") | ("G-", "pred"))
+}
+
+/// Whether the token at `index` is a conjunction, overt or empty.
+fn is_coordinator(index: usize, parts: &[&str]) -> bool {
+    parts[index] == "C-"
+}
+
+/// Relation of the token at `index` in the AGLDT tree.
+fn relation(index: usize, labels: &[&str], parts: &[&str], heads: &[Option<usize>]) -> Relation {
+    let dependents = || (0..labels.len()).filter(move |&i| heads[i] == Some(index));
+    if dependents().any(|i| is_marker(index, labels[i], parts)) {
+        return Relation::new(if parts[index] == "R-" {
+            BaseRelation::AuxP
+        } else {
+            BaseRelation::AuxC
+        });
+    }
+    let function = function(index, labels, parts, heads, 0);
+    if is_coordinator(index, parts) && dependents().any(|i| labels[i] == labels[index]) {
+        let mut relation = Relation::new(BaseRelation::Coord);
+        relation.coordination = function.coordination;
+        return relation;
+    }
+    function
+}
+
+/// Function of the token at `index`: its own relation, or the relation of the preposition or
+/// subjunction it is the object of, or of the coordination it is a member of.
+fn function(
+    index: usize,
+    labels: &[&str],
+    parts: &[&str],
+    heads: &[Option<usize>],
+    depth: usize,
+) -> Relation {
+    match heads[index] {
+        // A cycle of heads ends the search.
+        _ if depth > labels.len() => relation_from_proiel(labels[index], parts[index]),
+        Some(head) if is_marker(head, labels[index], parts) => {
+            function(head, labels, parts, heads, depth + 1)
+        }
+        Some(head) if is_coordinator(head, parts) && labels[head] == labels[index] => {
+            let mut relation = function(head, labels, parts, heads, depth + 1);
+            relation.coordination = true;
+            relation
+        }
+        _ => relation_from_proiel(labels[index], parts[index]),
+    }
+}
+
+/// The AGLDT postag closest to a PROIEL part of speech and morphology.
+///
+/// The morphology gives, in ten positions, the person, number, tense, mood, voice, gender, case,
+/// degree, strength and inflection. Values without an AGLDT counterpart, such as the resultative
+/// tense or the common genders, are left empty, as are the strength and the inflection.
+///
+/// ```
+/// use agldt::proiel::postag_from_proiel;
+///
+/// assert_eq!(postag_from_proiel("V-", "-srppmn--i").to_string(), "v-srppmn-");
+/// assert_eq!(postag_from_proiel("S-", "-s---fg--i").to_string(), "l-s---fg-");
+/// assert_eq!(postag_from_proiel("Df", "---------n").to_string(), "d--------");
+/// ```
+#[must_use]
+pub fn postag_from_proiel(part_of_speech: &str, morphology: &str) -> Postag {
+    let position = |index: usize| morphology.chars().nth(index).unwrap_or('-');
+    Postag {
+        pos: match part_of_speech {
+            "A-" | "Mo" | "Py" => Some(PartOfSpeech::Adjective),
+            "Df" | "Dq" | "Du" => Some(PartOfSpeech::Adverb),
+            "S-" => Some(PartOfSpeech::Article),
+            "Ma" => Some(PartOfSpeech::Numeral),
+            "Nb" | "Ne" => Some(PartOfSpeech::Noun),
+            "C-" | "G-" => Some(PartOfSpeech::Conjunction),
+            "Pc" | "Pd" | "Pi" | "Pk" | "Pp" | "Pr" | "Ps" | "Pt" | "Px" => {
+                Some(PartOfSpeech::Pronoun)
+            }
+            "N-" => Some(PartOfSpeech::Particle),
+            "I-" => Some(PartOfSpeech::Interjection),
+            "R-" => Some(PartOfSpeech::Preposition),
+            "V-" => Some(PartOfSpeech::Verb),
+            "F-" | "X-" => Some(PartOfSpeech::Irregular),
+            _ => None,
+        },
+        person: match position(0) {
+            '1' => Some(Person::First),
+            '2' => Some(Person::Second),
+            '3' => Some(Person::Third),
+            _ => None,
+        },
+        number: match position(1) {
+            's' => Some(Number::Singular),
+            'd' => Some(Number::Dual),
+            'p' => Some(Number::Plural),
+            _ => None,
+        },
+        tense: match position(2) {
+            'p' => Some(Tense::Present),
+            'i' => Some(Tense::Imperfect),
+            'r' => Some(Tense::Perfect),
+            'l' => Some(Tense::Pluperfect),
+            't' => Some(Tense::FuturePerfect),
+            'f' => Some(Tense::Future),
+            'a' => Some(Tense::Aorist),
+            _ => None,
+        },
+        mood: match position(3) {
+            'i' => Some(Mood::Indicative),
+            's' => Some(Mood::Subjunctive),
+            'o' => Some(Mood::Optative),
+            'n' => Some(Mood::Infinitive),
+            'm' => Some(Mood::Imperative),
+            'p' => Some(Mood::Participle),
+            'g' => Some(Mood::Gerundive),
+            'd' => Some(Mood::Gerund),
+            'u' => Some(Mood::Supine),
+            _ => None,
+        },
+        voice: match position(4) {
+            'a' => Some(Voice::Active),
+            'p' => Some(Voice::Passive),
+            'm' => Some(Voice::Middle),
+            'e' => Some(Voice::MedioPassive),
+            _ => None,
+        },
+        gender: match position(5) {
+            'm' => Some(Gender::Masculine),
+            'f' => Some(Gender::Feminine),
+            'n' => Some(Gender::Neuter),
+            _ => None,
+        },
+        case: match position(6) {
+            'n' => Some(Case::Nominative),
+            'g' => Some(Case::Genitive),
+            'd' => Some(Case::Dative),
+            'a' => Some(Case::Accusative),
+            'v' => Some(Case::Vocative),
+            'l' => Some(Case::Locative),
+            'b' => Some(Case::Ablative),
+            _ => None,
+        },
+        degree: match position(7) {
+            'c' => Some(Degree::Comparative),
+            's' => Some(Degree::Superlative),
+            _ => None,
+        },
+    }
+}
+
+/// The AGLDT relation closest to a PROIEL relation, given the PROIEL part of speech of the
+/// token, which tells apart the kinds of `aux`.
+///
+/// Only the label is mapped: the objects of prepositions and subjunctions, and the members of
+/// coordinations, are given their relation by [`ProielReader`].
+///
+/// ```
+/// use agldt::proiel::relation_from_proiel;
+///
+/// assert_eq!(relation_from_proiel("sub", "Nb").to_string(), "SBJ");
+/// assert_eq!(relation_from_proiel("xadv", "V-").to_string(), "ATV");
+/// assert_eq!(relation_from_proiel("aux", "S-").to_string(), "ATR");
+/// assert_eq!(relation_from_proiel("aux", "Df").to_string(), "AuxY");
+/// ```
+#[must_use]
+pub fn relation_from_proiel(relation: &str, part_of_speech: &str) -> Relation {
+    let base = match relation {
+        "pred" => BaseRelation::Pred,
+        "sub" | "xsub" => BaseRelation::Sbj,
+        "obj" | "obl" | "arg" | "comp" | "nonsub" => BaseRelation::Obj,
+        "xobj" => BaseRelation::Pnom,
+        "adv" | "ag" | "parpred" => BaseRelation::Adv,
+        "xadv" => BaseRelation::Atv,
+        "atr" | "adnom" | "narg" | "part" | "rel" | "apos" => BaseRelation::Atr,
+        "aux" => match part_of_speech {
+            "R-" => BaseRelation::AuxP,
+            "G-" => BaseRelation::AuxC,
+            "V-" => BaseRelation::AuxV,
+            "S-" => BaseRelation::Atr,
+            _ => BaseRelation::AuxY,
+        },
+        "expl" => BaseRelation::AuxY,
+        _ => BaseRelation::ExD,
+    };
+    Relation::new(base)
+}
@@ -0,0 +1,704 @@
+use crate::error::{Error, Location, Position, SUPPORTED_VERSIONS};
+use crate::parser::{
+    BiblScope, BiblStruct, Body, EditionStmt, FileDesc, Header, Imprint, Monogr, PersInfo,
+    Publication, RespStmt, Sentence, Token, Treebank,
+};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Read};
+use xml::attribute::OwnedAttribute;
+use xml::common::Position as _;
+use xml::name::OwnedName;
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+
+/// A lightweight `xml` element, holding its attributes, its children and its text content.
+///
+/// Elements are built straight from the event stream of [`xml::reader::EventReader`], so the
+/// source never needs to be rewritten before reading it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    pub(crate) name: String,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<Element>,
+    pub(crate) text: String,
+    pub(crate) position: Option<Position>,
+}
+
+impl Element {
+    /// Qualified name of the element, e.g. `word`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Value of the attribute `name`. Prefixed attributes are looked up by their qualified
+    /// name, e.g. `xml:lang`.
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Text content of the element, trimmed.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        self.text.trim()
+    }
+
+    /// Text content of the element, kept verbatim when it is marked with
+    /// `xml:space="preserve"` and trimmed otherwise.
+    #[must_use]
+    pub fn spaced_text(&self) -> String {
+        if self.attribute("xml:space") == Some("preserve") {
+            self.text.clone()
+        } else {
+            self.text().to_string()
+        }
+    }
+
+    /// First child named `name`.
+    #[must_use]
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Iterates over the children named `name`.
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Position of the start tag of the element in the source.
+    #[must_use]
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Location of the element, used for reporting errors.
+    #[must_use]
+    pub fn location(&self) -> Location {
+        Location {
+            sentence_id: match self.name.as_str() {
+                "sentence" => self.attribute("id").and_then(|id| id.trim().parse().ok()),
+                _ => None,
+            },
+            word_id: match self.name.as_str() {
+                "word" => self.attribute("id").and_then(|id| id.trim().parse().ok()),
+                _ => None,
+            },
+            position: self.position,
+        }
+    }
+
+    pub(crate) fn required_attribute(&self, name: &str) -> Result<&str, Error> {
+        self.attribute(name).ok_or_else(|| Error::MissingAttribute {
+            element: self.name.clone(),
+            attribute: name.to_string(),
+            location: self.location(),
+        })
+    }
+
+    pub(crate) fn required_child(&self, name: &str) -> Result<&Element, Error> {
+        self.child(name).ok_or_else(|| Error::MissingElement {
+            parent: self.name.clone(),
+            element: name.to_string(),
+            location: self.location(),
+        })
+    }
+
+    pub(crate) fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|child| child.text().to_string())
+    }
+
+    pub(crate) fn required_child_text(&self, name: &str) -> Result<String, Error> {
+        Ok(self.required_child(name)?.text().to_string())
+    }
+
+    /// Attributes of the element whose names are not in `known`.
+    pub(crate) fn other_attributes(&self, known: &[&str]) -> BTreeMap<String, String> {
+        self.attributes
+            .iter()
+            .filter(|(key, _)| !known.contains(&key.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn expect_name(&self, name: &str) -> Result<(), Error> {
+        if self.name == name {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedElement {
+                expected: name.to_string(),
+                found: self.name.clone(),
+                location: self.location(),
+            })
+        }
+    }
+}
+
+/// Types that can be built from an [`Element`] of an AGLDT file.
+pub trait FromXml: Sized {
+    /// Builds the value from `element`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `element` does not have the expected name,
+    /// or lacks a required attribute or child.
+    fn from_element(element: &Element) -> Result<Self, Error>;
+}
+
+/// Reads the first element of `src` into `T`.
+///
+/// This works both for whole treebanks and for fragments, such as a single `<editionStmt>`.
+///
+/// # Errors
+///
+/// This function will return an error if `src` is not well formed `xml` or if its content
+/// does not match `T`.
+pub fn from_str<T: FromXml>(src: &str) -> Result<T, Error> {
+    from_reader(src.as_bytes())
+}
+
+/// Reads the first element of `source` into `T`.
+///
+/// # Errors
+///
+/// This function will return an error if `source` is not well formed `xml` or if its content
+/// does not match `T`.
+pub fn from_reader<R: Read, T: FromXml>(source: R) -> Result<T, Error> {
+    let mut events = event_reader(source);
+    loop {
+        match events.next()? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let element = read_element(&mut events, &name, attributes)?;
+                return T::from_element(&element);
+            }
+            XmlEvent::EndDocument => return Err(no_root_element(&events)),
+            _ => {}
+        }
+    }
+}
+
+/// Reads a treebank sentence by sentence from any [`BufRead`] source.
+///
+/// The attributes of `<treebank>` and its `<header>` are read when the reader is built, the
+/// sentences are then read one at a time when iterating, so that only one `<sentence>` is kept
+/// in memory at once.
+///
+/// ```
+/// use agldt::reader::TreebankReader;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = File::open("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let mut reader = TreebankReader::new(BufReader::new(file)).unwrap();
+/// assert_eq!(reader.xml_lang(), "grc");
+///
+/// let first = reader.next().unwrap().unwrap();
+/// assert_eq!(first.count_tokens(), 46);
+/// assert_eq!(reader.count(), 433);
+/// ```
+pub struct TreebankReader<R: BufRead> {
+    events: EventReader<R>,
+    version: String,
+    xml_lang: String,
+    cts: String,
+    header: Header,
+    finished: bool,
+}
+
+impl<R: BufRead> TreebankReader<R> {
+    /// Reads the opening `<treebank>` tag and the `<header>` of `source`, stopping at the
+    /// beginning of `<body>`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the source is not well formed `xml`, if the root
+    /// element is not `<treebank>` or if the header cannot be read.
+    pub fn new(source: R) -> Result<Self, Error> {
+        let mut events = event_reader(source);
+        let root = loop {
+            match events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => break empty_element(&events, &name, attributes),
+                XmlEvent::EndDocument => return Err(no_root_element(&events)),
+                _ => {}
+            }
+        };
+        root.expect_name("treebank")?;
+        check_version(&root)?;
+
+        let mut header = None;
+        loop {
+            match events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    if qualified_name(&name) == "body" {
+                        break;
+                    }
+                    let element = read_element(&mut events, &name, attributes)?;
+                    if element.name == "header" {
+                        header = Some(Header::from_element(&element)?);
+                    }
+                }
+                XmlEvent::EndElement { .. } | XmlEvent::EndDocument => {
+                    return Err(Error::MissingElement {
+                        parent: root.name.clone(),
+                        element: "body".to_string(),
+                        location: root.location(),
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            events,
+            version: root.required_attribute("version")?.to_string(),
+            xml_lang: root.required_attribute("xml:lang")?.to_string(),
+            cts: root.required_attribute("cts")?.to_string(),
+            header: header.ok_or_else(|| Error::MissingElement {
+                parent: root.name.clone(),
+                element: "header".to_string(),
+                location: root.location(),
+            })?,
+            finished: false,
+        })
+    }
+
+    #[must_use]
+    pub fn version(&self) -> &str {
+        self.version.as_ref()
+    }
+
+    #[must_use]
+    pub fn xml_lang(&self) -> &str {
+        self.xml_lang.as_ref()
+    }
+
+    #[must_use]
+    pub fn cts(&self) -> &str {
+        self.cts.as_ref()
+    }
+
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the remaining sentences into a whole [`Treebank`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the remaining sentences cannot be read.
+    pub fn into_treebank(mut self) -> Result<Treebank, Error> {
+        let sentences = self.by_ref().collect::<Result<Vec<Sentence>, Error>>()?;
+        Ok(Treebank {
+            version: self.version,
+            xml_lang: self.xml_lang,
+            cts: self.cts,
+            header: self.header,
+            body: Body { sentences },
+        })
+    }
+
+    fn next_sentence(&mut self) -> Result<Option<Sentence>, Error> {
+        loop {
+            match self.events.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let element = read_element(&mut self.events, &name, attributes)?;
+                    if element.name == "sentence" {
+                        return Sentence::from_element(&element).map(Some);
+                    }
+                }
+                XmlEvent::EndElement { .. } | XmlEvent::EndDocument => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TreebankReader<R> {
+    type Item = Result<Sentence, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.next_sentence() {
+            Ok(Some(sentence)) => Some(Ok(sentence)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+pub(crate) fn event_reader<R: Read>(source: R) -> EventReader<R> {
+    ParserConfig::new()
+        .trim_whitespace(false)
+        .whitespace_to_characters(false)
+        .cdata_to_characters(true)
+        .ignore_comments(true)
+        .create_reader(source)
+}
+
+pub(crate) fn qualified_name(name: &OwnedName) -> String {
+    match &name.prefix {
+        Some(prefix) => format!("{prefix}:{}", name.local_name),
+        None => name.local_name.clone(),
+    }
+}
+
+pub(crate) fn no_root_element<R: Read>(events: &EventReader<R>) -> Error {
+    Error::MalformedXml {
+        message: "no root element".to_string(),
+        location: Location {
+            position: Some(events.position().into()),
+            ..Location::default()
+        },
+    }
+}
+
+/// Checks that the `version` of `<treebank>`, when given, is in [`SUPPORTED_VERSIONS`].
+fn check_version(element: &Element) -> Result<(), Error> {
+    match element.attribute("version") {
+        Some(version) if !SUPPORTED_VERSIONS.contains(&version) => Err(Error::UnsupportedVersion {
+            version: version.to_string(),
+            location: element.location(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Builds an element without children from a `StartElement` event.
+pub(crate) fn empty_element<R: Read>(
+    events: &EventReader<R>,
+    name: &OwnedName,
+    attributes: Vec<OwnedAttribute>,
+) -> Element {
+    Element {
+        name: qualified_name(name),
+        attributes: attributes
+            .into_iter()
+            .map(|attribute| (qualified_name(&attribute.name), attribute.value))
+            .collect(),
+        children: vec![],
+        text: String::new(),
+        position: Some(events.position().into()),
+    }
+}
+
+/// Reads the element opened by a `StartElement` event up to its matching `EndElement`.
+pub(crate) fn read_element<R: Read>(
+    events: &mut EventReader<R>,
+    name: &OwnedName,
+    attributes: Vec<OwnedAttribute>,
+) -> Result<Element, Error> {
+    let mut element = empty_element(events, name, attributes);
+    loop {
+        match events.next()? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => element
+                .children
+                .push(read_element(events, &name, attributes)?),
+            XmlEvent::Characters(text) => element.text.push_str(&text),
+            XmlEvent::EndElement { .. } => return Ok(element),
+            XmlEvent::EndDocument => {
+                return Err(Error::MalformedXml {
+                    message: format!("unexpected end of document inside <{}>", element.name),
+                    location: element.location(),
+                })
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn parse_u32(value: &str, attribute: &str, element: &Element) -> Result<u32, Error> {
+    value.trim().parse().map_err(|_| Error::InvalidAttribute {
+        element: element.name.clone(),
+        attribute: attribute.to_string(),
+        value: value.to_string(),
+        location: element.location(),
+    })
+}
+
+impl FromXml for Treebank {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("treebank")?;
+        check_version(element)?;
+        Ok(Self {
+            version: element.required_attribute("version")?.to_string(),
+            xml_lang: element.required_attribute("xml:lang")?.to_string(),
+            cts: element.required_attribute("cts")?.to_string(),
+            header: Header::from_element(element.required_child("header")?)?,
+            body: Body::from_element(element.required_child("body")?)?,
+        })
+    }
+}
+
+impl FromXml for Header {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("header")?;
+        Ok(Self {
+            release_date: element.required_child_text("releaseDate")?,
+            annotation_date: element.required_child_text("annotationDate")?,
+            annotation_scheme: element.required_child_text("annotationScheme")?,
+            file_desc: FileDesc::from_element(element.required_child("fileDesc")?)?,
+            encoding_desc: element.child("encodingDesc").map(Element::spaced_text),
+            profile_desc: element.child("profileDesc").map(Element::spaced_text),
+        })
+    }
+}
+
+impl FromXml for FileDesc {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("fileDesc")?;
+        Ok(Self {
+            edition_stmt: EditionStmt::from_element(element.required_child("editionStmt")?)?,
+            bibl_struct: BiblStruct::from_element(element.required_child("biblStruct")?)?,
+        })
+    }
+}
+
+impl FromXml for EditionStmt {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("editionStmt")?;
+        Ok(Self {
+            resp_stmts: element
+                .children("respStmt")
+                .map(RespStmt::from_element)
+                .collect::<Result<Vec<RespStmt>, Error>>()?,
+        })
+    }
+}
+
+impl FromXml for RespStmt {
+    /// Reads both shapes of `<persName>`: when it holds only text, the `<address>` sibling of
+    /// `<persName>` is taken as the address of the person.
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("respStmt")?;
+        let pers_name = match element.child("persName") {
+            Some(pers_name) => {
+                let mut pers_info = PersInfo::from_element(pers_name)?;
+                if pers_info.address.is_none() {
+                    pers_info.address = element.child_text("address");
+                }
+                Some(pers_info)
+            }
+            None => None,
+        };
+        Ok(Self {
+            pers_name,
+            resp: element.required_child_text("resp")?,
+            name: element.child_text("name"),
+        })
+    }
+}
+
+impl FromXml for PersInfo {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("persName")?;
+        read_pers_info(element)
+    }
+}
+
+/// Reads a `<persName>`, or an `<annotator>` of a sentence, holding either a bare name or a
+/// `<name>` with its `<short>`, `<uri>` and `<address>`.
+fn read_pers_info(element: &Element) -> Result<PersInfo, Error> {
+    if element.children.is_empty() {
+        return Ok(PersInfo::new(element.text()));
+    }
+    Ok(PersInfo {
+        name: element.required_child_text("name")?,
+        short: element.child_text("short"),
+        uri: element.child_text("uri"),
+        address: element.child_text("address"),
+    })
+}
+
+impl FromXml for BiblStruct {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("biblStruct")?;
+        Ok(Self {
+            monogr: Monogr::from_element(element.required_child("monogr")?)?,
+        })
+    }
+}
+
+impl FromXml for Monogr {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("monogr")?;
+        Ok(Self {
+            author: element.required_child_text("author")?,
+            title: element.required_child_text("title")?,
+            resp_stmts: element
+                .children("respStmt")
+                .map(RespStmt::from_element)
+                .collect::<Result<Vec<RespStmt>, Error>>()?,
+            imprint: element
+                .child("imprint")
+                .map(Imprint::from_element)
+                .transpose()?,
+            bibl_scopes: element
+                .children("biblScope")
+                .map(BiblScope::from_element)
+                .collect::<Result<Vec<BiblScope>, Error>>()?,
+        })
+    }
+}
+
+impl FromXml for Imprint {
+    /// Pairs each `<pubPlace>` with the `<publisher>` that follows it.
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("imprint")?;
+        let mut publications: Vec<Publication> = vec![];
+        for child in &element.children {
+            match child.name.as_str() {
+                "pubPlace" => publications.push(Publication {
+                    pub_place: Some(child.text().to_string()),
+                    publisher: None,
+                }),
+                "publisher" => match publications.last_mut() {
+                    Some(publication) if publication.publisher.is_none() => {
+                        publication.publisher = Some(child.text().to_string());
+                    }
+                    _ => publications.push(Publication {
+                        pub_place: None,
+                        publisher: Some(child.text().to_string()),
+                    }),
+                },
+                _ => {}
+            }
+        }
+        Ok(Self {
+            publications,
+            date: element.child_text("date"),
+        })
+    }
+}
+
+impl FromXml for BiblScope {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("biblScope")?;
+        Ok(Self {
+            scope_type: element.attribute("type").map(str::to_string),
+            value: element.text().to_string(),
+        })
+    }
+}
+
+impl FromXml for Body {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("body")?;
+        Ok(Self {
+            sentences: element
+                .children("sentence")
+                .map(Sentence::from_element)
+                .collect::<Result<Vec<Sentence>, Error>>()?,
+        })
+    }
+}
+
+impl FromXml for Sentence {
+    /// Besides the `<word>` children, the sentence level annotation tags written by Arethusa
+    /// (`<primary>`, `<secondary>` and `<annotator>`) are read, as well as any other attribute.
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("sentence")?;
+        let id = parse_u32(element.required_attribute("id")?, "id", element)?;
+        let texts = |name| {
+            element
+                .children(name)
+                .map(|child| child.text().to_string())
+                .filter(|text| !text.is_empty())
+                .collect()
+        };
+        Ok(Self {
+            id,
+            document_id: element.required_attribute("document_id")?.to_string(),
+            subdoc: element.required_attribute("subdoc")?.to_string(),
+            attributes: element.other_attributes(&["id", "document_id", "subdoc"]),
+            primary: texts("primary"),
+            secondary: texts("secondary"),
+            annotators: element
+                .children("annotator")
+                .map(read_pers_info)
+                .collect::<Result<Vec<PersInfo>, Error>>()
+                .map_err(|error| error.in_sentence(id))?,
+            comments: vec![],
+            multiword_tokens: vec![],
+            words: element
+                .children("word")
+                .map(Token::from_element)
+                .collect::<Result<Vec<Token>, Error>>()
+                .map_err(|error| error.in_sentence(id))?,
+        })
+    }
+}
+
+/// Attributes of `<word>` read into their own fields of [`Token`].
+const WORD_ATTRIBUTES: [&str; 9] = [
+    "id",
+    "form",
+    "lemma",
+    "postag",
+    "insertion_id",
+    "artificial",
+    "relation",
+    "head",
+    "cite",
+];
+
+impl FromXml for Token {
+    /// A missing or empty `head` is read as `0`. A non-empty `postag` must have exactly nine
+    /// positions. Attributes without a field of their own are kept in
+    /// [`Token::attributes`].
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        element.expect_name("word")?;
+        let head = match element.attribute("head") {
+            Some(head) if !head.trim().is_empty() => {
+                head.trim().parse().map_err(|_| Error::InvalidHead {
+                    head: head.to_string(),
+                    location: element.location(),
+                })?
+            }
+            _ => 0,
+        };
+        if let Some(postag) = element.attribute("postag") {
+            let length = postag.chars().count();
+            if length != 0 && length != 9 {
+                return Err(Error::InvalidPostag {
+                    postag: postag.to_string(),
+                    reason: format!("expected 9 positions, found {length}"),
+                    location: element.location(),
+                });
+            }
+        }
+        Ok(Self {
+            id: parse_u32(element.required_attribute("id")?, "id", element)?,
+            form: element.required_attribute("form")?.to_string(),
+            lemma: element.attribute("lemma").map(str::to_string),
+            postag: element.attribute("postag").map(str::to_string),
+            insertion_id: element.attribute("insertion_id").map(str::to_string),
+            artificial: element.attribute("artificial").map(str::to_string),
+            relation: element.required_attribute("relation")?.to_string(),
+            head,
+            cite: element.attribute("cite").map(str::to_string),
+            attributes: element.other_attributes(&WORD_ATTRIBUTES),
+            ud: None,
+        })
+    }
+}
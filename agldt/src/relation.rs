@@ -0,0 +1,275 @@
+use crate::error::{Error, Location};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Base labels of the AGLDT 2.0 guidelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseRelation {
+    /// Predicate of a main clause.
+    Pred,
+    /// Subject.
+    Sbj,
+    /// Object.
+    Obj,
+    /// Attribute.
+    Atr,
+    /// Adverbial.
+    Adv,
+    /// Complement (attributive verbal), not governed by the verb.
+    Atv,
+    /// Complement (attributive verbal), governed by the verb.
+    AtvV,
+    /// Predicate nominal.
+    Pnom,
+    /// Object complement.
+    Ocomp,
+    /// Coordinator.
+    Coord,
+    /// Apposing element.
+    Apos,
+    /// Preposition.
+    AuxP,
+    /// Conjunction.
+    AuxC,
+    /// Reflexive passive.
+    AuxR,
+    /// Auxiliary verb.
+    AuxV,
+    /// Comma.
+    AuxX,
+    /// Bracketing punctuation.
+    AuxG,
+    /// Terminal punctuation.
+    AuxK,
+    /// Sentence adverbials.
+    AuxY,
+    /// Emphasizing particles.
+    AuxZ,
+    /// Ellipsis, for a node whose governor is elided.
+    ExD,
+}
+
+impl BaseRelation {
+    /// Every base label, in the order of the guidelines.
+    pub const ALL: [Self; 21] = [
+        Self::Pred,
+        Self::Sbj,
+        Self::Obj,
+        Self::Atr,
+        Self::Adv,
+        Self::Atv,
+        Self::AtvV,
+        Self::Pnom,
+        Self::Ocomp,
+        Self::Coord,
+        Self::Apos,
+        Self::AuxP,
+        Self::AuxC,
+        Self::AuxR,
+        Self::AuxV,
+        Self::AuxX,
+        Self::AuxG,
+        Self::AuxK,
+        Self::AuxY,
+        Self::AuxZ,
+        Self::ExD,
+    ];
+
+    /// The label as written in AGLDT files.
+    #[must_use]
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pred => "PRED",
+            Self::Sbj => "SBJ",
+            Self::Obj => "OBJ",
+            Self::Atr => "ATR",
+            Self::Adv => "ADV",
+            Self::Atv => "ATV",
+            Self::AtvV => "AtvV",
+            Self::Pnom => "PNOM",
+            Self::Ocomp => "OCOMP",
+            Self::Coord => "COORD",
+            Self::Apos => "APOS",
+            Self::AuxP => "AuxP",
+            Self::AuxC => "AuxC",
+            Self::AuxR => "AuxR",
+            Self::AuxV => "AuxV",
+            Self::AuxX => "AuxX",
+            Self::AuxG => "AuxG",
+            Self::AuxK => "AuxK",
+            Self::AuxY => "AuxY",
+            Self::AuxZ => "AuxZ",
+            Self::ExD => "ExD",
+        }
+    }
+
+    /// Whether the label is one of the auxiliary ones (`Aux*`).
+    #[must_use]
+    pub fn is_auxiliary(&self) -> bool {
+        self.label().starts_with("Aux")
+    }
+}
+
+impl Display for BaseRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl FromStr for BaseRelation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|base| base.label() == s)
+            .ok_or_else(|| invalid_relation(s, format!("unknown label `{s}`")))
+    }
+}
+
+/// An `ExD<index>_<target>` component: the node stands for an elided node with the relation
+/// `target`, numbered `index` within the sentence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ellipsis {
+    pub index: u32,
+    pub target: BaseRelation,
+}
+
+impl Display for Ellipsis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExD{}_{}", self.index, self.target)
+    }
+}
+
+/// A dependency relation label, decomposed into its base label and suffixes.
+///
+/// The suffixes are written in the order used by AGLDT, e.g. `SBJ_ExD0_ADV`, `OBJ_AP_CO`:
+/// ellipses first, then apposition (`_AP`), then coordination (`_CO`).
+///
+/// ```
+/// use agldt::relation::{BaseRelation, Relation};
+///
+/// let relation: Relation = "OBJ_AP_CO".parse().unwrap();
+/// assert_eq!(relation.base, BaseRelation::Obj);
+/// assert!(relation.is_coordinated() && relation.is_apposition());
+/// assert_eq!(relation.to_string(), "OBJ_AP_CO");
+///
+/// let relation: Relation = "SBJ_ExD0_ADV".parse().unwrap();
+/// assert_eq!(relation.ellipses[0].target, BaseRelation::Adv);
+/// assert!("OBJ_CO_AP".parse::<Relation>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Relation {
+    pub base: BaseRelation,
+    pub ellipses: Vec<Ellipsis>,
+    pub apposition: bool,
+    pub coordination: bool,
+}
+
+impl Relation {
+    /// A relation with no suffixes.
+    #[must_use]
+    pub fn new(base: BaseRelation) -> Self {
+        Self {
+            base,
+            ellipses: vec![],
+            apposition: false,
+            coordination: false,
+        }
+    }
+
+    /// Whether the node is a member of a coordination (`_CO`).
+    #[must_use]
+    pub fn is_coordinated(&self) -> bool {
+        self.coordination
+    }
+
+    /// Whether the node is a member of an apposition (`_AP`).
+    #[must_use]
+    pub fn is_apposition(&self) -> bool {
+        self.apposition
+    }
+
+    /// Whether the label refers to an elided node, either as `ExD` or as an `ExD<n>` suffix.
+    #[must_use]
+    pub fn is_elliptic(&self) -> bool {
+        self.base == BaseRelation::ExD || !self.ellipses.is_empty()
+    }
+
+    /// Whether the base label is `base`, whatever the suffixes, e.g. `OBJ_CO` is an object.
+    #[must_use]
+    pub fn is(&self, base: BaseRelation) -> bool {
+        self.base == base
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.base)?;
+        for ellipsis in &self.ellipses {
+            write!(f, "_{ellipsis}")?;
+        }
+        if self.apposition {
+            write!(f, "_AP")?;
+        }
+        if self.coordination {
+            write!(f, "_CO")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Relation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('_');
+        let base = parts
+            .next()
+            .unwrap_or_default()
+            .parse::<BaseRelation>()
+            .map_err(|_| invalid_relation(s, "unknown base label"))?;
+        let mut relation = Self::new(base);
+        while let Some(part) = parts.next() {
+            match part {
+                "AP" if !relation.apposition && !relation.coordination => {
+                    relation.apposition = true;
+                }
+                "CO" if !relation.coordination => relation.coordination = true,
+                "AP" | "CO" => {
+                    return Err(invalid_relation(
+                        s,
+                        format!("misplaced or repeated suffix `_{part}`"),
+                    ));
+                }
+                _ if part.starts_with("ExD") => {
+                    if relation.apposition || relation.coordination {
+                        return Err(invalid_relation(
+                            s,
+                            "`_ExD` must come before `_AP` and `_CO`",
+                        ));
+                    }
+                    let index = part[3..]
+                        .parse::<u32>()
+                        .map_err(|_| invalid_relation(s, format!("invalid ellipsis `{part}`")))?;
+                    let target = parts
+                        .next()
+                        .ok_or_else(|| invalid_relation(s, format!("`{part}` has no target")))?
+                        .parse::<BaseRelation>()
+                        .map_err(|_| invalid_relation(s, format!("invalid target of `{part}`")))?;
+                    relation.ellipses.push(Ellipsis { index, target });
+                }
+                _ => return Err(invalid_relation(s, format!("unknown suffix `_{part}`"))),
+            }
+        }
+        Ok(relation)
+    }
+}
+
+fn invalid_relation(relation: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidRelation {
+        relation: relation.to_string(),
+        reason: reason.into(),
+        location: Location::default(),
+    }
+}
@@ -0,0 +1,239 @@
+use crate::error::Error;
+use crate::parser::{Sentence, Token};
+use crate::writer::escape;
+use std::io::Write;
+
+/// Font size of the forms in SVG pictures, in pixels.
+const FONT_SIZE: usize = 14;
+/// Font size of the lemmata, postags and relations in SVG pictures, in pixels.
+const SMALL_FONT_SIZE: usize = 11;
+/// Vertical distance between two levels of the tree in SVG pictures.
+const LEVEL_HEIGHT: usize = 90;
+/// Height of the three lines of text of a node in SVG pictures.
+const NODE_HEIGHT: usize = 44;
+/// Blank space around the tree and between two nodes in SVG pictures.
+const MARGIN: usize = 16;
+
+/// Writes the sentence as a Graphviz DOT digraph.
+///
+/// Nodes are labelled with the form, the lemma and the postag of the tokens, and edges, going
+/// from the head to its dependent, with the relation. Artificial nodes are dashed and greyed
+/// out. The root of the sentence is a node of its own, `w0`, labelled with the `subdoc`.
+///
+/// The output can be drawn with `dot -Tsvg`, which keeps the dependents of a node in the order
+/// of the text.
+///
+/// ```
+/// use agldt::parser::Treebank;
+/// use agldt::render::write_dot;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut dot = vec![];
+/// write_dot(&treebank.sentences()[2], &mut dot).unwrap();
+/// let dot = String::from_utf8(dot).unwrap();
+/// assert!(dot.starts_with("digraph \"urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.2@3\" {"));
+/// assert!(dot.contains("w6 [label=\"ἐν\\nἐν\\nr--------\"];"));
+/// assert!(dot.contains("w27 [label=\"[0]\", style=dashed, fontcolor=gray40];"));
+/// assert!(dot.contains("w8 -> w6 [label=\"AuxP\"];"));
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`crate::tree::DependencyTree::new`] if the sentence is not a tree.
+pub fn write_dot<W: Write>(sentence: &Sentence, writer: &mut W) -> Result<(), Error> {
+    sentence.tree()?;
+    let tokens = sentence.tokens_in_text_order();
+    writeln!(
+        writer,
+        "digraph \"{}\" {{",
+        dot_escape(&sentence.citation_id())
+    )?;
+    writeln!(writer, "  graph [ordering=out];")?;
+    writeln!(writer, "  node [shape=plaintext];")?;
+    writeln!(writer, "  w0 [label=\"{}\"];", dot_escape(&sentence.subdoc))?;
+    for token in &tokens {
+        let label = lines(token)
+            .iter()
+            .map(|line| dot_escape(line))
+            .collect::<Vec<String>>()
+            .join("\\n");
+        if token.is_artificial() {
+            writeln!(
+                writer,
+                "  w{} [label=\"{label}\", style=dashed, fontcolor=gray40];",
+                token.id
+            )?;
+        } else {
+            writeln!(writer, "  w{} [label=\"{label}\"];", token.id)?;
+        }
+    }
+    for token in &tokens {
+        writeln!(
+            writer,
+            "  w{} -> w{} [label=\"{}\"];",
+            token.head,
+            token.id,
+            dot_escape(&token.relation)
+        )?;
+    }
+    writeln!(writer, "}}")?;
+    Ok(())
+}
+
+/// Writes the sentence as an SVG picture of its tree, laid out without Graphviz.
+///
+/// Tokens keep the order of the text from left to right, one column each, and hang below their
+/// head, one level per step from the root. As in [`write_dot`], nodes show the form, the lemma
+/// and the postag, edges the relation, and artificial nodes are greyed out.
+///
+/// ```
+/// use agldt::parser::Treebank;
+/// use agldt::render::write_svg;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let mut svg = vec![];
+/// write_svg(&treebank.sentences()[2], &mut svg).unwrap();
+/// let svg = String::from_utf8(svg).unwrap();
+/// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+/// assert!(svg.contains(">Σπάρτῃ</text>"));
+/// assert!(svg.trim_end().ends_with("</svg>"));
+/// ```
+///
+/// # Errors
+///
+/// This function will return [`Error::Io`] if writing to `writer` fails, and the errors of
+/// [`crate::tree::DependencyTree::new`] if the sentence is not a tree.
+pub fn write_svg<W: Write>(sentence: &Sentence, writer: &mut W) -> Result<(), Error> {
+    let tree = sentence.tree()?;
+    let tokens = sentence.tokens_in_text_order();
+
+    // Each token gets a column as wide as its longest line, the root sits above the middle.
+    let mut x = MARGIN;
+    let mut columns = vec![];
+    for token in &tokens {
+        let width = lines(token)
+            .iter()
+            .map(|line| text_width(line, FONT_SIZE))
+            .max()
+            .unwrap_or_default();
+        columns.push(x + width / 2);
+        x += width + MARGIN;
+    }
+    let width = x.max(text_width(&sentence.subdoc, FONT_SIZE) + 2 * MARGIN);
+    let levels = tokens
+        .iter()
+        .filter_map(|token| tree.depth(token.id))
+        .max()
+        .map_or(1, |depth| depth + 2);
+    let height = 2 * MARGIN + (levels - 1) * LEVEL_HEIGHT + NODE_HEIGHT;
+    let top = |depth: usize| MARGIN + depth * LEVEL_HEIGHT;
+    let position = |id: u32| -> (usize, usize) {
+        match tokens.iter().position(|token| token.id == id) {
+            Some(index) => (columns[index], top(tree.depth(id).unwrap_or_default() + 1)),
+            None => (width / 2, top(0)),
+        }
+    };
+    // Edges go from below the head, or below the label of the root, to above the dependent.
+    let edge = |token: &Token| -> (usize, usize, usize, usize) {
+        let (x1, y1) = position(token.head);
+        let (x2, y2) = position(token.id);
+        let below = if token.head == 0 {
+            FONT_SIZE + 4
+        } else {
+            NODE_HEIGHT
+        };
+        (x1, y1 + below, x2, y2 - 2)
+    };
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="serif" font-size="{FONT_SIZE}">"#
+    )?;
+    writeln!(
+        writer,
+        "  <title>{}</title>",
+        escape(&sentence.citation_id())
+    )?;
+    writeln!(writer, r##"  <g class="edges" stroke="#777" fill="none">"##)?;
+    for token in &tokens {
+        let (x1, y1, x2, y2) = edge(token);
+        writeln!(
+            writer,
+            r#"    <line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"/>"#
+        )?;
+    }
+    writeln!(writer, "  </g>")?;
+    writeln!(
+        writer,
+        r##"  <g class="relations" font-size="{SMALL_FONT_SIZE}" fill="#a33" text-anchor="middle" stroke="white" stroke-width="3" paint-order="stroke">"##
+    )?;
+    for token in &tokens {
+        let (x1, y1, x2, y2) = edge(token);
+        writeln!(
+            writer,
+            r#"    <text x="{}" y="{}">{}</text>"#,
+            (x1 + x2) / 2,
+            (y1 + y2) / 2,
+            escape(&token.relation)
+        )?;
+    }
+    writeln!(writer, "  </g>")?;
+    writeln!(writer, r#"  <g class="nodes" text-anchor="middle">"#)?;
+    writeln!(
+        writer,
+        r#"    <text x="{}" y="{}" font-weight="bold">{}</text>"#,
+        width / 2,
+        top(0) + FONT_SIZE,
+        escape(&sentence.subdoc)
+    )?;
+    for token in &tokens {
+        let (x, y) = position(token.id);
+        let style = if token.is_artificial() {
+            r##" fill="#888" font-style="italic""##
+        } else {
+            ""
+        };
+        writeln!(writer, r#"    <g class="token" id="w{}"{style}>"#, token.id)?;
+        let mut baseline = y + FONT_SIZE;
+        for (i, line) in lines(token).iter().enumerate() {
+            if i == 0 {
+                writeln!(
+                    writer,
+                    r#"      <text x="{x}" y="{baseline}">{}</text>"#,
+                    escape(line)
+                )?;
+            } else {
+                writeln!(
+                    writer,
+                    r##"      <text x="{x}" y="{baseline}" font-size="{SMALL_FONT_SIZE}" fill="#555">{}</text>"##,
+                    escape(line)
+                )?;
+            }
+            baseline += SMALL_FONT_SIZE + 3;
+        }
+        writeln!(writer, "    </g>")?;
+    }
+    writeln!(writer, "  </g>")?;
+    writeln!(writer, "</svg>")?;
+    Ok(())
+}
+
+/// The form, lemma and postag of the token, leaving out those it lacks.
+fn lines(token: &Token) -> Vec<&str> {
+    std::iter::once(token.form.as_str())
+        .chain(token.lemma.as_deref())
+        .chain(token.postag.as_deref())
+        .collect()
+}
+
+/// Approximate width of `text` at `font_size`, about the width of an average serif letter.
+fn text_width(text: &str, font_size: usize) -> usize {
+    text.chars().count() * font_size * 3 / 5
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
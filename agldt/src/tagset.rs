@@ -0,0 +1,322 @@
+use crate::error::{Error, Location};
+use crate::features::{
+    Case, Degree, Gender, Mood, Number, PartOfSpeech, Person, Postag, PostagFeature, Tense, Voice,
+    POSITIONS,
+};
+use crate::parser::{Sentence, Token, Treebank};
+use crate::relation::{BaseRelation, Relation};
+use crate::validate::{Diagnostic, DiagnosticKind, Severity};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Rules checked by [`TagsetChecker`], each with its own severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// The postag does not have nine positions, or holds a character unknown at its position.
+    MalformedPostag,
+    /// A feature is set at a position that the part of speech does not use, e.g. a noun with a
+    /// tense, or a verb with a case when it is not a participle.
+    MisplacedFeature,
+    /// The relation label does not follow the AGLDT guidelines.
+    UnknownRelation,
+    /// The relation is not allowed for the part of speech, e.g. `AuxP` on a non-preposition.
+    RelationPos,
+}
+
+impl Rule {
+    pub const ALL: [Self; 4] = [
+        Self::MalformedPostag,
+        Self::MisplacedFeature,
+        Self::UnknownRelation,
+        Self::RelationPos,
+    ];
+
+    /// Name of the rule, as used by the command line.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MalformedPostag => "malformed-postag",
+            Self::MisplacedFeature => "misplaced-feature",
+            Self::UnknownRelation => "unknown-relation",
+            Self::RelationPos => "relation-pos",
+        }
+    }
+
+    /// Severity of the rule when no other is configured.
+    #[must_use]
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Self::MalformedPostag | Self::UnknownRelation => Severity::Error,
+            Self::MisplacedFeature | Self::RelationPos => Severity::Warning,
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| format!("unknown rule `{s}`"))
+    }
+}
+
+/// Checks postags and relation labels against the AGLDT tagset.
+///
+/// Every [`Rule`] starts with its [`Rule::default_severity`], which can be changed, or the rule
+/// disabled, with [`TagsetChecker::severity`].
+///
+/// ```
+/// use agldt::parser::Treebank;
+/// use agldt::tagset::{Rule, TagsetChecker};
+/// use agldt::validate::{DiagnosticKind, Severity};
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+///
+/// // Sentence 382 coordinates with two prepositions.
+/// let coord_on_preposition: Vec<_> = TagsetChecker::new()
+///     .check(&treebank)
+///     .into_iter()
+///     .filter(|diagnostic| diagnostic.location.sentence_id == Some(382))
+///     .collect();
+/// assert_eq!(coord_on_preposition.len(), 2);
+/// for (diagnostic, word_id) in coord_on_preposition.iter().zip([5, 12]) {
+///     assert_eq!(diagnostic.severity, Severity::Warning);
+///     assert_eq!(diagnostic.location.word_id, Some(word_id));
+///     assert_eq!(
+///         diagnostic.kind,
+///         DiagnosticKind::RelationPos { relation: "COORD".to_string(), pos: "r".to_string() }
+///     );
+/// }
+///
+/// let checker = TagsetChecker::new()
+///     .severity(Rule::RelationPos, Some(Severity::Error))
+///     .severity(Rule::MisplacedFeature, None);
+/// let diagnostics = checker.check(&treebank);
+/// assert_eq!(diagnostics.len(), 12);
+/// assert!(diagnostics.iter().all(|diagnostic| diagnostic.is_error()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TagsetChecker {
+    severities: HashMap<Rule, Option<Severity>>,
+}
+
+impl Default for TagsetChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TagsetChecker {
+    /// A checker with every rule enabled at its default severity.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            severities: Rule::ALL
+                .into_iter()
+                .map(|rule| (rule, Some(rule.default_severity())))
+                .collect(),
+        }
+    }
+
+    /// Sets the severity of `rule`, or disables it with `None`.
+    #[must_use]
+    pub fn severity(mut self, rule: Rule, severity: Option<Severity>) -> Self {
+        self.severities.insert(rule, severity);
+        self
+    }
+
+    /// Checks every sentence of the treebank.
+    #[must_use]
+    pub fn check(&self, treebank: &Treebank) -> Vec<Diagnostic> {
+        treebank
+            .body
+            .sentences
+            .iter()
+            .flat_map(|sentence| self.check_sentence(sentence))
+            .collect()
+    }
+
+    /// Checks every word of the sentence.
+    #[must_use]
+    pub fn check_sentence(&self, sentence: &Sentence) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for token in &sentence.words {
+            for (rule, kind) in check_token(token) {
+                if let Some(Some(severity)) = self.severities.get(&rule) {
+                    diagnostics.push(Diagnostic {
+                        severity: *severity,
+                        kind,
+                        location: Location {
+                            sentence_id: Some(sentence.id),
+                            word_id: Some(token.id),
+                            position: None,
+                        },
+                    });
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+fn check_token(token: &Token) -> Vec<(Rule, DiagnosticKind)> {
+    let mut found = vec![];
+
+    let postag = match token.postag.as_deref() {
+        None | Some("") => None,
+        Some(postag) => match check_characters(postag) {
+            Ok(postag) => Some(postag),
+            Err(reason) => {
+                found.push((
+                    Rule::MalformedPostag,
+                    DiagnosticKind::MalformedPostag { reason },
+                ));
+                None
+            }
+        },
+    };
+
+    if let Some(postag) = &postag {
+        let allowed = allowed_positions(postag);
+        for feature in postag.features() {
+            if !allowed.contains(&feature.index()) {
+                found.push((
+                    Rule::MisplacedFeature,
+                    DiagnosticKind::MisplacedFeature {
+                        postag: postag.to_string(),
+                        position: POSITIONS[usize::from(feature.index())],
+                    },
+                ));
+            }
+        }
+    }
+
+    match token.relation.parse::<Relation>() {
+        Err(error) => {
+            let reason = match error {
+                Error::InvalidRelation { reason, .. } => reason,
+                other => other.to_string(),
+            };
+            found.push((
+                Rule::UnknownRelation,
+                DiagnosticKind::UnknownRelation {
+                    relation: token.relation.clone(),
+                    reason,
+                },
+            ));
+        }
+        Ok(relation) => {
+            if let Some(pos) = postag.and_then(|postag| postag.pos) {
+                if !relation_allows(relation.base, pos) {
+                    found.push((
+                        Rule::RelationPos,
+                        DiagnosticKind::RelationPos {
+                            relation: token.relation.clone(),
+                            pos: pos.to_string(),
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// Parses the postag, reporting every position holding an unknown character.
+fn check_characters(postag: &str) -> Result<Postag, String> {
+    let chars: Vec<char> = postag.chars().collect();
+    if chars.len() != 9 {
+        return Err(format!("expected 9 positions, found {}", chars.len()));
+    }
+    let unknown: Vec<String> = chars
+        .iter()
+        .enumerate()
+        .filter(|(index, c)| **c != '-' && !is_known(*index, **c))
+        .map(|(index, c)| format!("`{c}` for {}", POSITIONS[index]))
+        .collect();
+    if unknown.is_empty() {
+        postag.parse::<Postag>().map_err(|error| error.to_string())
+    } else {
+        Err(format!("unknown {}", unknown.join(", ")))
+    }
+}
+
+fn is_known(index: usize, c: char) -> bool {
+    match index {
+        0 => PartOfSpeech::from_agldt_postag(c).is_ok(),
+        1 => Person::from_agldt_postag(c).is_ok(),
+        2 => Number::from_agldt_postag(c).is_ok(),
+        3 => Tense::from_agldt_postag(c).is_ok(),
+        4 => Mood::from_agldt_postag(c).is_ok(),
+        5 => Voice::from_agldt_postag(c).is_ok(),
+        6 => Gender::from_agldt_postag(c).is_ok(),
+        7 => Case::from_agldt_postag(c).is_ok(),
+        8 => Degree::from_agldt_postag(c).is_ok(),
+        _ => false,
+    }
+}
+
+/// Positions of the postag that may be set, given its part of speech and, for verbs, its mood.
+fn allowed_positions(postag: &Postag) -> &'static [u8] {
+    match postag.pos {
+        None => &[0, 1, 2, 3, 4, 5, 6, 7, 8],
+        Some(PartOfSpeech::Noun | PartOfSpeech::Article | PartOfSpeech::Numeral) => &[0, 2, 6, 7],
+        Some(PartOfSpeech::Adjective) => &[0, 2, 6, 7, 8],
+        Some(PartOfSpeech::Pronoun) => &[0, 1, 2, 6, 7],
+        Some(PartOfSpeech::Adverb) => &[0, 8],
+        Some(PartOfSpeech::Participle) => &[0, 2, 3, 4, 5, 6, 7],
+        Some(PartOfSpeech::Verb) => match postag.mood {
+            None => &[0, 1, 2, 3, 4, 5, 6, 7],
+            Some(Mood::Indicative | Mood::Subjunctive | Mood::Optative | Mood::Imperative) => {
+                &[0, 1, 2, 3, 4, 5]
+            }
+            Some(Mood::Infinitive) => &[0, 3, 4, 5],
+            Some(Mood::Participle | Mood::Gerundive) => &[0, 2, 3, 4, 5, 6, 7],
+            Some(Mood::Gerund | Mood::Supine) => &[0, 3, 4, 5, 7],
+        },
+        Some(
+            PartOfSpeech::Conjunction
+            | PartOfSpeech::Preposition
+            | PartOfSpeech::Particle
+            | PartOfSpeech::Interjection
+            | PartOfSpeech::Exclamation
+            | PartOfSpeech::Punctuation
+            | PartOfSpeech::Irregular,
+        ) => &[0],
+    }
+}
+
+/// Whether the guidelines allow the relation for a word with the part of speech `pos`.
+fn relation_allows(base: BaseRelation, pos: PartOfSpeech) -> bool {
+    match base {
+        BaseRelation::AuxP => pos == PartOfSpeech::Preposition,
+        // Subordinating adverbs (ὡς, ὅτε, ἐπεί) and relatives introduce clauses as well.
+        BaseRelation::AuxC => matches!(
+            pos,
+            PartOfSpeech::Conjunction | PartOfSpeech::Adverb | PartOfSpeech::Pronoun
+        ),
+        BaseRelation::AuxK | BaseRelation::AuxX | BaseRelation::AuxG => {
+            pos == PartOfSpeech::Punctuation
+        }
+        BaseRelation::AuxV => pos == PartOfSpeech::Verb,
+        BaseRelation::Coord | BaseRelation::Apos => matches!(
+            pos,
+            PartOfSpeech::Conjunction | PartOfSpeech::Punctuation | PartOfSpeech::Adverb
+        ),
+        // Punctuation only heads coordinations and appositions, or marks commas and ends of
+        // sentences.
+        _ => pos != PartOfSpeech::Punctuation,
+    }
+}
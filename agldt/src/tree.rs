@@ -0,0 +1,209 @@
+use crate::error::{Error, Location};
+use crate::parser::{Sentence, Token};
+use std::collections::HashMap;
+
+/// A view of a [`Sentence`] as a dependency tree, following the `head` of its tokens.
+///
+/// Tokens with `head = 0`, including those whose `head` was empty or missing in the source, are
+/// roots of the tree. AGLDT attaches both the main predicate and the final punctuation to the
+/// root, so a sentence usually has more than one of them. Artificial tokens, standing for elided
+/// nodes, are part of the tree like any other token, but are left out of
+/// [`DependencyTree::span`].
+///
+/// ```
+/// use agldt::parser::Treebank;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// let sentence = &treebank.sentences()[0];
+/// let tree = sentence.tree().unwrap();
+///
+/// let root = tree.root().unwrap();
+/// assert_eq!(tree.depth(root.id()), Some(0));
+/// assert_eq!(tree.descendants(root.id()).count() + 1, tree.subtree(root.id()).len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DependencyTree<'a> {
+    tokens: &'a [Token],
+    positions: HashMap<u32, usize>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    roots: Vec<usize>,
+}
+
+impl<'a> DependencyTree<'a> {
+    /// Builds the tree of `sentence`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::DuplicateId`] if two tokens share an id,
+    /// [`Error::InvalidHead`] if the `head` of a token references no token of the sentence, and
+    /// [`Error::HeadCycle`] if following the `head` links from a token never reaches a root.
+    pub fn new(sentence: &'a Sentence) -> Result<Self, Error> {
+        let tokens = sentence.words.as_slice();
+        let mut positions: HashMap<u32, usize> = HashMap::with_capacity(tokens.len());
+        for (position, token) in tokens.iter().enumerate() {
+            if positions.insert(token.id, position).is_some() {
+                return Err(Error::DuplicateId {
+                    location: Location::default(),
+                }
+                .in_word(token.id)
+                .in_sentence(sentence.id));
+            }
+        }
+
+        let mut parents = vec![None; tokens.len()];
+        let mut children = vec![vec![]; tokens.len()];
+        let mut roots = vec![];
+        for (position, token) in tokens.iter().enumerate() {
+            if token.head == 0 {
+                roots.push(position);
+                continue;
+            }
+            let Some(&parent) = positions.get(&token.head) else {
+                return Err(Error::InvalidHead {
+                    head: token.head.to_string(),
+                    location: Location::default(),
+                }
+                .in_word(token.id)
+                .in_sentence(sentence.id));
+            };
+            parents[position] = Some(parent);
+            children[parent].push(position);
+        }
+
+        let tree = Self {
+            tokens,
+            positions,
+            parents,
+            children,
+            roots,
+        };
+        for (position, token) in tokens.iter().enumerate() {
+            if tree
+                .ancestor_positions(position)
+                .nth(tokens.len())
+                .is_some()
+            {
+                return Err(Error::HeadCycle {
+                    location: Location::default(),
+                }
+                .in_word(token.id)
+                .in_sentence(sentence.id));
+            }
+        }
+        Ok(tree)
+    }
+
+    /// Tokens attached to the root of the sentence (`head = 0`), in order.
+    #[must_use]
+    pub fn roots(&self) -> Vec<&'a Token> {
+        self.roots.iter().map(|&p| &self.tokens[p]).collect()
+    }
+
+    /// The main root of the sentence: the first root that is not punctuation, falling back to
+    /// the first root.
+    #[must_use]
+    pub fn root(&self) -> Option<&'a Token> {
+        let roots = self.roots();
+        roots
+            .iter()
+            .find(|token| !token.postag.as_deref().is_some_and(|p| p.starts_with('u')))
+            .or_else(|| roots.first())
+            .copied()
+    }
+
+    /// The token with the given id.
+    #[must_use]
+    pub fn token(&self, id: u32) -> Option<&'a Token> {
+        self.positions.get(&id).map(|&p| &self.tokens[p])
+    }
+
+    /// The head of the token `id`, or `None` if it is a root or not in the sentence.
+    #[must_use]
+    pub fn parent(&self, id: u32) -> Option<&'a Token> {
+        let position = *self.positions.get(&id)?;
+        self.parents[position].map(|p| &self.tokens[p])
+    }
+
+    /// The direct dependents of the token `id`, in order.
+    pub fn children(&self, id: u32) -> impl Iterator<Item = &'a Token> + '_ {
+        self.positions
+            .get(&id)
+            .map(|&p| self.children[p].as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|&p| &self.tokens[p])
+    }
+
+    /// Every token dominated by the token `id`, depth first.
+    pub fn descendants(&self, id: u32) -> impl Iterator<Item = &'a Token> + '_ {
+        let mut stack: Vec<usize> = self
+            .positions
+            .get(&id)
+            .map(|&p| self.children[p].iter().rev().copied().collect())
+            .unwrap_or_default();
+        std::iter::from_fn(move || {
+            let position = stack.pop()?;
+            stack.extend(self.children[position].iter().rev());
+            Some(&self.tokens[position])
+        })
+    }
+
+    /// The heads of the token `id`, from its parent up to a root.
+    pub fn ancestors(&self, id: u32) -> impl Iterator<Item = &'a Token> + '_ {
+        let start = self.positions.get(&id).copied();
+        start
+            .into_iter()
+            .flat_map(|p| self.ancestor_positions(p))
+            .map(|p| &self.tokens[p])
+    }
+
+    fn ancestor_positions(&self, position: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.parents[position], |&p| self.parents[p])
+    }
+
+    /// The token `id` followed by its ancestors, up to a root.
+    #[must_use]
+    pub fn path_to_root(&self, id: u32) -> Vec<&'a Token> {
+        self.token(id)
+            .into_iter()
+            .chain(self.ancestors(id))
+            .collect()
+    }
+
+    /// Number of heads between the token `id` and a root, which has depth 0.
+    #[must_use]
+    pub fn depth(&self, id: u32) -> Option<usize> {
+        self.token(id).map(|_| self.ancestors(id).count())
+    }
+
+    /// The token `id` and its descendants, in the order of the sentence.
+    #[must_use]
+    pub fn subtree(&self, id: u32) -> Vec<&'a Token> {
+        let Some(token) = self.token(id) else {
+            return vec![];
+        };
+        let mut subtree: Vec<&Token> = std::iter::once(token).chain(self.descendants(id)).collect();
+        subtree.sort_by_key(|token| self.positions[&token.id]);
+        subtree
+    }
+
+    /// Ids of the first and last tokens of the subtree of the token `id`, leaving out
+    /// artificial tokens, which have no place in the text.
+    ///
+    /// Returns `None` if the token is not in the sentence or if its subtree is made only of
+    /// artificial tokens.
+    #[must_use]
+    pub fn span(&self, id: u32) -> Option<(u32, u32)> {
+        let ids = self
+            .subtree(id)
+            .into_iter()
+            .filter(|token| token.artificial.is_none())
+            .map(|token| token.id);
+        ids.fold(None, |span, id| match span {
+            None => Some((id, id)),
+            Some((first, last)) => Some((first.min(id), last.max(id))),
+        })
+    }
+}
@@ -2,52 +2,75 @@ use crate::parser::{Body, Sentence, Token, Treebank};
 use icu::collator::{Collator, CollatorOptions, Strength};
 use icu::locid::{locale, Locale};
 
+/// Which tokens to yield with respect to artificial nodes, see [`Token::is_artificial`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Artificial {
+    /// Yields every token.
+    #[default]
+    Include,
+    /// Yields only the tokens found in the text.
+    Exclude,
+    /// Yields only the artificial nodes.
+    Only,
+}
+
+/// Iterates over the tokens of a treebank, or of a part of it, without cloning them.
 pub trait IterTokens {
-    fn iter_tokens(&self) -> std::vec::IntoIter<Token>;
+    /// Iterates over the tokens along with the sentence each one belongs to.
+    fn iter_tokens_in_context(&self) -> impl Iterator<Item = (&Sentence, &Token)>;
+
+    fn iter_tokens(&self) -> impl Iterator<Item = &Token> {
+        self.iter_tokens_in_context().map(|(_, token)| token)
+    }
+
+    /// Iterates over the tokens, keeping or leaving out the artificial nodes.
+    fn iter_tokens_with(&self, artificial: Artificial) -> impl Iterator<Item = &Token> {
+        self.iter_tokens().filter(move |token| match artificial {
+            Artificial::Include => true,
+            Artificial::Exclude => !token.is_artificial(),
+            Artificial::Only => token.is_artificial(),
+        })
+    }
 }
 
 impl IterTokens for Treebank {
-    fn iter_tokens(&self) -> std::vec::IntoIter<Token> {
-        self.body().iter_tokens()
+    fn iter_tokens_in_context(&self) -> impl Iterator<Item = (&Sentence, &Token)> {
+        self.body().iter_tokens_in_context()
     }
 }
 
 impl IterTokens for Body {
-    fn iter_tokens(&self) -> std::vec::IntoIter<Token> {
-        self.clone()
-            .into_iter()
-            .flatten()
-            .collect::<Vec<Token>>()
-            .into_iter()
+    fn iter_tokens_in_context(&self) -> impl Iterator<Item = (&Sentence, &Token)> {
+        self.iter_sentences()
+            .flat_map(IterTokens::iter_tokens_in_context)
     }
 }
 
 impl IterTokens for Sentence {
-    fn iter_tokens(&self) -> std::vec::IntoIter<Token> {
-        self.clone().into_iter()
+    fn iter_tokens_in_context(&self) -> impl Iterator<Item = (&Sentence, &Token)> {
+        self.words().iter().map(move |token| (self, token))
     }
 }
 
 pub trait Lexicon: IterTokens {
-    fn get_lemmata(&self) -> Vec<String> {
+    /// Lemmata of the words, in order and with repetitions.
+    fn get_lemmata(&self) -> Vec<&str> {
         self.iter_tokens()
-            .filter(Token::is_word)
-            .filter_map(|t| t.lemma())
-            .collect::<Vec<String>>()
+            .filter(|token| token.is_word())
+            .filter_map(Token::lemma)
+            .collect::<Vec<&str>>()
     }
     fn build_lexicon(&self) -> Vec<String> {
         let mut lexicon = self.get_lemmata();
         let locale_el: Locale = locale!("el");
         let mut options = CollatorOptions::new();
         options.strength = Some(Strength::Primary);
-        let collator_el: Collator =
-            Collator::try_new_unstable(&icu_testdata::unstable(), &locale_el.into(), options)
-                .unwrap();
+        let collator_el: Collator = Collator::try_new(&locale_el.into(), options).unwrap();
 
-        lexicon.sort();
+        lexicon.sort_unstable();
         lexicon.dedup();
         lexicon.sort_by(|a, b| collator_el.compare(a, b));
-        lexicon
+        lexicon.into_iter().map(str::to_string).collect()
     }
 }
 
@@ -105,10 +128,54 @@ mod tests {
     #[test]
     fn conc() {
         use crate::parser::*;
-        use serde_xml_rs::from_str;
         use std::fs::read_to_string;
         let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
-        let se = from_str::<Treebank>(&preprocess(&src)).unwrap().sentences()[0].clone();
-        dbg!(s_ce(&se));
+        let treebank = Treebank::from_xml_str(&src).unwrap();
+        let se = &treebank.sentences()[0];
+        dbg!(s_ce(se));
+    }
+
+    #[test]
+    fn artificial() {
+        use crate::parser::*;
+        use std::fs::read_to_string;
+        let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+        let treebank = Treebank::from_xml_str(&src).unwrap();
+        assert_eq!(
+            treebank.iter_tokens_with(Artificial::Include).count(),
+            10709
+        );
+        assert_eq!(treebank.iter_tokens_with(Artificial::Only).count(), 139);
+        assert_eq!(
+            treebank.iter_tokens_with(Artificial::Exclude).count(),
+            10570
+        );
+        assert!(treebank
+            .iter_tokens_with(Artificial::Only)
+            .all(|token| token.insertion_id().is_some() && !token.is_word()));
+        assert_eq!(treebank.count_words(), 9384);
+    }
+
+    #[test]
+    fn borrowed_iteration() {
+        use crate::parser::*;
+        use std::fs::read_to_string;
+        let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+        let treebank = Treebank::from_xml_str(&src).unwrap();
+        assert_eq!(treebank.iter_sentences().count(), 434);
+        assert!(treebank
+            .iter_tokens_in_context()
+            .all(|(sentence, token)| sentence.words().contains(token)));
+        let first_len = treebank.sentences()[0].words().len();
+        let (sentence, token) = treebank.iter_tokens_in_context().nth(first_len).unwrap();
+        assert!(std::ptr::eq(sentence, &treebank.sentences()[1]));
+        assert!(std::ptr::eq(token, &treebank.sentences()[1].words()[0]));
+        assert_eq!(
+            treebank.get_lemmata().len(),
+            treebank
+                .iter_tokens()
+                .filter(|token| token.is_word() && token.lemma().is_some())
+                .count()
+        );
     }
 }
@@ -0,0 +1,268 @@
+use crate::error::Location;
+use crate::parser::{Sentence, Treebank};
+use crate::tagset::Rule;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The annotation is unusual, but can still be processed.
+    Warning,
+    /// The annotation is broken, e.g. the sentence is not a tree.
+    Error,
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            _ => Err(format!(
+                "unknown severity `{s}`, expected `warning` or `error`"
+            )),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Problems found in the structure of a sentence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// Following the `head` of the word leads back to the word itself.
+    Cycle,
+    /// The `head` of the word references no word of the sentence.
+    OrphanHead { head: u32 },
+    /// Another word of the sentence has the same `id`.
+    DuplicateId,
+    /// No word of the sentence is attached to the root (`head = 0`).
+    MissingRoot,
+    /// More than one word, besides punctuation, is attached to the root.
+    MultipleRoots { roots: Vec<u32> },
+    /// The word, not being artificial, has no postag.
+    MissingPostag,
+    /// The word, not being artificial, has no lemma.
+    MissingLemma,
+    /// The postag has a wrong length or unknown characters, see [`crate::tagset::Rule`].
+    MalformedPostag { reason: String },
+    /// A feature is set at a position not used by the part of speech.
+    MisplacedFeature {
+        postag: String,
+        position: &'static str,
+    },
+    /// The relation label does not follow the AGLDT guidelines.
+    UnknownRelation { relation: String, reason: String },
+    /// The relation is not allowed for the part of speech of the word.
+    RelationPos { relation: String, pos: String },
+}
+
+impl DiagnosticKind {
+    /// Severity of the problem when no other is configured.
+    #[must_use]
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Self::Cycle | Self::OrphanHead { .. } | Self::DuplicateId | Self::MissingRoot => {
+                Severity::Error
+            }
+            Self::MultipleRoots { .. } | Self::MissingPostag | Self::MissingLemma => {
+                Severity::Warning
+            }
+            Self::MalformedPostag { .. } => Rule::MalformedPostag.default_severity(),
+            Self::MisplacedFeature { .. } => Rule::MisplacedFeature.default_severity(),
+            Self::UnknownRelation { .. } => Rule::UnknownRelation.default_severity(),
+            Self::RelationPos { .. } => Rule::RelationPos.default_severity(),
+        }
+    }
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cycle => write!(f, "the heads of the word form a cycle"),
+            Self::OrphanHead { head } => write!(f, "head {head} references no word"),
+            Self::DuplicateId => write!(f, "duplicate word id"),
+            Self::MissingRoot => write!(f, "no word is attached to the root"),
+            Self::MultipleRoots { roots } => {
+                write!(f, "several words attached to the root: {roots:?}")
+            }
+            Self::MissingPostag => write!(f, "missing postag"),
+            Self::MissingLemma => write!(f, "missing lemma"),
+            Self::MalformedPostag { reason } => write!(f, "malformed postag: {reason}"),
+            Self::MisplacedFeature { postag, position } => {
+                write!(
+                    f,
+                    "postag `{postag}` sets a {position} for its part of speech"
+                )
+            }
+            Self::UnknownRelation { relation, reason } => {
+                write!(f, "invalid relation `{relation}`: {reason}")
+            }
+            Self::RelationPos { relation, pos } => {
+                write!(
+                    f,
+                    "relation `{relation}` not allowed for part of speech `{pos}`"
+                )
+            }
+        }
+    }
+}
+
+/// A problem found while validating a treebank, with where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub location: Location,
+}
+
+impl Diagnostic {
+    fn new(kind: DiagnosticKind, sentence_id: u32, word_id: Option<u32>) -> Self {
+        Self {
+            severity: kind.default_severity(),
+            kind,
+            location: Location {
+                sentence_id: Some(sentence_id),
+                word_id,
+                position: None,
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({})", self.severity, self.kind, self.location)
+    }
+}
+
+/// Checks that every sentence of the treebank is a well-formed dependency tree.
+///
+/// ```
+/// use agldt::parser::Treebank;
+/// use agldt::validate::validate;
+///
+/// let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+/// let treebank = Treebank::from_xml_str(&src).unwrap();
+/// assert!(validate(&treebank).iter().all(|diagnostic| !diagnostic.is_error()));
+/// ```
+#[must_use]
+pub fn validate(treebank: &Treebank) -> Vec<Diagnostic> {
+    treebank
+        .body
+        .sentences
+        .iter()
+        .flat_map(validate_sentence)
+        .collect()
+}
+
+/// Checks that the sentence is a well-formed dependency tree, see [`DiagnosticKind`].
+#[must_use]
+pub fn validate_sentence(sentence: &Sentence) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let words = &sentence.words;
+
+    // With duplicate ids, the first word with the id is the one whose links are followed.
+    let mut heads: HashMap<u32, u32> = HashMap::new();
+    for word in words {
+        if let Entry::Vacant(entry) = heads.entry(word.id) {
+            entry.insert(word.head);
+        } else {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::DuplicateId,
+                sentence.id,
+                Some(word.id),
+            ));
+        }
+    }
+
+    for word in words {
+        if word.head != 0 && !heads.contains_key(&word.head) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::OrphanHead { head: word.head },
+                sentence.id,
+                Some(word.id),
+            ));
+        }
+    }
+
+    let mut in_cycle: Vec<u32> = vec![];
+    for word in words {
+        if in_cycle.contains(&word.id) {
+            continue;
+        }
+        let mut visited = vec![word.id];
+        let mut current = word.head;
+        while let Some(&head) = heads.get(&current) {
+            if current == word.id {
+                in_cycle.extend(&visited);
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::Cycle,
+                    sentence.id,
+                    Some(word.id),
+                ));
+                break;
+            }
+            if visited.contains(&current) {
+                break;
+            }
+            visited.push(current);
+            current = head;
+        }
+    }
+
+    let roots: Vec<u32> = words
+        .iter()
+        .filter(|word| word.head == 0)
+        .filter(|word| !word.postag.as_deref().is_some_and(|p| p.starts_with('u')))
+        .map(|word| word.id)
+        .collect();
+    if words.iter().all(|word| word.head != 0) {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::MissingRoot,
+            sentence.id,
+            None,
+        ));
+    } else if roots.len() > 1 {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::MultipleRoots { roots },
+            sentence.id,
+            None,
+        ));
+    }
+
+    for word in words.iter().filter(|word| word.artificial.is_none()) {
+        if word.postag.as_deref().is_none_or(str::is_empty) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MissingPostag,
+                sentence.id,
+                Some(word.id),
+            ));
+        }
+        if word.lemma.as_deref().is_none_or(str::is_empty) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MissingLemma,
+                sentence.id,
+                Some(word.id),
+            ));
+        }
+    }
+
+    diagnostics
+}
@@ -0,0 +1,326 @@
+use crate::parser::{
+    BiblScope, BiblStruct, Body, EditionStmt, FileDesc, Header, Imprint, Monogr, PersInfo,
+    RespStmt, Sentence, Token, Treebank,
+};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Indentation unit used by AGLDT files.
+const INDENT: &str = "  ";
+
+/// Escapes the characters that are not allowed verbatim inside `xml` text or attribute values.
+#[must_use]
+pub fn escape(src: &str) -> String {
+    let mut escaped = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes a [`Treebank`] following the AGLDT 2.1 `xml` layout.
+///
+/// The output can be read again with [`Treebank::from_xml_str`] and yield an equal [`Treebank`].
+///
+/// # Errors
+///
+/// This function will return an error if writing to `writer` fails.
+pub fn write_treebank<W: Write>(treebank: &Treebank, writer: &mut W) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<treebank version="{}" xml:lang="{}" cts="{}">"#,
+        escape(&treebank.version),
+        escape(&treebank.xml_lang),
+        escape(&treebank.cts)
+    )?;
+    write_header(&treebank.header, writer, 1)?;
+    write_body(&treebank.body, writer, 1)?;
+    writeln!(writer, "</treebank>")
+}
+
+/// Writes the element `<header>` and its children.
+///
+/// # Errors
+///
+/// This function will return an error if writing to `writer` fails.
+pub fn write_header<W: Write>(header: &Header, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<header>")?;
+    write_text_element(writer, depth + 1, "releaseDate", &header.release_date)?;
+    write_text_element(writer, depth + 1, "annotationDate", &header.annotation_date)?;
+    write_text_element(
+        writer,
+        depth + 1,
+        "annotationScheme",
+        &header.annotation_scheme,
+    )?;
+    write_file_desc(&header.file_desc, writer, depth + 1)?;
+    if let Some(encoding_desc) = &header.encoding_desc {
+        write_preserved_element(writer, depth + 1, "encodingDesc", encoding_desc)?;
+    }
+    if let Some(profile_desc) = &header.profile_desc {
+        write_preserved_element(writer, depth + 1, "profileDesc", profile_desc)?;
+    }
+    writeln!(writer, "{indent}</header>")
+}
+
+fn write_file_desc<W: Write>(file_desc: &FileDesc, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<fileDesc>")?;
+    write_edition_stmt(&file_desc.edition_stmt, writer, depth + 1)?;
+    write_bibl_struct(&file_desc.bibl_struct, writer, depth + 1)?;
+    writeln!(writer, "{indent}</fileDesc>")
+}
+
+fn write_edition_stmt<W: Write>(
+    edition_stmt: &EditionStmt,
+    writer: &mut W,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<editionStmt>")?;
+    for resp_stmt in &edition_stmt.resp_stmts {
+        write_resp_stmt(resp_stmt, writer, depth + 1)?;
+    }
+    writeln!(writer, "{indent}</editionStmt>")
+}
+
+/// Writes a `<respStmt>`, choosing between the two shapes of `<persName>` used by AGLDT:
+/// the plain one, when only a name and an address are known, and the structured one otherwise.
+fn write_resp_stmt<W: Write>(resp_stmt: &RespStmt, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<respStmt>")?;
+    let mut address = None;
+    match &resp_stmt.pers_name {
+        Some(pers_info) if pers_info.short.is_none() && pers_info.uri.is_none() => {
+            write_text_element(writer, depth + 1, "persName", &pers_info.name)?;
+            address = pers_info.address.as_ref();
+        }
+        Some(pers_info) => write_pers_info(pers_info, "persName", writer, depth + 1)?,
+        None => {}
+    }
+    write_text_element(writer, depth + 1, "resp", &resp_stmt.resp)?;
+    if let Some(name) = &resp_stmt.name {
+        write_text_element(writer, depth + 1, "name", name)?;
+    }
+    if let Some(address) = address {
+        write_text_element(writer, depth + 1, "address", address)?;
+    }
+    writeln!(writer, "{indent}</respStmt>")
+}
+
+/// Writes a person as the element `name`, e.g. `<persName>` or `<annotator>`.
+fn write_pers_info<W: Write>(
+    pers_info: &PersInfo,
+    name: &str,
+    writer: &mut W,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<{name}>")?;
+    if let Some(short) = &pers_info.short {
+        write_text_element(writer, depth + 1, "short", short)?;
+    }
+    write_text_element(writer, depth + 1, "name", &pers_info.name)?;
+    if let Some(address) = &pers_info.address {
+        write_text_element(writer, depth + 1, "address", address)?;
+    }
+    if let Some(uri) = &pers_info.uri {
+        write_text_element(writer, depth + 1, "uri", uri)?;
+    }
+    writeln!(writer, "{indent}</{name}>")
+}
+
+fn write_bibl_struct<W: Write>(
+    bibl_struct: &BiblStruct,
+    writer: &mut W,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<biblStruct>")?;
+    write_monogr(&bibl_struct.monogr, writer, depth + 1)?;
+    writeln!(writer, "{indent}</biblStruct>")
+}
+
+fn write_monogr<W: Write>(monogr: &Monogr, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<monogr>")?;
+    write_text_element(writer, depth + 1, "author", &monogr.author)?;
+    write_text_element(writer, depth + 1, "title", &monogr.title)?;
+    for resp_stmt in &monogr.resp_stmts {
+        write_resp_stmt(resp_stmt, writer, depth + 1)?;
+    }
+    if let Some(imprint) = &monogr.imprint {
+        write_imprint(imprint, writer, depth + 1)?;
+    }
+    for bibl_scope in &monogr.bibl_scopes {
+        write_bibl_scope(bibl_scope, writer, depth + 1)?;
+    }
+    writeln!(writer, "{indent}</monogr>")
+}
+
+fn write_imprint<W: Write>(imprint: &Imprint, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<imprint>")?;
+    for publication in &imprint.publications {
+        if let Some(pub_place) = &publication.pub_place {
+            write_text_element(writer, depth + 1, "pubPlace", pub_place)?;
+        }
+        if let Some(publisher) = &publication.publisher {
+            write_text_element(writer, depth + 1, "publisher", publisher)?;
+        }
+    }
+    if let Some(date) = &imprint.date {
+        write_text_element(writer, depth + 1, "date", date)?;
+    }
+    writeln!(writer, "{indent}</imprint>")
+}
+
+fn write_bibl_scope<W: Write>(
+    bibl_scope: &BiblScope,
+    writer: &mut W,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    match &bibl_scope.scope_type {
+        Some(scope_type) => writeln!(
+            writer,
+            r#"{indent}<biblScope type="{}">{}</biblScope>"#,
+            escape(scope_type),
+            escape(&bibl_scope.value)
+        ),
+        None => write_text_element(writer, depth, "biblScope", &bibl_scope.value),
+    }
+}
+
+/// Writes the element `<body>` and all its sentences.
+///
+/// # Errors
+///
+/// This function will return an error if writing to `writer` fails.
+pub fn write_body<W: Write>(body: &Body, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(writer, "{indent}<body>")?;
+    for sentence in &body.sentences {
+        write_sentence(sentence, writer, depth + 1)?;
+    }
+    writeln!(writer, "{indent}</body>")
+}
+
+/// Writes a `<sentence>` element, its annotators (`<primary>`, `<secondary>` and
+/// `<annotator>`) and its `<word>` children.
+///
+/// # Errors
+///
+/// This function will return an error if writing to `writer` fails.
+pub fn write_sentence<W: Write>(
+    sentence: &Sentence,
+    writer: &mut W,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    write!(
+        writer,
+        r#"{indent}<sentence id="{}" document_id="{}" subdoc="{}""#,
+        sentence.id,
+        escape(&sentence.document_id),
+        escape(&sentence.subdoc)
+    )?;
+    write_attributes(&sentence.attributes, writer)?;
+    writeln!(writer, ">")?;
+    for primary in &sentence.primary {
+        write_text_element(writer, depth + 1, "primary", primary)?;
+    }
+    for secondary in &sentence.secondary {
+        write_text_element(writer, depth + 1, "secondary", secondary)?;
+    }
+    for annotator in &sentence.annotators {
+        write_pers_info(annotator, "annotator", writer, depth + 1)?;
+    }
+    for token in &sentence.words {
+        write_token(token, writer, depth + 1)?;
+    }
+    writeln!(writer, "{indent}</sentence>")
+}
+
+/// Writes a single `<word>` element.
+///
+/// # Errors
+///
+/// This function will return an error if writing to `writer` fails.
+pub fn write_token<W: Write>(token: &Token, writer: &mut W, depth: usize) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    write!(writer, r#"{indent}<word id="{}""#, token.id)?;
+    if let Some(insertion_id) = &token.insertion_id {
+        write!(writer, r#" insertion_id="{}""#, escape(insertion_id))?;
+    }
+    if let Some(artificial) = &token.artificial {
+        write!(writer, r#" artificial="{}""#, escape(artificial))?;
+    }
+    write!(writer, r#" form="{}""#, escape(&token.form))?;
+    if let Some(lemma) = &token.lemma {
+        write!(writer, r#" lemma="{}""#, escape(lemma))?;
+    }
+    if let Some(postag) = &token.postag {
+        write!(writer, r#" postag="{}""#, escape(postag))?;
+    }
+    write!(
+        writer,
+        r#" relation="{}" head="{}""#,
+        escape(&token.relation),
+        token.head
+    )?;
+    if let Some(cite) = &token.cite {
+        write!(writer, r#" cite="{}""#, escape(cite))?;
+    }
+    write_attributes(&token.attributes, writer)?;
+    writeln!(writer, "/>")
+}
+
+/// Writes the attributes kept without a field of their own, e.g. `span` on `<sentence>`.
+fn write_attributes<W: Write>(
+    attributes: &BTreeMap<String, String>,
+    writer: &mut W,
+) -> io::Result<()> {
+    for (name, value) in attributes {
+        write!(writer, r#" {name}="{}""#, escape(value))?;
+    }
+    Ok(())
+}
+
+/// Writes an element marked with `xml:space="preserve"`, whose text is written verbatim.
+fn write_preserved_element<W: Write>(
+    writer: &mut W,
+    depth: usize,
+    name: &str,
+    text: &str,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    writeln!(
+        writer,
+        r#"{indent}<{name} xml:space="preserve">{}</{name}>"#,
+        escape(text)
+    )
+}
+
+fn write_text_element<W: Write>(
+    writer: &mut W,
+    depth: usize,
+    name: &str,
+    text: &str,
+) -> io::Result<()> {
+    let indent = INDENT.repeat(depth);
+    if text.is_empty() {
+        writeln!(writer, "{indent}<{name}/>")
+    } else {
+        writeln!(writer, "{indent}<{name}>{}</{name}>", escape(text))
+    }
+}
@@ -2,7 +2,7 @@ use agldt::parser::*;
 
 #[test]
 fn test_persname() {
-    use serde_xml_rs::from_str;
+    use agldt::reader::from_str;
     let src = r#"
             <editionStmt>
         <respStmt>
@@ -21,22 +21,76 @@ fn test_persname() {
         </respStmt>
         </editionStmt>
             "#;
-    assert!(from_str::<EditionStmt>(&preprocess(&src)).is_ok());
+    assert!(from_str::<EditionStmt>(src).is_ok());
+    let one_line = src.lines().map(str::trim).collect::<String>();
+    assert_eq!(
+        from_str::<EditionStmt>(&one_line).unwrap(),
+        from_str::<EditionStmt>(src).unwrap()
+    );
 }
 
 #[test]
 fn test_serde() {
-    use serde_xml_rs::from_str;
     use std::fs::read_to_string;
     let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
-    assert!(from_str::<Treebank>(&preprocess(&src)).is_ok());
+    assert!(Treebank::from_xml_str(&src).is_ok());
+}
+
+/// Compares two treebanks read from differently laid out sources. The text of `<encodingDesc>`
+/// and `<profileDesc>` is kept verbatim (`xml:space="preserve"`) and so changes along with the
+/// layout of the file: only its presence is compared.
+fn assert_same_treebank(actual: &Treebank, expected: &Treebank) {
+    assert_eq!(actual.version(), expected.version());
+    assert_eq!(actual.xml_lang(), expected.xml_lang());
+    assert_eq!(actual.cts(), expected.cts());
+    let (actual_header, expected_header) = (actual.header(), expected.header());
+    assert_eq!(actual_header.release_date(), expected_header.release_date());
+    assert_eq!(
+        actual_header.annotation_date(),
+        expected_header.annotation_date()
+    );
+    assert_eq!(
+        actual_header.annotation_scheme(),
+        expected_header.annotation_scheme()
+    );
+    assert_eq!(actual_header.file_desc(), expected_header.file_desc());
+    for header in [actual_header, expected_header] {
+        assert!(header.encoding_desc().is_some());
+        assert!(header.profile_desc().is_some());
+    }
+    assert_eq!(actual.sentences(), expected.sentences());
+}
+
+#[test]
+fn test_minified() {
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let minified = src.lines().map(str::trim).collect::<String>();
+    assert_eq!(minified.lines().count(), 1);
+    assert_same_treebank(
+        &Treebank::from_xml_str(&minified).unwrap(),
+        &Treebank::from_xml_str(&src).unwrap(),
+    );
+}
+
+#[test]
+fn test_reformatted() {
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let reformatted = src
+        .replace("  ", "\t")
+        .replace("\" ", "\"\r\n        ")
+        .replace("<persName>", "<persName>\n")
+        .replace("</persName>", "\n</persName>")
+        .replace("head=\"0\"", "head=\"\"");
+    assert_same_treebank(
+        &Treebank::from_xml_str(&reformatted).unwrap(),
+        &Treebank::from_xml_str(&src).unwrap(),
+    );
 }
 
 #[test]
 #[ignore]
 fn test_serde_all() {
     use dotenv;
-    use serde_xml_rs::from_str;
     use std::fs::{read_dir, read_to_string};
 
     dotenv::dotenv().ok();
@@ -44,11 +98,1198 @@ fn test_serde_all() {
     for file in paths {
         let file = file.unwrap().path();
         let src = read_to_string(&file).unwrap();
-        if !(from_str::<Treebank>(&preprocess(&src)).is_ok()) {
+        if Treebank::from_xml_str(&src).is_err() {
             dbg!(&file);
-            from_str::<Treebank>(&preprocess(&src)).unwrap();
+            Treebank::from_xml_str(&src).unwrap();
             panic!();
         }
-        // assert!(from_str::<Treebank>(&preprocess(&src)).is_ok());
+        // assert!(Treebank::from_xml_str(&src).is_ok());
+    }
+}
+
+#[test]
+fn test_write_roundtrip() {
+    use std::fs::read_to_string;
+    let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    let written = treebank.to_xml_string();
+    assert!(written.contains(r#"xml:lang="grc""#));
+    assert!(written.contains(r#"insertion_id="0026e" artificial="elliptic""#));
+    assert_eq!(Treebank::from_xml_str(&written).unwrap(), treebank);
+}
+
+#[test]
+fn test_streaming_reader() {
+    use agldt::reader::TreebankReader;
+    use std::fs::{read_to_string, File};
+    use std::io::BufReader;
+    let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+
+    let file = File::open("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let mut reader = TreebankReader::new(BufReader::new(file)).unwrap();
+    assert_eq!(
+        reader.cts(),
+        "urn:cts:greekLit:tlg0007.tlg004.perseus-grc1.tb"
+    );
+    let first = reader.next().unwrap().unwrap();
+    assert_eq!(first, treebank.sentences()[0]);
+
+    let minified = src.lines().map(str::trim).collect::<String>();
+    let reader = TreebankReader::new(minified.as_bytes()).unwrap();
+    assert_same_treebank(
+        &reader.into_treebank().unwrap(),
+        &Treebank::from_xml_str(&src).unwrap(),
+    );
+}
+
+#[test]
+fn test_errors() {
+    use agldt::error::{Error, Position};
+    let treebank = |version: &str, words: &str| {
+        format!(
+            r#"<treebank version="{version}" xml:lang="grc" cts="urn:cts:test">
+<header><releaseDate/><annotationDate/><annotationScheme/>
+<fileDesc><editionStmt/><biblStruct><monogr><author/><title/></monogr></biblStruct></fileDesc>
+</header>
+<body>
+<sentence id="7" document_id="urn:cts:test" subdoc="1.1">
+{words}
+</sentence>
+</body>
+</treebank>"#
+        )
+    };
+    let ok =
+        r#"<word id="1" form="λόγος" lemma="λόγος" postag="n-s---mn-" relation="PRED" head="0"/>"#;
+    assert!(Treebank::from_xml_str(&treebank("2.1", ok)).is_ok());
+
+    match Treebank::from_xml_str(&treebank("2.1", r#"<word id="1" form="λόγος" head="0"/>"#)) {
+        Err(Error::MissingAttribute {
+            attribute,
+            location,
+            ..
+        }) => {
+            assert_eq!(attribute, "relation");
+            assert_eq!(location.sentence_id, Some(7));
+            assert_eq!(location.word_id, Some(1));
+            assert_eq!(location.position, Some(Position { line: 7, column: 1 }));
+        }
+        other => panic!("{other:?}"),
+    }
+    assert!(matches!(
+        Treebank::from_xml_str(&treebank("2.1", &ok.replace("n-s---mn-", "n-s"))),
+        Err(Error::InvalidPostag { .. })
+    ));
+    assert!(matches!(
+        Treebank::from_xml_str(&treebank("2.1", &ok.replace(r#"head="0""#, r#"head="x""#))),
+        Err(Error::InvalidHead { .. })
+    ));
+    assert!(matches!(
+        Treebank::from_xml_str(&treebank("1.5", ok)),
+        Err(Error::UnsupportedVersion { .. })
+    ));
+    assert!(matches!(
+        Treebank::from_xml_str(&treebank("2.1", "<word")),
+        Err(Error::MalformedXml { .. })
+    ));
+}
+
+#[test]
+fn test_postag() {
+    use agldt::features::{Case, Number, PartOfSpeech, Postag, Tense};
+    use std::fs::read_to_string;
+    let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    for sentence in treebank.sentences() {
+        for token in sentence.words() {
+            if let Some(postag) = token.postag().unwrap() {
+                assert_eq!(token.postag_str(), Some(postag.to_string().as_str()));
+            }
+        }
+    }
+
+    let postag: Postag = "n-s---mg-".parse().unwrap();
+    assert_eq!(postag.pos, Some(PartOfSpeech::Noun));
+    assert_eq!(postag.number, Some(Number::Singular));
+    assert_eq!(postag.case, Some(Case::Genitive));
+    assert_eq!(postag.tense, None::<Tense>);
+    assert_eq!(postag.features().len(), 4);
+    assert!("n-s---mz-".parse::<Postag>().is_err());
+    assert!("n-s".parse::<Postag>().is_err());
+}
+
+#[test]
+fn test_features() {
+    use agldt::features::*;
+    assert_eq!(Tense::Pluperfect.to_agldt_postag().char(), 'l');
+    assert_eq!(Tense::Pluperfect.to_agldt_postag().index(), 3);
+    assert_eq!(PartOfSpeech::Preposition.to_string(), "r");
+    assert_eq!(Person::Third.to_string(), "3");
+    assert_eq!(Mood::Infinitive.to_string(), "n");
+    assert_eq!(Voice::MedioPassive.to_string(), "e");
+    assert_eq!(Case::Ablative.to_agldt_postag().index(), 7);
+    assert_eq!(Degree::Superlative.to_agldt_postag().index(), 8);
+}
+
+#[test]
+fn test_relations() {
+    use agldt::relation::{BaseRelation, Relation};
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    for sentence in treebank.sentences() {
+        for token in sentence.words() {
+            let relation = token.relation().unwrap();
+            assert_eq!(relation.to_string(), token.relation_str());
+        }
+    }
+
+    let relation: Relation = "COORD_ExD0_ADV".parse().unwrap();
+    assert!(relation.is(BaseRelation::Coord) && relation.is_elliptic());
+    assert_eq!(relation.ellipses[0].index, 0);
+    assert!(!relation.is_coordinated());
+    assert!("ExD".parse::<Relation>().unwrap().is_elliptic());
+    assert!(BaseRelation::AuxK.is_auxiliary());
+    for invalid in [
+        "",
+        "OBJ_XX",
+        "FOO_CO",
+        "OBJ_CO_CO",
+        "SBJ_ExD_ADV",
+        "SBJ_ExD0",
+        "ADV_CO_ExD0_OBJ",
+    ] {
+        assert!(invalid.parse::<Relation>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_dependency_tree() {
+    use std::fs::read_to_string;
+    let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    for sentence in treebank.sentences() {
+        let tree = sentence.tree().unwrap();
+        let reached: usize = tree
+            .roots()
+            .iter()
+            .map(|root| tree.subtree(root.id()).len())
+            .sum();
+        assert_eq!(reached, sentence.count_tokens());
+        for token in sentence.words() {
+            let path = tree.path_to_root(token.id());
+            assert_eq!(path.len(), tree.depth(token.id()).unwrap() + 1);
+            assert_eq!(path.last().unwrap().head(), 0);
+        }
+    }
+
+    let src = r#"<sentence id="1" document_id="doc" subdoc="1.1">
+        <word id="1" form="ὁ" lemma="ὁ" postag="l-s---mn-" relation="ATR" head="2"/>
+        <word id="2" form="ἀνήρ" lemma="ἀνήρ" postag="n-s---mn-" relation="SBJ" head="4"/>
+        <word id="3" form="καλός" lemma="καλός" postag="a-s---mn-" relation="ATR" head="2"/>
+        <word id="4" form="ἐστί" lemma="εἰμί" postag="v3spia---" relation="PRED" head="0"/>
+        <word id="5" form="." lemma="punc1" postag="u--------" relation="AuxK" head="0"/>
+        <word id="6" insertion_id="0004e" artificial="elliptic" form="[0]" relation="ATR" head="3"/>
+    </sentence>"#;
+    let sentence: Sentence = agldt::reader::from_str(src).unwrap();
+    let tree = sentence.tree().unwrap();
+    assert_eq!(tree.roots().len(), 2);
+    assert_eq!(tree.root().unwrap().form(), "ἐστί");
+    assert_eq!(tree.parent(1).unwrap().id(), 2);
+    assert!(tree.parent(4).is_none());
+    let children: Vec<u32> = tree.children(2).map(Token::id).collect();
+    assert_eq!(children, vec![1, 3]);
+    let descendants: Vec<u32> = tree.descendants(4).map(Token::id).collect();
+    assert_eq!(descendants, vec![2, 1, 3, 6]);
+    let ancestors: Vec<u32> = tree.ancestors(6).map(Token::id).collect();
+    assert_eq!(ancestors, vec![3, 2, 4]);
+    assert_eq!(tree.depth(6), Some(3));
+    assert_eq!(tree.span(2), Some((1, 3)));
+    assert_eq!(tree.span(6), None);
+
+    let cyclic = src.replace(r#"relation="PRED" head="0""#, r#"relation="PRED" head="3""#);
+    let sentence: Sentence = agldt::reader::from_str(&cyclic).unwrap();
+    assert!(matches!(
+        sentence.tree(),
+        Err(agldt::Error::HeadCycle { .. })
+    ));
+    let dangling = src.replace(r#"relation="AuxK" head="0""#, r#"relation="AuxK" head="9""#);
+    let sentence: Sentence = agldt::reader::from_str(&dangling).unwrap();
+    assert!(matches!(
+        sentence.tree(),
+        Err(agldt::Error::InvalidHead { .. })
+    ));
+    let duplicated = src.replace(r#"<word id="3""#, r#"<word id="1""#);
+    let sentence: Sentence = agldt::reader::from_str(&duplicated).unwrap();
+    assert!(matches!(
+        sentence.tree(),
+        Err(agldt::Error::DuplicateId { location }) if location.word_id == Some(1)
+    ));
+}
+
+#[test]
+fn test_validate() {
+    use agldt::validate::{validate_sentence, DiagnosticKind, Severity};
+    let src = r#"<sentence id="3" document_id="doc" subdoc="1.1">
+        <word id="1" form="ὁ" postag="l-s---mn-" relation="ATR" head="2"/>
+        <word id="2" form="ἀνήρ" lemma="ἀνήρ" postag="n-s---mn-" relation="SBJ" head="3"/>
+        <word id="3" form="καλός" lemma="καλός" postag="a-s---mn-" relation="ATR" head="2"/>
+        <word id="4" form="ἐστί" lemma="εἰμί" relation="PRED" head="9"/>
+        <word id="4" insertion_id="0004e" artificial="elliptic" form="[0]" relation="ATR" head="2"/>
+    </sentence>"#;
+    let sentence: Sentence = agldt::reader::from_str(src).unwrap();
+    let kinds: Vec<(DiagnosticKind, Option<u32>)> = validate_sentence(&sentence)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.kind, diagnostic.location.word_id))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (DiagnosticKind::DuplicateId, Some(4)),
+            (DiagnosticKind::OrphanHead { head: 9 }, Some(4)),
+            (DiagnosticKind::Cycle, Some(2)),
+            (DiagnosticKind::MissingRoot, None),
+            (DiagnosticKind::MissingLemma, Some(1)),
+            (DiagnosticKind::MissingPostag, Some(4)),
+        ]
+    );
+
+    let src = r#"<sentence id="4" document_id="doc" subdoc="1.1">
+        <word id="1" form="ἦλθεν" lemma="ἔρχομαι" postag="v3saia---" relation="PRED" head="0"/>
+        <word id="2" form="ἀπῆλθεν" lemma="ἀπέρχομαι" postag="v3saia---" relation="PRED" head="0"/>
+        <word id="3" form="." lemma="punc1" postag="u--------" relation="AuxK" head="0"/>
+    </sentence>"#;
+    let sentence: Sentence = agldt::reader::from_str(src).unwrap();
+    let diagnostics = validate_sentence(&sentence);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::MultipleRoots { roots: vec![1, 2] }
+    );
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+}
+
+#[test]
+fn test_tagset() {
+    use agldt::tagset::{Rule, TagsetChecker};
+    use agldt::validate::{DiagnosticKind, Severity};
+    let src = r#"<sentence id="5" document_id="doc" subdoc="1.1">
+        <word id="1" form="ἀνήρ" lemma="ἀνήρ" postag="n-sp--mn-" relation="SBJ" head="3"/>
+        <word id="2" form="ἐν" lemma="ἐν" postag="c--------" relation="AuxP" head="3"/>
+        <word id="3" form="λέγει" lemma="λέγω" postag="v3spia-n-" relation="PRED" head="0"/>
+        <word id="4" form="λέγων" lemma="λέγω" postag="v-sppamn-" relation="ADV_CO" head="3"/>
+        <word id="5" form="λέγειν" lemma="λέγω" postag="v--pnaz--" relation="OBJ_XX" head="3"/>
+        <word id="6" form="." lemma="punc1" postag="u--------" relation="AuxK" head="0"/>
+        <word id="7" form="ὅτε" lemma="ὅτε" postag="d--------" relation="AuxC" head="3"/>
+        <word id="8" form="ὅς" lemma="ὅς" postag="p-s---mn-" relation="AuxC" head="3"/>
+    </sentence>"#;
+    let sentence: Sentence = agldt::reader::from_str(src).unwrap();
+    let checker = TagsetChecker::new();
+    let diagnostics = checker.check_sentence(&sentence);
+    let found: Vec<(Option<u32>, Severity)> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.location.word_id, diagnostic.severity))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (Some(1), Severity::Warning),
+            (Some(2), Severity::Warning),
+            (Some(3), Severity::Warning),
+            (Some(5), Severity::Error),
+            (Some(5), Severity::Error),
+        ]
+    );
+    assert!(matches!(
+        &diagnostics[1].kind,
+        DiagnosticKind::RelationPos { relation, pos } if relation == "AuxP" && pos == "c"
+    ));
+    assert!(matches!(
+        &diagnostics[2].kind,
+        DiagnosticKind::MisplacedFeature { position, .. } if *position == "case"
+    ));
+
+    let checker = TagsetChecker::new()
+        .severity(Rule::MisplacedFeature, None)
+        .severity(Rule::RelationPos, Some(Severity::Error));
+    let diagnostics = checker.check_sentence(&sentence);
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.is_error()));
+}
+
+#[test]
+fn test_header() {
+    use std::fs::read_to_string;
+    let src = read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    let header = treebank.header();
+    assert_eq!(header.release_date(), "25 September 2015");
+
+    let monogr = header.file_desc().bibl_struct().monogr();
+    assert_eq!(monogr.author(), "Plutarch");
+    assert_eq!(
+        monogr.resp_stmts()[0].resp(),
+        "with an English Translation by"
+    );
+    assert_eq!(monogr.resp_stmts()[0].name(), Some("Bernadotte Perrin"));
+    let imprint = monogr.imprint().unwrap();
+    let publications: Vec<(Option<&str>, Option<&str>)> = imprint
+        .publications()
+        .iter()
+        .map(|publication| (publication.pub_place(), publication.publisher()))
+        .collect();
+    assert_eq!(
+        publications,
+        vec![
+            (Some("Cambridge, MA"), Some("Harvard University Press")),
+            (Some("London"), Some("William Heinemann Ltd.")),
+        ]
+    );
+    assert_eq!(imprint.date(), Some("1914"));
+    let scopes: Vec<(Option<&str>, &str)> = monogr
+        .bibl_scopes()
+        .iter()
+        .map(|scope| (scope.scope_type(), scope.value()))
+        .collect();
+    assert_eq!(scopes, vec![(Some("volume"), "1"), (None, "Lycurgus")]);
+
+    let encoding_desc = header.encoding_desc().unwrap();
+    assert!(encoding_desc.starts_with("\n    The unannotated text comes"));
+    assert!(encoding_desc.ends_with("variants for the text.\n    "));
+    assert!(header
+        .profile_desc()
+        .unwrap()
+        .contains("harmonization of the text,\n    and the file TAGSETS.xml"));
+
+    let written = Treebank::from_xml_str(&treebank.to_xml_string()).unwrap();
+    assert_eq!(written.header(), header);
+}
+
+#[test]
+fn test_builder() {
+    use agldt::error::Error;
+    let token = |id: u32, head: u32| {
+        Token::builder(id, "λόγος")
+            .lemma("λόγος")
+            .postag("n-s---mn-")
+            .relation("SBJ")
+            .head(head)
+    };
+    let sentence = Sentence::builder(7)
+        .document_id("doc")
+        .subdoc("1.1")
+        .tokens([token(1, 2).build().unwrap(), token(2, 0).build().unwrap()])
+        .build()
+        .unwrap();
+    assert_eq!(sentence.id(), 7);
+    assert_eq!(sentence.document_id(), "doc");
+    assert_eq!(sentence.subdoc(), "1.1");
+    let word = &sentence.words()[0];
+    assert_eq!((word.id(), word.head(), word.relation_str()), (1, 2, "SBJ"));
+
+    assert!(matches!(
+        token(0, 1).build(),
+        Err(Error::InvalidAttribute { .. })
+    ));
+    assert!(matches!(
+        token(1, 1).build(),
+        Err(Error::InvalidAttribute { .. })
+    ));
+    assert!(matches!(
+        token(1, 0).postag("n-s---mz-").build(),
+        Err(Error::InvalidPostag { .. })
+    ));
+    assert!(matches!(
+        token(1, 0).relation("SBJ_XX").build(),
+        Err(Error::InvalidRelation { .. })
+    ));
+    assert!(matches!(
+        Token::builder(1, "λόγος").build(),
+        Err(Error::MissingAttribute { .. })
+    ));
+    let artificial = token(3, 2).artificial("elliptic", "0002e").build().unwrap();
+    assert!(artificial.is_artificial());
+    assert_eq!(artificial.insertion_id(), Some("0002e"));
+
+    let dangling = Sentence::builder(8)
+        .token(token(1, 5).build().unwrap())
+        .build();
+    assert!(matches!(
+        dangling,
+        Err(Error::InvalidSentence { location, .. }) if location.word_id == Some(1)
+    ));
+
+    let header = Header::new(Monogr::new("Plutarch", "Lives"))
+        .with_annotation_scheme("AGLDT 2.0")
+        .with_resp_stmt(RespStmt::new("annotator").with_pers_name(PersInfo::new("Annotator")))
+        // A `<name>` next to a `<persName>` of either shape is kept.
+        .with_resp_stmt(
+            RespStmt::new("editor")
+                .with_pers_name(PersInfo::new("Jane Doe").with_address("jdoe@example.org"))
+                .with_name("Perseus Digital Library"),
+        )
+        .with_resp_stmt(
+            RespStmt::new("reviewer")
+                .with_pers_name(PersInfo::new("John Doe").with_short("jd"))
+                .with_name("Perseus Digital Library"),
+        );
+    let duplicate = Treebank::builder(header.clone())
+        .sentences([sentence.clone(), sentence.clone()])
+        .build();
+    assert!(matches!(duplicate, Err(Error::InvalidSentence { .. })));
+    assert!(matches!(
+        Treebank::builder(header.clone()).version("3.0").build(),
+        Err(Error::UnsupportedVersion { .. })
+    ));
+    let treebank = Treebank::builder(header)
+        .xml_lang("grc")
+        .cts("urn:cts:greekLit:tlg0007.tlg004")
+        .sentence(sentence)
+        .build()
+        .unwrap();
+    assert_eq!(treebank.version(), "2.1");
+    assert_eq!(treebank.cts(), "urn:cts:greekLit:tlg0007.tlg004");
+    assert_eq!(
+        Treebank::from_xml_str(&treebank.to_xml_string()).unwrap(),
+        treebank
+    );
+}
+
+#[test]
+fn test_edit() {
+    use agldt::error::Error;
+    use agldt::validate::validate;
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let mut treebank = Treebank::from_xml_str(&src).unwrap();
+
+    let token = treebank.sentence_mut(3).unwrap().token_mut(1).unwrap();
+    assert!(matches!(
+        token.set_postag(Some("l-p---mz-")),
+        Err(Error::InvalidPostag { .. })
+    ));
+    assert!(matches!(
+        token.set_relation("ATR_XX"),
+        Err(Error::InvalidRelation { .. })
+    ));
+    assert_eq!(
+        (token.postag_str(), token.relation_str()),
+        (Some("l-p---mn-"), "ATR")
+    );
+    token.set_lemma(Some("ὁ"));
+    token.set_relation("SBJ").unwrap();
+
+    let sentence = treebank.sentence_mut(3).unwrap();
+    assert!(matches!(
+        sentence.set_head(22, 9),
+        Err(Error::HeadCycle { .. })
+    ));
+    assert!(matches!(
+        sentence.set_head(1, 99),
+        Err(Error::InvalidHead { .. })
+    ));
+    assert!(matches!(
+        sentence.set_head(99, 1),
+        Err(Error::NotFound { id: 99, .. })
+    ));
+    sentence.set_head(2, 13).unwrap();
+    assert_eq!(sentence.token(2).unwrap().head(), 13);
+
+    let node = || {
+        Token::builder(1, "[1]")
+            .relation("ATR")
+            .head(22)
+            .build()
+            .unwrap()
+    };
+    assert_eq!(sentence.insert_artificial(5, node()).unwrap(), 28);
+    assert_eq!(sentence.insert_artificial(5, node()).unwrap(), 29);
+    assert_eq!(sentence.token(29).unwrap().insertion_id(), Some("0005f"));
+    assert_eq!(sentence.token(29).unwrap().artificial(), Some("elliptic"));
+    assert!(matches!(
+        sentence.insert_token(1, sentence.token(28).unwrap().clone()),
+        Err(Error::InvalidAttribute { .. })
+    ));
+
+    assert!(matches!(
+        sentence.remove_token(4),
+        Err(Error::InvalidSentence { .. })
+    ));
+    let removed = sentence.remove_token(5).unwrap();
+    assert_eq!(removed.form(), "τῶν");
+    assert_eq!(sentence.count_tokens(), 28);
+    assert_eq!(sentence.token(6).unwrap().head(), 5);
+    let inserted: Vec<_> = sentence
+        .words()
+        .iter()
+        .filter_map(Token::insertion_id)
+        .collect();
+    assert_eq!(inserted, ["0025e", "0004e", "0004f"]);
+
+    assert!(matches!(
+        treebank.split_sentence(3, 13),
+        Err(Error::InvalidSentence { .. })
+    ));
+    assert!(matches!(
+        treebank.merge_sentences(u32::MAX),
+        Err(Error::NotFound { .. })
+    ));
+    assert!(matches!(
+        treebank.split_sentence(3, 1),
+        Err(Error::InvalidSentence { .. })
+    ));
+    // Suffixes go on past `z`, staying unique and in order.
+    let mut many = Treebank::from_xml_str(&src).unwrap();
+    let sentence = many.sentence_mut(1).unwrap();
+    let leaf = (2..)
+        .find(|id| *id != 22 && sentence.words().iter().all(|token| token.head() != *id))
+        .unwrap();
+    for _ in 0..24 {
+        sentence.insert_artificial(leaf, node()).unwrap();
+    }
+    let suffixes = |sentence: &Sentence| -> Vec<String> {
+        sentence
+            .tokens_in_text_order()
+            .into_iter()
+            .filter_map(|token| token.insertion_id().map(|id| id[4..].to_string()))
+            .collect()
+    };
+    let expected: Vec<String> = ('e'..='z')
+        .map(String::from)
+        .chain(["ze".to_string(), "zf".to_string()])
+        .collect();
+    assert_eq!(suffixes(sentence), expected);
+    sentence.remove_token(leaf).unwrap();
+    assert_eq!(suffixes(sentence), expected);
+    assert!(sentence
+        .words()
+        .iter()
+        .filter_map(Token::insertion_id)
+        .all(|id| id.starts_with(&format!("{:04}", leaf - 1))));
+
+    // Word ids need not start at 1.
+    let word = |id: u32, form: &str, relation: &str| {
+        Token::builder(id, form)
+            .relation(relation)
+            .head(0)
+            .build()
+            .unwrap()
+    };
+    let sentence = Sentence::builder(1)
+        .token(word(2, "λέγει", "PRED"))
+        .token(word(3, ".", "AuxK"))
+        .build()
+        .unwrap();
+    let mut offset = Treebank::builder(Header::new(Monogr::new("Plutarch", "Lycurgus")))
+        .sentence(sentence)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        offset.split_sentence(1, 2),
+        Err(Error::InvalidSentence { .. })
+    ));
+    assert_eq!(offset.sentences().len(), 1);
+    let last = treebank.sentences().last().unwrap().id();
+    assert!(matches!(
+        treebank.merge_sentences(last),
+        Err(Error::InvalidSentence { .. })
+    ));
+
+    let with_attributes = |fourth: &str| {
+        let src = src
+            .replace(
+                r#"<sentence id="3""#,
+                r#"<sentence id="3" span="οἱ0:ἐλέχθη0" status="done" checked="no""#,
+            )
+            .replace(
+                r#"<sentence id="4""#,
+                &format!(r#"<sentence id="4" {fourth}"#),
+            );
+        Treebank::from_xml_str(&src).unwrap()
+    };
+    let attributes = |treebank: &Treebank, id: u32| -> Vec<(String, String)> {
+        treebank
+            .sentence(id)
+            .unwrap()
+            .attributes()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    };
+    let mut merged =
+        with_attributes(r#"span="τοῖς0:ὑπάρξαι0" status="done" checked="yes" source="x""#);
+    merged.merge_sentences(3).unwrap();
+    assert_eq!(
+        attributes(&merged, 3),
+        [
+            ("source".to_string(), "x".to_string()),
+            ("span".to_string(), "οἱ0:ὑπάρξαι0".to_string()),
+            ("status".to_string(), "done".to_string()),
+        ]
+    );
+    // Splitting them again keeps the attributes on both sides, but for the span.
+    let new_id = merged.split_sentence(3, 27).unwrap();
+    for id in [3, new_id] {
+        assert_eq!(
+            attributes(&merged, id),
+            [
+                ("source".to_string(), "x".to_string()),
+                ("status".to_string(), "done".to_string()),
+            ]
+        );
+    }
+    assert!(validate(&treebank)
+        .iter()
+        .all(|diagnostic| !diagnostic.is_error()));
+}
+
+#[test]
+fn test_conllu() {
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    let conllu = treebank.to_conllu_string().unwrap();
+    let blocks: Vec<&str> = conllu.split_terminator("\n\n").collect();
+    assert_eq!(blocks.len(), treebank.sentences().len());
+
+    for (block, sentence) in blocks.iter().zip(treebank.sentences()) {
+        let lines: Vec<Vec<&str>> = block
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| line.split('\t').collect())
+            .collect();
+        assert!(lines.iter().all(|columns| columns.len() == 10));
+        assert_eq!(
+            lines.len(),
+            sentence.count_tokens()
+                - sentence
+                    .words()
+                    .iter()
+                    .filter(|t| t.is_artificial())
+                    .count()
+        );
+
+        // A single root, and every word reaches it.
+        let heads: Vec<usize> = lines.iter().map(|c| c[6].parse().unwrap()).collect();
+        assert_eq!(
+            heads.iter().filter(|&&head| head == 0).count(),
+            1,
+            "{block}"
+        );
+        for (i, columns) in lines.iter().enumerate() {
+            assert_eq!(columns[0], (i + 1).to_string());
+            assert_eq!(heads[i] == 0, columns[7] == "root");
+            let mut head = heads[i];
+            let mut steps = 0;
+            while head != 0 {
+                head = heads[head - 1];
+                steps += 1;
+                assert!(steps <= lines.len(), "cycle in {block}");
+            }
+        }
+    }
+
+    // καὶ coordinates Ἐρατοσθένης and Ἀπολλόδωρος, which head it.
+    let third: Vec<Vec<&str>> = blocks[2]
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split('\t').collect())
+        .collect();
+    assert_eq!(&third[14][6..8], ["16", "cc"]);
+    assert_eq!(&third[15][6..8], ["14", "conj"]);
+    assert_eq!(&third[21][6..8], ["0", "root"]);
+    assert_eq!(&third[25][6..8], ["22", "punct"]);
+}
+
+#[test]
+fn test_conllu_import() {
+    use agldt::conllu::{feats, postag_from_ud, upos, Mapping};
+    use agldt::error::Error;
+    use agldt::features::PartOfSpeech;
+    use agldt::utils::Lexicon;
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+    let conllu = treebank.to_conllu_string().unwrap();
+
+    let raw = Treebank::from_conllu_str(&conllu, Mapping::Raw).unwrap();
+    assert_eq!(raw.to_conllu_string().unwrap(), conllu);
+    assert_eq!(raw.sentences()[2].id(), 3);
+    assert_eq!(raw.sentences()[2].subdoc(), "1.2");
+    assert_eq!(raw.count_words(), treebank.count_words());
+    assert_eq!(raw.build_lexicon(), treebank.build_lexicon());
+    let token = &raw.sentences()[0].words()[0];
+    assert_eq!((token.relation_str(), token.postag_str()), ("case", None));
+    assert_eq!(token.ud().unwrap().upos(), "ADP");
+
+    let mapped = Treebank::from_conllu_str(&conllu, Mapping::Agldt).unwrap();
+    for (sentence, original) in mapped.sentences().iter().zip(treebank.sentences()) {
+        let words: Vec<_> = original
+            .words()
+            .iter()
+            .filter(|t| !t.is_artificial())
+            .collect();
+        for (token, original) in sentence.words().iter().zip(words) {
+            assert_eq!(token.postag_str(), original.postag_str());
+        }
+    }
+    assert_eq!(mapped.sentences()[2].words()[14].relation_str(), "COORD");
+    assert_eq!(mapped.sentences()[2].words()[15].relation_str(), "ADV_CO");
+
+    // The features alone give back the postag, but for the parts of speech UD merges.
+    for token in treebank.sentences().iter().flat_map(|s| s.words()) {
+        let Some(postag) = token.postag().unwrap() else {
+            continue;
+        };
+        if matches!(
+            postag.pos,
+            None | Some(PartOfSpeech::Participle | PartOfSpeech::Exclamation)
+        ) {
+            continue;
+        }
+        assert_eq!(
+            postag_from_ud(upos(token, Some(&postag)), &feats(&postag)),
+            postag
+        );
+    }
+
+    let empty_nodes = "\
+# sent_id = s1
+1-2\tκἀγώ\t_\t_\t_\t_\t_\t_\t_\t_
+1\tκαὶ\tκαί\tCCONJ\t_\t_\t2\tcc\t2:cc\t_
+2\tἐγώ\tἐγώ\tPRON\t_\tCase=Nom\t0\troot\t0:root\t_
+2.1\tλέγω\tλέγω\tVERB\t_\t_\t_\t_\t0:root\tSpaceAfter=No
+3\t.\t.\tPUNCT\t_\t_\t2\tpunct\t2:punct\t_
+
+";
+    let treebank = Treebank::from_conllu_str(empty_nodes, Mapping::Raw).unwrap();
+    let sentence = &treebank.sentences()[0];
+    assert_eq!(sentence.subdoc(), "s1");
+    assert_eq!(sentence.count_tokens(), 4);
+    let node = sentence.token(4).unwrap();
+    assert_eq!(
+        (node.insertion_id(), node.head(), node.relation_str()),
+        (Some("0002e"), 0, "root")
+    );
+    let written = treebank.to_conllu_string().unwrap();
+    assert!(written.contains("2.1\tλέγω\tλέγω\tVERB\t_\t_\t_\t_\t0:root\tSpaceAfter=No\n"));
+    // Empty nodes are numbered by their order, whatever the suffix of their `insertion_id`.
+    let relettered = treebank
+        .to_json_string()
+        .replace(r#""insertion_id":"0002e""#, r#""insertion_id":"0002a""#);
+    let relettered = Treebank::from_json_str(&relettered).unwrap();
+    assert_eq!(
+        relettered.sentences()[0].token(4).unwrap().insertion_id(),
+        Some("0002a")
+    );
+    assert_eq!(relettered.to_conllu_string().unwrap(), written);
+    // The head of an empty node is its first enhanced head.
+    let mut reattached = treebank.clone();
+    reattached.sentence_mut(1).unwrap().set_head(4, 2).unwrap();
+    let written = reattached.to_conllu_string().unwrap();
+    assert!(written.contains("2.1\tλέγω\tλέγω\tVERB\t_\t_\t_\t_\t2:root\tSpaceAfter=No\n"));
+
+    // Raw sentences are written back as they were read, with their comments and multiword
+    // tokens.
+    let ittb = std::fs::read_to_string("./tests/ittb-scg.conllu").unwrap();
+    let raw = Treebank::from_conllu_str(&ittb, Mapping::Raw).unwrap();
+    assert_eq!(raw.to_conllu_string().unwrap(), ittb);
+    let mapped = Treebank::from_conllu_str(&ittb, Mapping::Agldt).unwrap();
+    assert_eq!(mapped.to_conllu_string().unwrap(), ittb);
+    // The copula takes the relation of the nominal predicate, shared by its conjunct.
+    let relations: Vec<&str> = mapped.sentences()[0]
+        .words()
+        .iter()
+        .map(Token::relation_str)
+        .collect();
+    assert_eq!(
+        relations,
+        [
+            "PRED", "AuxV", "AuxY", "SBJ", "SBJ", "ATR", "PNOM_CO", "AuxX", "PNOM_CO", "COORD",
+            "AuxK"
+        ]
+    );
+    assert_eq!(Mapping::default(), Mapping::Agldt);
+
+    // Edits to the postag and the relation are written, the other columns being kept.
+    let mut edited = mapped.clone();
+    let sentence = edited.sentence_mut(1).unwrap();
+    sentence
+        .token_mut(3)
+        .unwrap()
+        .set_postag(Some("d--------"))
+        .unwrap();
+    sentence.token_mut(4).unwrap().set_relation("OBJ").unwrap();
+    let written = edited.to_conllu_string().unwrap();
+    assert!(written.contains("\n3\tautem\tautem\tADV\td--------\t_\t1\tdiscourse\t_\t_\n"));
+    assert!(written.contains(
+        "\n4\tdeum\tdeus\tNOUN\tA1|grn1|casD|gen2\tCase=Acc|Gender=Masc|Number=Sing\t7\tobj\t_\t_\n"
+    ));
+    assert_eq!(
+        written.lines().filter(|line| !ittb.contains(line)).count(),
+        2
+    );
+    // So are tokens inserted without UD columns, among raw ones.
+    let mut edited = raw.clone();
+    let enim = Token::builder(3, "enim")
+        .lemma("enim")
+        .postag("d--------")
+        .relation("AuxY")
+        .head(4)
+        .build()
+        .unwrap();
+    edited
+        .sentence_mut(2)
+        .unwrap()
+        .insert_token(3, enim)
+        .unwrap();
+    let written = edited.to_conllu_string().unwrap();
+    assert!(written.contains("\n3\tenim\tenim\tADV\td--------\t_\t4\tadvmod\t_\t_\n"));
+    assert!(written.contains("\n1\tQuod\tqui\tPRON\tF1|grn1|casA|gen3\tCase=Nom|Gender=Neut|Number=Sing|PronType=Rel\t4\tnsubj:pass\t4:nsubj:pass\t_\n"));
+    assert!(written.contains("\n7\talio\talius\tDET\tF1|grn1|casF|gen1\tCase=Abl|Gender=Masc|Number=Sing|PronType=Con\t8\tobl:agent\t8:obl:agent\t_\n"));
+    let first = &raw.sentences()[0];
+    assert_eq!(
+        (first.id(), first.document_id(), first.subdoc()),
+        (1, "ittb-scg", "train-s1")
+    );
+    assert_eq!(first.comments()[0], "# newdoc id = ittb-scg");
+    assert_eq!(first.comments()[3], "# citation_hierarchy = 1.13.1");
+    let multiword = &first.multiword_tokens()[0];
+    assert_eq!(
+        (multiword.first(), multiword.last(), multiword.form()),
+        (9, 10, "bonumque")
+    );
+    assert_eq!(multiword.misc(), Some("SpaceAfter=No"));
+    assert_eq!(raw.sentences()[1].document_id(), "ittb-scg");
+    assert_eq!(Treebank::from_json_str(&raw.to_json_string()).unwrap(), raw);
+
+    let mut edited = raw.clone();
+    assert!(matches!(
+        edited.split_sentence(1, 10),
+        Err(Error::InvalidSentence { .. })
+    ));
+    edited.sentence_mut(1).unwrap().remove_token(3).unwrap();
+    let written = edited.to_conllu_string().unwrap();
+    assert!(written.contains("# text = Ostensum est deum esse primum movens, bonumque.\n"));
+    assert!(written.contains("\n8-9\tbonumque\t"));
+    edited.merge_sentences(1).unwrap();
+    let merged = &edited.sentences()[0];
+    assert_eq!(merged.comments().len(), 3);
+    assert_eq!(merged.multiword_tokens()[0].first(), 8);
+
+    // Enhanced dependencies follow the words they point at.
+    let mut edited = raw.clone();
+    let sentence = edited.sentence_mut(2).unwrap();
+    let autem = sentence.remove_token(2).unwrap();
+    let deps = |sentence: &Sentence, id: u32| {
+        sentence
+            .token(id)
+            .unwrap()
+            .ud()
+            .unwrap()
+            .deps()
+            .map(str::to_string)
+    };
+    assert_eq!(deps(sentence, 1).as_deref(), Some("2:nsubj:pass"));
+    assert_eq!(deps(sentence, 5).as_deref(), Some("6:obl:agent"));
+    sentence.insert_token(2, autem).unwrap();
+    assert_eq!(edited.to_conllu_string().unwrap(), ittb);
+
+    let malformed = "1\tλόγος\tλόγος\tNOUN\n";
+    assert!(matches!(
+        Treebank::from_conllu_str(malformed, Mapping::Raw),
+        Err(Error::MalformedConllu { location, .. })
+            if location.position.map(|p| p.line) == Some(1)
+    ));
+}
+
+#[test]
+fn test_json() {
+    use agldt::error::Error;
+    use agldt::json::{read_lines, write_lines, JsonLinesReader, SCHEMA_VERSION};
+    let src = std::fs::read_to_string("./tests/tlg0007.tlg004.perseus-grc1.tb.xml").unwrap();
+    let treebank = Treebank::from_xml_str(&src).unwrap();
+
+    let json = treebank.to_json_string();
+    assert!(json.starts_with(&format!("{{\"schema\":{SCHEMA_VERSION},")));
+    assert!(json.contains(r#""header":{"release_date":"#));
+    assert!(json.contains(r#""pers_name":{"name":"Giuseppe G. A. Celano","address":"#));
+    assert!(json.contains(r#""subdoc":"1.1","words":[{"id":1,"form":"περὶ""#));
+    assert_eq!(Treebank::from_json_str(&json).unwrap(), treebank);
+
+    let mut lines = vec![];
+    write_lines(&treebank, &mut lines).unwrap();
+    let lines = String::from_utf8(lines).unwrap();
+    assert_eq!(lines.lines().count(), treebank.sentences().len());
+    let read = read_lines(&lines).unwrap();
+    assert_eq!(read.sentences(), treebank.sentences());
+    let artificial = &read.sentences()[2].token(27).unwrap();
+    assert_eq!(artificial.insertion_id(), Some("0026e"));
+
+    let mut reader = JsonLinesReader::new(lines.as_bytes());
+    assert_eq!(reader.next().unwrap().unwrap().words()[0].form(), "περὶ");
+
+    let newer = json.replacen(
+        &format!("\"schema\":{SCHEMA_VERSION}"),
+        &format!("\"schema\":{}", SCHEMA_VERSION + 1),
+        1,
+    );
+    assert!(matches!(
+        Treebank::from_json_str(&newer),
+        Err(Error::MalformedJson { .. })
+    ));
+    let duplicate = json.replacen(r#"{"id":2,"document_id""#, r#"{"id":1,"document_id""#, 1);
+    assert!(matches!(
+        Treebank::from_json_str(&duplicate),
+        Err(Error::InvalidSentence { location, .. }) if location.sentence_id == Some(1)
+    ));
+    let broken = format!("{}\n{{\"id\":2}}\n", lines.lines().next().unwrap());
+    assert!(matches!(
+        read_lines(&broken),
+        Err(Error::MalformedJson { location, .. })
+            if location.position.map(|p| p.line) == Some(2)
+    ));
+}
+
+#[test]
+fn test_proiel() {
+    use agldt::error::Error;
+    use agldt::utils::Lexicon;
+    let src = std::fs::read_to_string("./tests/proiel-john.xml").unwrap();
+    let treebank = Treebank::from_proiel_str(&src).unwrap();
+    assert_eq!(treebank.xml_lang(), "grc");
+    assert_eq!(treebank.cts(), "greek-nt-john");
+    assert_eq!(treebank.header().annotation_scheme(), "PROIEL");
+    assert_eq!(
+        treebank
+            .header()
+            .file_desc()
+            .edition_stmt()
+            .resp_stmts()
+            .len(),
+        2
+    );
+    assert_eq!(treebank.sentences().len(), 3);
+    assert_eq!(treebank.count_words(), 26);
+    assert!(treebank.build_lexicon().contains(&"λόγος".to_string()));
+
+    // καὶ coordinates the two clauses, ἐν ἀρχῇ and πρὸς τὸν θεόν are prepositional phrases.
+    let first = &treebank.sentences()[0];
+    let relations: Vec<(&str, u32)> = first
+        .words()
+        .iter()
+        .map(|token| (token.relation_str(), token.head()))
+        .collect();
+    assert_eq!(
+        relations,
+        [
+            ("AuxP", 3),
+            ("ADV", 1),
+            ("PRED_CO", 6),
+            ("ATR", 5),
+            ("SBJ", 3),
+            ("COORD", 0),
+            ("ATR", 8),
+            ("SBJ", 9),
+            ("PRED_CO", 6),
+            ("AuxP", 9),
+            ("ATR", 12),
+            ("ADV", 10),
+        ]
+    );
+    assert_eq!(first.words()[2].postag_str(), Some("v3siia---"));
+    assert_eq!(first.words()[3].postag_str(), Some("l-s---mn-"));
+
+    // The elided copula and the asyndetic coordination are artificial nodes.
+    let second = &treebank.sentences()[1];
+    assert_eq!(second.subdoc(), "JOHN 1.6");
+    let copula = second.token(9).unwrap();
+    assert_eq!(copula.form(), "[0]");
+    assert_eq!(copula.insertion_id(), Some("0008e"));
+    assert_eq!((copula.relation_str(), copula.head()), ("PRED_CO", 10));
+    let coordination = second.token(10).unwrap();
+    assert_eq!(coordination.insertion_id(), Some("0008f"));
+    assert_eq!(coordination.relation_str(), "COORD");
+    assert_eq!(second.token(8).unwrap().relation_str(), "PNOM");
+    assert_eq!(second.token(3).unwrap().relation_str(), "ATV");
+    assert_eq!(second.token(5).unwrap().relation_str(), "ADV");
+    assert_eq!(second.count_words(), 8);
+
+    // ἵνα introduces an adverbial clause.
+    let third = &treebank.sentences()[2];
+    assert_eq!(third.token(5).unwrap().relation_str(), "AuxC");
+    let verb = third.token(6).unwrap();
+    assert_eq!((verb.relation_str(), verb.head()), ("ADV", 5));
+    assert_eq!(verb.postag_str(), Some("v3sasa---"));
+
+    let dangling = src.replace(r#"head-id="302""#, r#"head-id="999""#);
+    assert!(matches!(
+        Treebank::from_proiel_str(&dangling),
+        Err(Error::InvalidHead { location, .. }) if location.sentence_id == Some(3)
+    ));
+}
+
+#[test]
+fn test_arethusa() {
+    use agldt::conllu::Mapping;
+    use agldt::reader::from_str;
+    let src = r#"
+        <sentence id="1" document_id="urn:cts:greekLit:tlg0007.tlg004.perseus-grc1" subdoc="1.1" span="περὶ0:Λυκούργου0">
+          <primary>vgorman1</primary>
+          <secondary>balmas</secondary>
+          <annotator>
+            <short>vgorman1</short>
+            <name>Vanessa Gorman</name>
+            <address>vbgorman@gmail.com</address>
+            <uri>http://data.perseus.org/sosol/users/Vanessa%20Gorman</uri>
+          </annotator>
+          <annotator>Bridget Almas</annotator>
+          <word id="1" form="περὶ" lemma="περί" postag="r--------" relation="AuxP" head="0" cite="urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.1" gloss="about"/>
+          <word id="2" form="Λυκούργου" lemma="Λυκοῦργος" postag="n-s---mg-" relation="ADV" head="1" cite="urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.1" gloss="Lycurgus|lawgiver"/>
+        </sentence>"#;
+    let sentence: Sentence = from_str(src).unwrap();
+    assert_eq!(sentence.primary(), ["vgorman1"]);
+    assert_eq!(sentence.secondary(), ["balmas"]);
+    assert_eq!(sentence.annotators().len(), 2);
+    assert_eq!(sentence.annotators()[0].short(), Some("vgorman1"));
+    assert_eq!(sentence.annotators()[1].name(), "Bridget Almas");
+    assert_eq!(
+        sentence.attributes().get("span").map(String::as_str),
+        Some("περὶ0:Λυκούργου0")
+    );
+    let word = &sentence.words()[0];
+    assert_eq!(
+        word.cite(),
+        Some("urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.1")
+    );
+    assert_eq!(
+        word.attributes().get("gloss").map(String::as_str),
+        Some("about")
+    );
+    assert!(!word.attributes().contains_key("cite"));
+
+    // Every writer keeps the attribution.
+    let treebank = Treebank::builder(Header::new(Monogr::new("Plutarch", "Lycurgus")))
+        .sentence(sentence.clone())
+        .build()
+        .unwrap();
+    let xml = treebank.to_xml_string();
+    assert!(xml.contains("<primary>vgorman1</primary>"));
+    assert!(xml.contains(r#"gloss="about""#));
+    assert_eq!(Treebank::from_xml_str(&xml).unwrap(), treebank);
+    assert_eq!(
+        Treebank::from_json_str(&treebank.to_json_string()).unwrap(),
+        treebank
+    );
+    let conllu = treebank.to_conllu_string().unwrap();
+    assert!(conllu.contains(
+        "# primary = vgorman1\n\
+         # secondary = balmas\n\
+         # annotator = Vanessa Gorman\n\
+         # annotator short = vgorman1\n\
+         # annotator uri = http://data.perseus.org/sosol/users/Vanessa%20Gorman\n\
+         # annotator address = vbgorman@gmail.com\n\
+         # annotator = Bridget Almas\n\
+         # attribute span = περὶ0:Λυκούργου0\n"
+    ));
+    assert!(conllu.contains("Cite=urn:cts:greekLit:tlg0007.tlg004.perseus-grc1:1.1"));
+    assert!(conllu.contains("|Attribute:gloss=Lycurgus\\plawgiver\n"));
+    for mapping in [Mapping::Agldt, Mapping::Raw] {
+        let read = Treebank::from_conllu_str(&conllu, mapping).unwrap();
+        let read_sentence = &read.sentences()[0];
+        assert_eq!(read_sentence.primary(), sentence.primary());
+        assert_eq!(read_sentence.secondary(), sentence.secondary());
+        assert_eq!(read_sentence.annotators(), sentence.annotators());
+        assert_eq!(read_sentence.attributes(), sentence.attributes());
+        assert!(!read_sentence
+            .comments()
+            .iter()
+            .any(|comment| comment.contains("annotator")));
+        for (read_word, word) in read_sentence.words().iter().zip(sentence.words()) {
+            assert_eq!(read_word.cite(), word.cite());
+            assert_eq!(read_word.attributes(), word.attributes());
+        }
+        assert_eq!(read.to_conllu_string().unwrap(), conllu);
     }
+
+    // Correcting a sentence credits the corrector, who is not added twice.
+    let mut treebank = treebank;
+    let sentence = treebank.sentence_mut(1).unwrap();
+    let corrector = PersInfo::new("Jane Doe").with_short("jdoe");
+    sentence.add_annotator(corrector.clone(), false);
+    sentence.add_annotator(corrector, false);
+    assert_eq!(sentence.secondary(), ["balmas", "jdoe"]);
+    assert_eq!(sentence.annotators().len(), 3);
+}
+
+#[test]
+fn test_render() {
+    use agldt::render::{write_dot, write_svg};
+    let src = std::fs::read_to_string("./tests/proiel-john.xml").unwrap();
+    let treebank = Treebank::from_proiel_str(&src).unwrap();
+    let sentence = &treebank.sentences()[1];
+    let mut dot = vec![];
+    write_dot(sentence, &mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph \"greek-nt-john:JOHN 1.6@2\" {"));
+    assert!(dot.contains("w0 -> w10 [label=\"COORD\"];"));
+    assert!(dot.contains("w9 [label=\"[0]\", style=dashed, fontcolor=gray40];"));
+    assert_eq!(dot.matches(" -> ").count(), sentence.words().len());
+
+    let mut svg = vec![];
+    write_svg(sentence, &mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    assert_eq!(svg.matches("<line ").count(), sentence.words().len());
+    assert!(svg.contains(r##"<g class="token" id="w9" fill="#888" font-style="italic">"##));
+    assert!(svg.contains(">PRED_CO</text>"));
+
+    // Sentences that are not trees cannot be drawn.
+    let cycle: Sentence = agldt::reader::from_str(
+        r#"<sentence id="1" document_id="doc" subdoc="1">
+             <word id="1" form="a" relation="PRED" head="2"/>
+             <word id="2" form="b" relation="OBJ" head="1"/>
+           </sentence>"#,
+    )
+    .unwrap();
+    assert!(write_dot(&cycle, &mut vec![]).is_err());
+    assert!(write_svg(&cycle, &mut vec![]).is_err());
+}
+
+#[test]
+fn test_latex() {
+    use agldt::latex::{write_dependency, write_document, write_forest, Style};
+    let src = std::fs::read_to_string("./tests/proiel-john.xml").unwrap();
+    let treebank = Treebank::from_proiel_str(&src).unwrap();
+    let sentence = &treebank.sentences()[1];
+
+    // Artificial nodes come last, and the coordinator between them is the root.
+    let mut tex = vec![];
+    write_dependency(sentence, false, &mut tex).unwrap();
+    let tex = String::from_utf8(tex).unwrap();
+    assert!(tex
+        .contains("Ἰωάννης \\& \\textcolor{gray}{{[}0{]}} \\& \\textcolor{gray}{{[}1{]}} \\\\\n"));
+    assert!(tex.contains("\\deproot{10}{COORD}\n"));
+    assert!(tex.contains("\\depedge{10}{1}{PRED\\_CO}\n"));
+    assert_eq!(tex.matches("\\\\\n").count(), 1);
+
+    let mut tex = vec![];
+    write_forest(sentence, true, &mut tex).unwrap();
+    let tex = String::from_utf8(tex).unwrap();
+    assert!(tex.contains("  [{JOHN 1.6}\n"));
+    assert!(tex.contains(
+        "[{Ἐγένετο\\\\\\textit{γίγνομαι}\\\\3\\textsc{sg.aor.ind.mid}\\\\\\textsf{PRED\\_CO}}"
+    ));
+    assert_eq!(tex.matches('[').count(), tex.matches(']').count());
+
+    let mut tex = vec![];
+    write_document(
+        treebank.header(),
+        treebank.sentences(),
+        Style::Dependency,
+        true,
+        &mut tex,
+    )
+    .unwrap();
+    let tex = String::from_utf8(tex).unwrap();
+    assert!(tex.contains("\\usepackage{tikz-dependency}\n"));
+    assert!(tex.contains("\\title{The Greek New Testament}\n"));
+    assert!(tex.contains("\\section*{JOHN 1.7 \\normalfont\\small(3)}\n"));
+    assert_eq!(tex.matches("\\begin{dependency}").count(), 3);
 }
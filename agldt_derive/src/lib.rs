@@ -1,52 +1,114 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse_macro_input;
+use syn::spanned::Spanned;
 use syn::{
-    Attribute, Data, DataEnum, DeriveInput, Ident, Lit, Meta, MetaList, NestedMeta, Variant,
+    Attribute, Data, DataEnum, DeriveInput, Error, Fields, Ident, Lit, LitChar, Meta, MetaList,
+    NestedMeta, Result, Variant,
 };
 
-fn get_variants(data: &DataEnum) -> Vec<&Variant> {
+/// Names of the positions of the postag, used as keys of `#[postag(...)]` in complex features.
+const POSITIONS: [&str; 9] = [
+    "pos", "person", "number", "tense", "mood", "voice", "gender", "case", "degree",
+];
+
+/// Collects the variants of the enum, rejecting those carrying data.
+fn get_variants(data: &DataEnum) -> Result<Vec<&Variant>> {
     let DataEnum { variants, .. } = data;
     let mut vv: Vec<&Variant> = vec![];
     for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                format!(
+                    "variant `{}` carries data, but features only accept unit variants",
+                    variant.ident
+                ),
+            ));
+        }
         vv.push(variant);
     }
-    vv
+    Ok(vv)
+}
+
+/// Whether `attr` is one of the attributes handled by the derive, so that others, such as doc
+/// comments, can be ignored.
+fn is_feature_attr(attr: &Attribute) -> bool {
+    ["postag", "postagindex", "complexfeature"]
+        .iter()
+        .any(|name| attr.path.is_ident(name))
+}
+
+fn feature_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs.iter().filter(|attr| is_feature_attr(attr)).collect()
+}
+
+/// Returns the `#[postag(...)]` attribute of a variant, if any, as a list of its arguments.
+fn variant_attr(variant: &Variant) -> Result<Option<MetaList>> {
+    let attrs = feature_attrs(&variant.attrs);
+    if let Some(attr) = attrs.iter().find(|attr| !attr.path.is_ident("postag")) {
+        return Err(Error::new_spanned(
+            attr,
+            "only `#[postag(...)]` is allowed on variants",
+        ));
+    }
+    if attrs.len() > 1 {
+        return Err(Error::new_spanned(
+            attrs[1],
+            format!("variant `{}` has more than one `#[postag]`", variant.ident),
+        ));
+    }
+    match attrs.first() {
+        None => Ok(None),
+        Some(attr) => match attr.parse_meta()? {
+            Meta::List(list) if !list.nested.is_empty() => Ok(Some(list)),
+            meta => Err(Error::new_spanned(
+                meta,
+                "expected `#[postag('<char>')]` or `#[postag(<position> = '<char>', ...)]`",
+            )),
+        },
+    }
+}
+
+fn expect_char(nested: &NestedMeta) -> Result<&LitChar> {
+    match nested {
+        NestedMeta::Lit(Lit::Char(c)) => Ok(c),
+        _ => Err(Error::new_spanned(
+            nested,
+            "expected a char literal, e.g. `'a'`",
+        )),
+    }
 }
 
-fn gen_postags(variants: &[&Variant]) -> Vec<char> {
+/// Builds the postag char of every variant of a simple feature, rejecting duplicates.
+fn gen_postags(variants: &[&Variant]) -> Result<Vec<char>> {
     let mut vc: Vec<char> = vec![];
     for variant in variants {
-        if variant.attrs.is_empty() {
-            let pt = gen_postag(variant);
-            if vc.contains(&pt) {
-                panic!("Two variants tried to use the same postag value: {pt} ");
-            } else {
-                vc.push(pt);
-            }
-        } else if variant.attrs.len() > 1 {
-            unimplemented!("Only one variant implemented");
-        } else {
-            let attr = &variant.attrs[0].parse_meta().unwrap();
-            match attr {
-                Meta::List(MetaList { path, nested, .. }) => {
-                    assert_eq!(path.segments[0].ident, "postag");
-                    if let NestedMeta::Lit(Lit::Char(c)) = &nested[0] {
-                        let pt = c
-                            .token()
-                            .to_string()
-                            .replace(['\'', '\\'], "")
-                            .chars()
-                            .next()
-                            .unwrap();
-                        vc.push(pt);
-                    }
+        let (pt, span) = match variant_attr(variant)? {
+            None => (gen_postag(variant), variant.ident.span()),
+            Some(MetaList { nested, .. }) => {
+                if nested.len() > 1 {
+                    return Err(Error::new_spanned(
+                        &nested[1],
+                        "simple features take a single char, use `#[complexfeature(true)]` to set several positions",
+                    ));
                 }
-                _ => unimplemented!("Invalid meta"),
+                let c = expect_char(&nested[0])?;
+                (c.value(), c.span())
             }
+        };
+        if let Some(j) = vc.iter().position(|c| *c == pt) {
+            return Err(Error::new(
+                span,
+                format!(
+                    "variant `{}` uses the postag value '{pt}', already used by `{}`",
+                    variant.ident, variants[j].ident
+                ),
+            ));
         }
+        vc.push(pt);
     }
-    vc
+    Ok(vc)
 }
 
 fn gen_postag(variant: &Variant) -> char {
@@ -54,55 +116,278 @@ fn gen_postag(variant: &Variant) -> char {
     if ident == "EMPTY" {
         return '-';
     }
-    return ident.to_lowercase().chars().next().unwrap();
+    ident.to_lowercase().chars().next().unwrap()
 }
 
-fn get_index(attrs: &Vec<Attribute>) -> u8 {
-    if attrs.is_empty() {
-        return 0;
+/// Reads `#[postagindex(<index>)]`, which every feature must have.
+fn get_index(main_name: &Ident, attrs: &[Attribute]) -> Result<u8> {
+    for attr in feature_attrs(attrs) {
+        if attr.path.is_ident("complexfeature") {
+            continue;
+        }
+        if !attr.path.is_ident("postagindex") {
+            return Err(Error::new_spanned(
+                attr,
+                "only `#[postagindex(...)]` and `#[complexfeature(...)]` are allowed on the enum",
+            ));
+        }
+        return match attr.parse_meta()? {
+            Meta::List(MetaList { nested, .. }) if nested.len() == 1 => get_nested_id(&nested[0]),
+            meta => Err(Error::new_spanned(meta, "expected `#[postagindex(<0-8>)]`")),
+        };
     }
-    if attrs.len() > 1 {
-        let attr = attrs[0].parse_meta().unwrap();
-        match attr {
-            Meta::List(MetaList { path, nested, .. }) => {
-                if path.segments[0].ident == "postagindex" {
-                    get_nested_id(&nested[0])
-                } else if path.segments[0].ident == "complexfeature" {
-                    let attr = attrs[1].parse_meta().unwrap();
-                    if let Meta::List(MetaList { path, nested, .. }) = attr {
-                        assert_eq!(path.segments[0].ident, "postagindex");
-                        get_nested_id(&nested[0])
-                    } else {
-                        panic!("{attr:#?}");
-                    }
-                } else {
-                    unimplemented!("Invalid attribute.");
+    Err(Error::new_spanned(
+        main_name,
+        "missing #[postagindex(<0-8>)]",
+    ))
+}
+
+/// Whether the enum is marked with `#[complexfeature]` or `#[complexfeature(true)]`.
+fn is_complex(attrs: &[Attribute]) -> Result<bool> {
+    for attr in feature_attrs(attrs) {
+        if attr.path.is_ident("complexfeature") {
+            return match attr.parse_meta()? {
+                Meta::Path(_) => Ok(true),
+                Meta::List(MetaList { nested, .. }) => match nested.first() {
+                    None => Ok(true),
+                    Some(NestedMeta::Lit(Lit::Bool(b))) if nested.len() == 1 => Ok(b.value),
+                    Some(_) => Err(Error::new_spanned(
+                        nested,
+                        "expected `#[complexfeature(true)]` or `#[complexfeature(false)]`",
+                    )),
+                },
+                meta @ Meta::NameValue(_) => Err(Error::new_spanned(
+                    meta,
+                    "expected `#[complexfeature(true)]` or `#[complexfeature(false)]`",
+                )),
+            };
+        }
+    }
+    Ok(false)
+}
+
+/// A rule of a complex feature: the character expected at a position of the postag.
+type Rule = (u8, char);
+
+/// Builds the rules of every variant of a complex feature.
+///
+/// A variant without attributes, or with `#[postag(<char>)]`, has a single rule at `index`;
+/// `#[postag(tense = 'a', voice = 'p')]` gives one rule per named position.
+fn gen_rules(variants: &[&Variant], index: u8) -> Result<Vec<Vec<Rule>>> {
+    let mut rules: Vec<Vec<Rule>> = vec![];
+    for variant in variants {
+        let Some(MetaList { nested, .. }) = variant_attr(variant)? else {
+            rules.push(vec![(index, gen_postag(variant))]);
+            continue;
+        };
+        let mut variant_rules: Vec<Rule> = vec![];
+        for meta in &nested {
+            let rule = match meta {
+                NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                    let name = name_value
+                        .path
+                        .get_ident()
+                        .map(ToString::to_string)
+                        .unwrap_or_default();
+                    let Some(position) = POSITIONS.iter().position(|p| *p == name) else {
+                        return Err(Error::new_spanned(
+                            &name_value.path,
+                            format!("unknown postag position, expected one of {POSITIONS:?}"),
+                        ));
+                    };
+                    let Lit::Char(c) = &name_value.lit else {
+                        return Err(Error::new_spanned(
+                            &name_value.lit,
+                            "expected a char literal, e.g. `'a'`",
+                        ));
+                    };
+                    (u8::try_from(position).unwrap(), c.value())
                 }
+                _ => (index, expect_char(meta)?.value()),
+            };
+            if variant_rules.iter().any(|(i, _)| *i == rule.0) {
+                return Err(Error::new_spanned(
+                    meta,
+                    format!(
+                        "variant `{}` sets the postag position {} more than once",
+                        variant.ident, rule.0
+                    ),
+                ));
             }
-            _ => unimplemented!("Invalid meta."),
+            variant_rules.push(rule);
         }
-    } else {
-        let attr = attrs[0].parse_meta().unwrap();
-        match attr {
-            Meta::List(MetaList { path, nested, .. }) => {
-                assert_eq!(path.segments[0].ident, "postagindex");
-                get_nested_id(&nested[0])
+        rules.push(variant_rules);
+    }
+    check_rules(variants, &rules)?;
+    Ok(rules)
+}
+
+/// Checks that no postag can be decoded into two variants of a complex feature.
+///
+/// Two variants conflict when their rules can hold for the same postag (they do not require
+/// different characters at a shared position) and neither is strictly more specific than the
+/// other. Decoding tries the most specific variants first, so that, e.g., a variant for the
+/// present participle may coexist with one for the present.
+fn check_rules(variants: &[&Variant], rules: &[Vec<Rule>]) -> Result<()> {
+    for (i, a) in rules.iter().enumerate() {
+        for (j, b) in rules.iter().enumerate().skip(i + 1) {
+            let compatible = a
+                .iter()
+                .all(|(ia, ca)| b.iter().all(|(ib, cb)| ia != ib || ca == cb));
+            let a_in_b = a.iter().all(|rule| b.contains(rule));
+            let b_in_a = b.iter().all(|rule| a.contains(rule));
+            if compatible && (a_in_b == b_in_a) {
+                return Err(Error::new_spanned(
+                    &variants[j].ident,
+                    format!(
+                        "variants `{}` and `{}` can be decoded from the same postag",
+                        variants[i].ident, variants[j].ident
+                    ),
+                ));
             }
-            _ => unimplemented!("Invalid meta."),
         }
     }
+    Ok(())
 }
 
-fn get_nested_id(nested: &NestedMeta) -> u8 {
-    if let NestedMeta::Lit(l) = &nested {
-        if let Lit::Int(i) = l {
-            let id: u8 = i.token().to_string().parse().unwrap();
-            id
-        } else {
-            panic!("{l:#?}");
+/// Generates the implementation of `agldt::features::ComplexPostagFeature`.
+fn derive_complex(
+    main_name: &Ident,
+    variants: &[&Variant],
+    index: u8,
+) -> Result<proc_macro2::TokenStream> {
+    let rules = gen_rules(variants, index)?;
+    let feature_name = main_name.to_string();
+
+    let variants_names = variants.iter().map(|v| &v.ident).collect::<Vec<&Ident>>();
+    let encodings = rules.iter().map(|variant_rules| {
+        let indices = variant_rules.iter().map(|(i, _)| i);
+        let chars = variant_rules.iter().map(|(_, c)| c);
+        quote!(vec![#(::agldt::features::POSFeature::new(#indices, #chars).unwrap()),*])
+    });
+
+    let mut order: Vec<usize> = (0..variants.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(rules[i].len()));
+    let decodings = order.iter().map(|&i| {
+        let name = &variants[i].ident;
+        let indices = rules[i].iter().map(|(i, _)| i);
+        let chars = rules[i].iter().map(|(_, c)| c);
+        quote!(
+            if [#((#indices, #chars)),*]
+                .iter()
+                .all(|(index, char)| features.iter().any(|f| f.index() == *index && f.char() == *char))
+            {
+                return Ok(Self::#name);
+            }
+        )
+    });
+
+    let mut indices: Vec<u8> = rules.iter().flatten().map(|(i, _)| *i).collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    Ok(quote!(
+        impl ::agldt::features::ComplexPostagFeature for #main_name {
+            const INDICES: &'static [u8] = &[#(#indices),*];
+            fn to_agldt_postags(&self) -> Vec<::agldt::features::POSFeature> {
+                match self {
+                    #(Self::#variants_names => #encodings,)*
+                }
+            }
+            fn from_agldt_postags(
+                features: &[::agldt::features::POSFeature],
+            ) -> Result<Self, ::agldt::Error> {
+                #(#decodings)*
+                Err(::agldt::features::unmatched_features(#feature_name, features))
+            }
         }
-    } else {
-        panic!("epa");
+    ))
+}
+
+/// Generates the implementations of `agldt::features::PostagFeature` and
+/// `TryFrom<agldt::features::POSFeature>`.
+fn derive_simple(
+    main_name: &Ident,
+    variants: &[&Variant],
+    index: u8,
+) -> Result<proc_macro2::TokenStream> {
+    let variants_names = variants.iter().map(|v| &v.ident).collect::<Vec<&Ident>>();
+    let postags: Vec<char> = gen_postags(variants)?;
+
+    let feature_name = main_name.to_string();
+
+    Ok(quote!(
+
+        impl ::agldt::features::PostagFeature for #main_name {
+            const INDEX: u8 = #index;
+            fn to_agldt_postag(&self) -> ::agldt::features::POSFeature {
+                match self {
+                    #(Self::#variants_names => ::agldt::features::POSFeature::new(#index, #postags).unwrap(),)*
+                }
+            }
+            fn to_string(&self) -> String {
+                format!("{}", self.to_agldt_postag())
+            }
+            fn from_agldt_postag(char: char) -> Result<Self, ::agldt::Error> {
+                match char {
+                    #(#postags => Ok(Self::#variants_names),)*
+                    _ => Err(::agldt::features::unknown_feature(#feature_name, #index, char)),
+                }
+            }
+        }
+
+        impl ::std::convert::TryFrom<::agldt::features::POSFeature> for #main_name {
+            type Error = ::agldt::Error;
+            fn try_from(feature: ::agldt::features::POSFeature) -> Result<Self, Self::Error> {
+                if feature.index() == #index {
+                    <Self as ::agldt::features::PostagFeature>::from_agldt_postag(feature.char())
+                } else {
+                    Err(::agldt::features::misplaced_feature(#feature_name, #index, feature))
+                }
+            }
+        }
+    ))
+}
+
+fn get_nested_id(nested: &NestedMeta) -> Result<u8> {
+    let NestedMeta::Lit(Lit::Int(i)) = nested else {
+        return Err(Error::new_spanned(
+            nested,
+            "expected an integer postag index between 0 and 8",
+        ));
+    };
+    match i.base10_parse::<u8>() {
+        Ok(id) if id <= 8 => Ok(id),
+        _ => Err(Error::new_spanned(
+            i,
+            format!(
+                "postag index {} is out of range, postags have 9 positions (0 to 8)",
+                i.base10_digits()
+            ),
+        )),
+    }
+}
+
+fn expand(ast: &DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let main_name = &ast.ident;
+    match &ast.data {
+        Data::Enum(enumdata) => {
+            let index = get_index(main_name, &ast.attrs)?;
+            let variants = get_variants(enumdata)?;
+            if is_complex(&ast.attrs)? {
+                derive_complex(main_name, &variants, index)
+            } else {
+                derive_simple(main_name, &variants, index)
+            }
+        }
+        Data::Struct(data) => Err(Error::new(
+            data.struct_token.span(),
+            "`PostagFeature` can only be derived for enums",
+        )),
+        Data::Union(data) => Err(Error::new(
+            data.union_token.span(),
+            "`PostagFeature` can only be derived for enums",
+        )),
     }
 }
 
@@ -181,34 +466,82 @@ fn get_nested_id(nested: &NestedMeta) -> u8 {
 /// assert_eq!(perfect.to_agldt_postag().index(), 3);
 /// ```
 ///
+/// The derive also implements the reverse direction, reading the feature back from a single
+/// postag character, from a [`POSFeature`](agldt::features::POSFeature) or from the position
+/// `#[postagindex]` of a full postag string:
+///
+/// ```
+/// extern crate agldt;
+/// use agldt::features::{POSFeature, PostagFeature};
+/// extern crate agldt_derive;
+/// use agldt_derive::PostagFeature;
+///
+/// #[derive(PostagFeature, Debug, PartialEq)]
+/// #[postagindex(7)]
+/// enum Case {
+///     Nominative,
+///     Genitive,
+///     Dative,
+///     Accusative,
+///     Vocative,
+/// }
+///
+/// assert_eq!(Case::from_agldt_postag('g').unwrap(), Case::Genitive);
+/// assert!(Case::from_agldt_postag('x').is_err());
+/// assert_eq!(Case::try_from(POSFeature::new(7, 'd').unwrap()).unwrap(), Case::Dative);
+/// assert!(Case::try_from(POSFeature::new(6, 'd').unwrap()).is_err());
+/// assert_eq!(Case::from_postag_str("n-s---mg-").unwrap(), Some(Case::Genitive));
+/// assert_eq!(Case::from_postag_str("d--------").unwrap(), None);
+/// ```
+///
+/// # Complex features
+///
+/// Some features are spread over more than one position of the postag, e.g. a verbal form
+/// combining tense and voice. Marking the enum with `#[complexfeature(true)]` derives
+/// `agldt::features::ComplexPostagFeature` instead, and each variant may give a rule for several
+/// positions, named `pos`, `person`, `number`, `tense`, `mood`, `voice`, `gender`, `case` and
+/// `degree`. Variants without such rules use `#[postagindex]` as simple features do.
+///
+/// ```
+/// extern crate agldt;
+/// use agldt::features::ComplexPostagFeature;
+/// extern crate agldt_derive;
+/// use agldt_derive::PostagFeature;
+///
+/// #[derive(PostagFeature, Debug, PartialEq)]
+/// #[complexfeature(true)]
+/// #[postagindex(3)]
+/// enum VerbForm {
+///     #[postag(tense = 'a', voice = 'p')]
+///     AoristPassive,
+///     #[postag(tense = 'p', mood = 'p')]
+///     PresentParticiple,
+///     Present,
+///     Aorist,
+/// }
+///
+/// assert_eq!(VerbForm::AoristPassive.to_postag_string(), "---a-p---");
+/// assert_eq!(VerbForm::from_postag_str("v-sapp---").unwrap(), Some(VerbForm::AoristPassive));
+/// assert_eq!(VerbForm::from_postag_str("v-sapa---").unwrap(), Some(VerbForm::Aorist));
+/// assert_eq!(VerbForm::from_postag_str("v-pppamn-").unwrap(), Some(VerbForm::PresentParticiple));
+/// assert_eq!(VerbForm::from_postag_str("v3spia---").unwrap(), Some(VerbForm::Present));
+/// assert_eq!(VerbForm::from_postag_str("n-s---mg-").unwrap(), None);
+/// ```
+///
+/// Decoding tries the variants with the most rules first. Two variants that could be decoded
+/// from the same postag without one being more specific than the other are rejected at compile
+/// time.
+///
+/// # Errors
+///
+/// Invalid input is reported as a compile error pointing at the offending span: use on a struct
+/// or union, variants carrying data, a missing `#[postagindex]` or one outside `0..=8`, a
+/// `#[postag]` value that is not a char literal, and two variants sharing the same postag value.
 #[proc_macro_derive(PostagFeature, attributes(postag, postagindex, complexfeature))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-
-    let main_name = &ast.ident;
-    let index = get_index(&ast.attrs);
-
-    let expanded: proc_macro2::TokenStream = match &ast.data {
-        Data::Enum(enumdata) => {
-            let variants = get_variants(enumdata);
-            let variants_names = variants.iter().map(|v| &v.ident).collect::<Vec<&Ident>>();
-            let postags: Vec<char> = gen_postags(&variants);
-
-            quote!(
-
-                impl PostagFeature for #main_name {
-                    fn to_agldt_postag(&self) -> POSFeature {
-                        match self {
-                            #(Self::#variants_names => POSFeature::new(#index, #postags).unwrap(),)*
-                        }
-                    }
-                    fn to_string(&self) -> String {
-                        format!("{}", self.to_agldt_postag())
-                    }
-                }
-            )
-        }
-        _ => unimplemented!("Derive only implemented for Enums."),
-    };
-    TokenStream::from(expanded)
+    match expand(&ast) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(error) => TokenStream::from(error.to_compile_error()),
+    }
 }
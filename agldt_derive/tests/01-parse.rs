@@ -5,6 +5,7 @@ extern crate agldt_derive;
 use agldt_derive::PostagFeature;
 
 #[derive(PostagFeature)]
+#[postagindex(6)]
 pub enum MockFeature {
     Masculine,
     Feminine,
@@ -0,0 +1,22 @@
+extern crate agldt;
+use agldt::features::PostagFeature;
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+/// Documented feature.
+#[derive(PostagFeature)]
+#[postagindex(6)]
+pub enum MockFeature {
+    /// Documented variant.
+    Masculine,
+    /// Documented variant with an explicit postag.
+    #[postag('a')]
+    Middle,
+    Neuter,
+}
+
+fn main() {
+    let a = MockFeature::Middle;
+    assert_eq!(a.to_agldt_postag().char(), 'a');
+    assert_eq!(a.to_agldt_postag().index(), 6);
+}
@@ -0,0 +1,38 @@
+extern crate agldt;
+use agldt::features::{POSFeature, PostagFeature};
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature, Debug, PartialEq)]
+#[postagindex(3)]
+pub enum TenseAspect {
+    Future,
+    #[postag('t')]
+    FuturePerfect,
+    Aorist,
+    EMPTY,
+}
+
+fn main() {
+    assert_eq!(TenseAspect::INDEX, 3);
+    assert_eq!(
+        TenseAspect::from_agldt_postag('t').unwrap(),
+        TenseAspect::FuturePerfect
+    );
+    assert_eq!(TenseAspect::from_agldt_postag('-').unwrap(), TenseAspect::EMPTY);
+    assert!(TenseAspect::from_agldt_postag('z').is_err());
+    assert_eq!(
+        TenseAspect::try_from(POSFeature::new(3, 'a').unwrap()).unwrap(),
+        TenseAspect::Aorist
+    );
+    assert!(TenseAspect::try_from(POSFeature::new(2, 'a').unwrap()).is_err());
+    assert_eq!(
+        TenseAspect::from_postag_str("v3sfia---").unwrap(),
+        Some(TenseAspect::Future)
+    );
+    assert_eq!(
+        TenseAspect::from_postag_str("n-s---mg-").unwrap(),
+        Some(TenseAspect::EMPTY)
+    );
+    assert!(TenseAspect::from_postag_str("v3").is_err());
+}
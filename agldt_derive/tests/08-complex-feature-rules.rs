@@ -0,0 +1,48 @@
+extern crate agldt;
+use agldt::features::{ComplexPostagFeature, POSFeature};
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature, Debug, PartialEq)]
+#[complexfeature(true)]
+#[postagindex(3)]
+pub enum VerbForm {
+    #[postag(tense = 'a', voice = 'p')]
+    AoristPassive,
+    #[postag(tense = 'p', mood = 'p')]
+    PresentParticiple,
+    #[postag(pos = 'v', tense = 'p', mood = 'p', case = 'g')]
+    GenitivePresentParticiple,
+    Present,
+    Aorist,
+    #[postag('r')]
+    Perfect,
+}
+
+fn main() {
+    assert_eq!(VerbForm::INDICES, &[0, 3, 4, 5, 7]);
+    assert_eq!(
+        VerbForm::AoristPassive.to_agldt_postags(),
+        vec![POSFeature::new(3, 'a').unwrap(), POSFeature::new(5, 'p').unwrap()]
+    );
+    assert_eq!(VerbForm::Perfect.to_postag_string(), "---r-----");
+    assert_eq!(
+        VerbForm::from_postag_str("v-sapp---").unwrap(),
+        Some(VerbForm::AoristPassive)
+    );
+    assert_eq!(
+        VerbForm::from_postag_str("v-pppamg-").unwrap(),
+        Some(VerbForm::GenitivePresentParticiple)
+    );
+    assert_eq!(
+        VerbForm::from_postag_str("v-pppamn-").unwrap(),
+        Some(VerbForm::PresentParticiple)
+    );
+    assert_eq!(
+        VerbForm::from_postag_str("v3spia---").unwrap(),
+        Some(VerbForm::Present)
+    );
+    assert_eq!(VerbForm::from_postag_str("n-s---mg-").unwrap(), None);
+    assert!(VerbForm::from_postag_str("v-sapp").is_err());
+    assert!(VerbForm::from_agldt_postags(&[POSFeature::new(3, 'f').unwrap()]).is_err());
+}
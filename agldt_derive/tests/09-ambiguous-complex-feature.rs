@@ -0,0 +1,14 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+#[complexfeature(true)]
+#[postagindex(3)]
+pub enum VerbForm {
+    #[postag(tense = 'a', voice = 'p')]
+    AoristPassive,
+    #[postag(tense = 'a', mood = 'p')]
+    AoristParticiple,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+#[postagindex(9)]
+pub enum MockFeature {
+    Masculine,
+    Feminine,
+}
+
+fn main() {}
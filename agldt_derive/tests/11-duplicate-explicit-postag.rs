@@ -0,0 +1,13 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+#[postagindex(6)]
+pub enum MockFeature {
+    #[postag('x')]
+    Masculine,
+    #[postag('x')]
+    Feminine,
+}
+
+fn main() {}
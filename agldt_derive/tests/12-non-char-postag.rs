@@ -0,0 +1,12 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+#[postagindex(6)]
+pub enum MockFeature {
+    #[postag("m")]
+    Masculine,
+    Feminine,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+#[postagindex(6)]
+pub enum MockFeature {
+    Masculine(u8),
+    Feminine,
+}
+
+fn main() {}
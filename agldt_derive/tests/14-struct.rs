@@ -0,0 +1,9 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+pub struct MockFeature {
+    gender: char,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+extern crate agldt_derive;
+use agldt_derive::PostagFeature;
+
+#[derive(PostagFeature)]
+pub enum MockFeature {
+    Masculine,
+    Feminine,
+}
+
+#[derive(PostagFeature)]
+#[complexfeature(true)]
+pub enum MockComplexFeature {
+    #[postag(tense = 'p', mood = 'p')]
+    PresentParticiple,
+    #[postag(tense = 'a', mood = 'p')]
+    AoristParticiple,
+}
+
+fn main() {}
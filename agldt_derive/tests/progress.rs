@@ -6,4 +6,14 @@ fn tests() {
     t.pass("tests/03-postag-attribute.rs");
     t.pass("tests/04-postagindex-attribute.rs");
     t.pass("tests/05-complex-feature-basic-parse.rs");
+    t.pass("tests/06-doc-comments.rs");
+    t.pass("tests/07-from-postag.rs");
+    t.pass("tests/08-complex-feature-rules.rs");
+    t.compile_fail("tests/09-ambiguous-complex-feature.rs");
+    t.compile_fail("tests/10-postagindex-out-of-range.rs");
+    t.compile_fail("tests/11-duplicate-explicit-postag.rs");
+    t.compile_fail("tests/12-non-char-postag.rs");
+    t.compile_fail("tests/13-data-carrying-variant.rs");
+    t.compile_fail("tests/14-struct.rs");
+    t.compile_fail("tests/15-missing-postagindex.rs");
 }
@@ -1,8 +1,14 @@
 use crate::tools::{
-    build_lexicon_forms, build_lexicon_lemmata, check_unicode, pick_treebank_file, print_info,
+    build_lexicon_forms, build_lexicon_lemmata, check_unicode, convert_treebank,
+    pick_treebank_file, print_info, render_sentences, typeset_sentences, validate_treebank,
 };
+use agldt::conllu::Mapping;
+use agldt::latex::Style;
+use agldt::tagset::{Rule, TagsetChecker};
+use agldt::validate::Severity;
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::Path;
 
 const HELP_TEMPLATE: &str = "\
 {before-help}{name} {version}
@@ -17,13 +23,16 @@ pub(crate) struct Cli {
     /// Commands for running on the treebank
     #[clap(subcommand)]
     command: Commands,
+    /// How to read the tokens of CoNLL-U files
+    #[clap(long, global = true, value_enum, default_value = "agldt")]
+    mapping: ConlluMapping,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Builds a lexicon for the treebank
     Lexicon {
-        /// AGLDT Treebank file
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
         #[clap(value_name = "TREEBANK")]
         treebank_file: String,
         /// File where to save the treebank
@@ -38,23 +47,199 @@ enum Commands {
     },
     /// Describes treebank
     Describe {
-        /// AGLDT Treebank file
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
         #[clap(value_name = "TREEBANK")]
         treebank_file: String,
     },
     /// Checks unicode normalization
     UniCheck {
-        /// AGLDT Treebank file
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
         #[clap(value_name = "TREEBANK(S)")]
         treebank_files: Vec<String>,
     },
+    /// Checks that every sentence is a well-formed dependency tree
+    Validate {
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
+        #[clap(value_name = "TREEBANK(S)")]
+        treebank_files: Vec<String>,
+        /// Also checks postags and relations against the AGLDT tagset
+        #[clap(short, long, action)]
+        tagset: bool,
+        /// Sets the severity of a tagset rule, e.g. `relation-pos=error` or `misplaced-feature=off`
+        #[clap(short, long, value_name = "RULE=LEVEL", value_parser = parse_severity)]
+        severity: Vec<(Rule, Option<Severity>)>,
+    },
+    /// Converts the treebank to another format
+    Convert {
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
+        #[clap(value_name = "TREEBANK")]
+        treebank_file: String,
+        /// Format to convert to
+        #[clap(short, long, value_enum)]
+        to: Format,
+        /// File where to save the converted treebank
+        #[clap(short, long, default_value = "OUT")]
+        output: String,
+    },
+    /// Draws the dependency trees of sentences of the treebank, one file per sentence
+    Render {
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
+        #[clap(value_name = "TREEBANK")]
+        treebank_file: String,
+        /// Sentence to draw, by id (e.g. `3`) or by `subdoc` (e.g. `1.2`); can be repeated
+        #[clap(short, long = "sentence", value_name = "ID|SUBDOC", required = true)]
+        sentences: Vec<String>,
+        /// Format of the pictures
+        #[clap(short, long, value_enum, default_value = "svg")]
+        format: Image,
+        /// Directory where to save the pictures, named after the treebank and the sentence id
+        #[clap(short, long, default_value = ".")]
+        output: String,
+    },
+    /// Typesets the trees of a range of sentences as a standalone LaTeX document
+    Latex {
+        /// AGLDT Treebank file, a PROIEL `xml` file, or a CoNLL-U file (`.conllu`)
+        #[clap(value_name = "TREEBANK")]
+        treebank_file: String,
+        /// First `subdoc` to typeset (e.g. `1.2`), from the start of the treebank if not given
+        #[clap(long, value_name = "SUBDOC")]
+        from: Option<String>,
+        /// Last `subdoc` to typeset (e.g. `1.5`), up to the end of the treebank if not given
+        #[clap(long, value_name = "SUBDOC")]
+        to: Option<String>,
+        /// LaTeX package drawing the trees
+        #[clap(short, long, value_enum, default_value = "dependency")]
+        style: Diagram,
+        /// Adds the lemma and the parsed postag of every word
+        #[clap(short, long)]
+        gloss: bool,
+        /// File where to save the document
+        #[clap(short, long, default_value = "OUT")]
+        output: String,
+    },
+}
+
+/// Ways the tokens of CoNLL-U files can be read.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum ConlluMapping {
+    /// Maps the UD annotation back to AGLDT postags and relation labels
+    Agldt,
+    /// Keeps the UD annotation as it is, e.g. to write the file back as CoNLL-U unchanged
+    Raw,
+}
+
+impl From<ConlluMapping> for Mapping {
+    fn from(mapping: ConlluMapping) -> Self {
+        match mapping {
+            ConlluMapping::Agldt => Self::Agldt,
+            ConlluMapping::Raw => Self::Raw,
+        }
+    }
+}
+
+/// LaTeX packages the trees of sentences can be typeset with.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum Diagram {
+    /// Arcs over the words in the order of the text, with `tikz-dependency`
+    Dependency,
+    /// Trees in the Prague style, with `forest`
+    Forest,
+}
+
+impl From<Diagram> for Style {
+    fn from(diagram: Diagram) -> Self {
+        match diagram {
+            Diagram::Dependency => Self::Dependency,
+            Diagram::Forest => Self::Forest,
+        }
+    }
+}
+
+/// Formats the trees of sentences can be drawn in.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum Image {
+    /// SVG, laid out without Graphviz
+    Svg,
+    /// Graphviz DOT, to be laid out with `dot`
+    Dot,
+}
+
+impl Image {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Dot => "dot",
+        }
+    }
+}
+
+/// Formats a treebank can be converted to.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum Format {
+    /// CoNLL-U, following the Universal Dependencies guidelines
+    Conllu,
+    /// JSON, a single document holding the header and the sentences
+    Json,
+    /// JSON Lines, one sentence per line
+    Jsonl,
+}
+
+impl Format {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Conllu => "conllu",
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Parses `RULE=LEVEL`, where `LEVEL` is `error`, `warning` or `off`.
+fn parse_severity(src: &str) -> Result<(Rule, Option<Severity>), String> {
+    let (rule, level) = src
+        .split_once('=')
+        .ok_or_else(|| format!("expected RULE=LEVEL, found `{src}`"))?;
+    let severity = match level {
+        "off" => None,
+        level => Some(level.parse()?),
+    };
+    Ok((rule.parse()?, severity))
+}
+
+/// File where to save the output made from `treebank_file`: `output`, unless it is `OUT`, in
+/// which case the treebank file with its extension replaced by `extension`.
+///
+/// # Errors
+///
+/// Fails if the file is the treebank file itself, which would be overwritten while being read.
+fn output_path(treebank_file: &str, output: String, extension: &str) -> Result<String> {
+    let output_file = if output == "OUT" {
+        Path::new(treebank_file)
+            .with_extension(extension)
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        output
+    };
+    let same_file = Path::new(&output_file) == Path::new(treebank_file)
+        || matches!(
+            (std::fs::canonicalize(&output_file), std::fs::canonicalize(treebank_file)),
+            (Ok(output), Ok(input)) if output == input
+        );
+    if same_file {
+        anyhow::bail!(
+            "output file `{output_file}` is the treebank file, choose another one with --output"
+        );
+    }
+    Ok(output_file)
 }
 
 pub(crate) fn run_command(cli: Cli) -> Result<()> {
+    let mapping = Mapping::from(cli.mapping);
     match cli.command {
         Commands::Describe { treebank_file } => {
-            let src = pick_treebank_file(&treebank_file)?;
-            print_info(&src);
+            let src = pick_treebank_file(&treebank_file, mapping)?;
+            print_info(src)?;
         }
         Commands::Lexicon {
             treebank_file,
@@ -62,7 +247,7 @@ pub(crate) fn run_command(cli: Cli) -> Result<()> {
             forms,
             count,
         } => {
-            let src = pick_treebank_file(&treebank_file)?;
+            let src = pick_treebank_file(&treebank_file, mapping)?;
             let output_file = if &output == "OUT" {
                 format!("lexicon-{}", &treebank_file.replace(".xml", ".csv"))
             } else {
@@ -70,17 +255,78 @@ pub(crate) fn run_command(cli: Cli) -> Result<()> {
             };
 
             if forms {
-                build_lexicon_forms(&src, &output_file, count)?;
+                build_lexicon_forms(src, &output_file, count)?;
             } else {
-                build_lexicon_lemmata(&src, &output_file, count)?;
+                build_lexicon_lemmata(src, &output_file, count)?;
             }
         }
         Commands::UniCheck { treebank_files } => {
             for treebank_file in treebank_files {
-                let src = pick_treebank_file(&treebank_file)?;
-                check_unicode(&src)?;
+                let src = pick_treebank_file(&treebank_file, mapping)?;
+                check_unicode(src)?;
+            }
+        }
+        Commands::Validate {
+            treebank_files,
+            tagset,
+            severity,
+        } => {
+            let checker = tagset.then(|| {
+                severity
+                    .into_iter()
+                    .fold(TagsetChecker::new(), |checker, (rule, severity)| {
+                        checker.severity(rule, severity)
+                    })
+            });
+            let mut errors = 0;
+            for treebank_file in treebank_files {
+                let src = pick_treebank_file(&treebank_file, mapping)?;
+                errors += validate_treebank(&treebank_file, src, checker.as_ref())?;
+            }
+            if errors > 0 {
+                anyhow::bail!("found {errors} error(s)");
             }
         }
+        Commands::Convert {
+            treebank_file,
+            to,
+            output,
+        } => {
+            let output_file = output_path(&treebank_file, output, to.extension())?;
+            let src = pick_treebank_file(&treebank_file, mapping)?;
+            convert_treebank(src, to, &output_file)?;
+        }
+        Commands::Render {
+            treebank_file,
+            sentences,
+            format,
+            output,
+        } => {
+            let src = pick_treebank_file(&treebank_file, mapping)?;
+            let name = std::path::Path::new(&treebank_file)
+                .file_stem()
+                .map_or_else(|| "sentence".into(), |stem| stem.to_string_lossy());
+            let prefix = std::path::Path::new(&output).join(name.as_ref());
+            render_sentences(src, &sentences, format, &prefix.to_string_lossy())?;
+        }
+        Commands::Latex {
+            treebank_file,
+            from,
+            to,
+            style,
+            gloss,
+            output,
+        } => {
+            let output_file = output_path(&treebank_file, output, "tex")?;
+            let src = pick_treebank_file(&treebank_file, mapping)?;
+            typeset_sentences(
+                src,
+                (from.as_deref(), to.as_deref()),
+                style.into(),
+                gloss,
+                &output_file,
+            )?;
+        }
         #[allow(unreachable_patterns)]
         _ => unimplemented!(),
     }
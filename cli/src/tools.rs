@@ -1,10 +1,84 @@
-use agldt::parser::Treebank;
+use crate::cli::{Format, Image};
+use agldt::conllu::{self, ConlluReader, Mapping};
+use agldt::json;
+use agldt::latex::{self, Style};
+use agldt::parser::{Header, Sentence, Treebank};
+use agldt::proiel::ProielReader;
+use agldt::reader::TreebankReader;
+use agldt::render;
+use agldt::tagset::TagsetChecker;
+use agldt::validate::validate_sentence;
 use anyhow::Result;
 use icu::collator::{Collator, CollatorOptions, Strength};
 use icu::locid::{locale, Locale};
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs::{read_to_string, write};
+use std::fs::{write, File};
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
 use unicode_normalization::{is_nfkc, UnicodeNormalization};
+use xml::reader::{EventReader, XmlEvent};
+
+/// Treebank read sentence by sentence from a file, in AGLDT `xml` or, mapped to AGLDT postags
+/// and relations, in PROIEL `xml` or CoNLL-U for `.conllu` files.
+pub(crate) enum TreebankFile {
+    Xml(Box<TreebankReader<BufReader<File>>>),
+    Proiel(Box<ProielReader<BufReader<File>>>),
+    Conllu(Box<ConlluReader<BufReader<File>>>),
+}
+
+impl TreebankFile {
+    pub(crate) fn header(&self) -> &Header {
+        match self {
+            Self::Xml(reader) => reader.header(),
+            Self::Proiel(reader) => reader.header(),
+            Self::Conllu(reader) => reader.header(),
+        }
+    }
+
+    pub(crate) fn into_treebank(self) -> Result<Treebank, agldt::Error> {
+        match self {
+            Self::Xml(reader) => reader.into_treebank(),
+            Self::Proiel(reader) => reader.into_treebank(),
+            Self::Conllu(reader) => reader.into_treebank(),
+        }
+    }
+}
+
+impl Iterator for TreebankFile {
+    type Item = Result<Sentence, agldt::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Xml(reader) => reader.next(),
+            Self::Proiel(reader) => reader.next(),
+            Self::Conllu(reader) => reader.next(),
+        }
+    }
+}
+
+/// Counts gathered while reading the sentences of a treebank.
+#[derive(Debug, Default)]
+pub(crate) struct Counts {
+    sentences: usize,
+    tokens: usize,
+    words: usize,
+}
+
+impl Counts {
+    pub(crate) fn add(&mut self, sentence: &Sentence) {
+        self.sentences += 1;
+        self.tokens += sentence.count_tokens();
+        self.words += sentence.count_words();
+    }
+}
+
+/// Describes the treebank as `Display` does for a whole `Treebank`.
+pub(crate) fn describe(header: &Header, counts: &Counts) -> String {
+    format!(
+        "{header}\n\nDocument information:\n  Sentences:\t{}\n  Tokens:\t{}\n  Words:\t{}\n\n",
+        counts.sentences, counts.tokens, counts.words
+    )
+}
 
 /// Converts the input to NFKC Unicode.
 pub(crate) fn normalize_unicode<T: Into<String>>(input: T) -> String {
@@ -18,17 +92,20 @@ pub(crate) fn order_greek(tokens: &mut [String]) {
     const LOCALE: Locale = locale!("el");
     let mut options = CollatorOptions::new();
     options.strength = Some(Strength::Primary);
-    let collator =
-        Collator::try_new_unstable(&icu_testdata::unstable(), &LOCALE.into(), options).unwrap();
+    let collator = Collator::try_new(&LOCALE.into(), options).unwrap();
     tokens.sort_by(|a, b| collator.compare(a, b));
 }
 
-pub(crate) fn build_lexicon_lemmata(treebank: &Treebank, output: &str, count: bool) -> Result<()> {
+pub(crate) fn build_lexicon_lemmata(
+    treebank: TreebankFile,
+    output: &str,
+    count: bool,
+) -> Result<()> {
     let mut tokens: Vec<String> = vec![];
     let lexicon_string: String;
 
-    for sentence in treebank.sentences() {
-        for word in sentence.words() {
+    for sentence in treebank {
+        for word in sentence?.words() {
             if let Some(form_string) = word.lemma() {
                 if word.is_word() {
                     if form_string.is_empty() {
@@ -77,11 +154,11 @@ pub(crate) fn build_lexicon_lemmata(treebank: &Treebank, output: &str, count: bo
     Ok(())
 }
 
-pub(crate) fn build_lexicon_forms(treebank: &Treebank, output: &str, count: bool) -> Result<()> {
+pub(crate) fn build_lexicon_forms(treebank: TreebankFile, output: &str, count: bool) -> Result<()> {
     let mut tokens: Vec<String> = vec![];
     let lexicon_string: String;
-    for sentence in treebank.sentences() {
-        for word in sentence.words() {
+    for sentence in treebank {
+        for word in sentence?.words() {
             let form_string = word.form().to_string();
             if word.is_word() {
                 tokens.push(normalize_unicode(form_string));
@@ -118,32 +195,197 @@ pub(crate) fn build_lexicon_forms(treebank: &Treebank, output: &str, count: bool
     Ok(())
 }
 
-pub(crate) fn print_info(treebank: &Treebank) {
-    println!("{treebank}");
+pub(crate) fn print_info(mut treebank: TreebankFile) -> Result<()> {
+    let mut counts = Counts::default();
+    for sentence in treebank.by_ref() {
+        counts.add(&sentence?);
+    }
+    println!("{}", describe(treebank.header(), &counts));
+    Ok(())
 }
 
-pub(crate) fn pick_treebank_file(treebank_file: &String) -> Result<Treebank> {
-    let src: String;
+/// Opens the treebank file, looked up in `AGLDT_PATH` first if set, with the reader for its
+/// format: CoNLL-U files, read with `mapping`, are told by their extension, PROIEL files by
+/// their root element.
+pub(crate) fn pick_treebank_file(treebank_file: &String, mapping: Mapping) -> Result<TreebankFile> {
+    let mut file: File;
     if let Ok(agldt_path) = dotenv::var("AGLDT_PATH") {
         let agldt_file = std::path::Path::new(&agldt_path).join(treebank_file);
-        src = if let Ok(string) = read_to_string(&agldt_file) {
+        file = if let Ok(file) = File::open(&agldt_file) {
             log::info!("Using path {:?}", agldt_file);
-            string
+            file
         } else {
             log::info!("Using path {}", treebank_file);
-            read_to_string(treebank_file)?
+            File::open(treebank_file)?
         };
     } else {
-        src = read_to_string(treebank_file)?;
+        file = File::open(treebank_file)?;
+    }
+
+    let conllu = treebank_file.ends_with(".conllu");
+    let proiel = !conllu && is_proiel(BufReader::new(&mut file));
+    file.rewind()?;
+    let source = BufReader::new(file);
+    if conllu {
+        let reader = ConlluReader::new(source, mapping);
+        Ok(TreebankFile::Conllu(Box::new(reader)))
+    } else if proiel {
+        let reader = ProielReader::new(source)?;
+        Ok(TreebankFile::Proiel(Box::new(reader)))
+    } else {
+        Ok(TreebankFile::Xml(Box::new(TreebankReader::new(source)?)))
+    }
+}
+
+/// Whether the root element of an `xml` file is `<proiel>`, reading the file only up to it. A
+/// file that is not well formed is not, and is left to the AGLDT reader to report.
+fn is_proiel<R: Read>(source: R) -> bool {
+    for event in EventReader::new(source) {
+        match event {
+            Ok(XmlEvent::StartElement { name, .. }) => return name.local_name == "proiel",
+            Ok(_) => {}
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// Prints the diagnostics found in every sentence of the treebank, returning how many of them
+/// are errors. The tagset is checked only when a `checker` is given.
+pub(crate) fn validate_treebank(
+    name: &str,
+    treebank: TreebankFile,
+    checker: Option<&TagsetChecker>,
+) -> Result<usize> {
+    let mut errors = 0;
+    for sentence in treebank {
+        let sentence = sentence?;
+        let mut diagnostics = validate_sentence(&sentence);
+        if let Some(checker) = checker {
+            diagnostics.extend(checker.check_sentence(&sentence));
+        }
+        for diagnostic in diagnostics {
+            if diagnostic.is_error() {
+                errors += 1;
+            }
+            println!("{name}: {diagnostic}");
+        }
+    }
+    Ok(errors)
+}
+
+/// Writes the treebank to `output` in the format `to`, one sentence at a time but for JSON,
+/// which needs the whole treebank.
+pub(crate) fn convert_treebank(treebank: TreebankFile, to: Format, output: &str) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(output)?);
+    match to {
+        Format::Json => json::write_treebank(&treebank.into_treebank()?, &mut writer)?,
+        Format::Conllu => {
+            for sentence in treebank {
+                conllu::write_sentence(&sentence?, &mut writer)?;
+            }
+        }
+        Format::Jsonl => {
+            for sentence in treebank {
+                json::write_sentence(&sentence?, &mut writer)?;
+            }
+        }
     }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Draws the sentences matching any of `selectors`, by id or by `subdoc`, in the format `image`,
+/// each one to `<prefix>-<id>.<extension>`.
+pub(crate) fn render_sentences(
+    treebank: TreebankFile,
+    selectors: &[String],
+    image: Image,
+    prefix: &str,
+) -> Result<()> {
+    let mut rendered = 0;
+    for sentence in treebank {
+        let sentence = sentence?;
+        let id = sentence.id().to_string();
+        if !selectors
+            .iter()
+            .any(|selector| selector == &id || selector == sentence.subdoc())
+        {
+            continue;
+        }
+        let output = format!("{prefix}-{id}.{}", image.extension());
+        log::info!("Drawing sentence {id} in {output}");
+        let mut writer = BufWriter::new(File::create(&output)?);
+        match image {
+            Image::Svg => render::write_svg(&sentence, &mut writer)?,
+            Image::Dot => render::write_dot(&sentence, &mut writer)?,
+        }
+        writer.flush()?;
+        rendered += 1;
+    }
+    if rendered == 0 {
+        anyhow::bail!("no sentence matches {}", selectors.join(", "));
+    }
+    Ok(())
+}
+
+/// Writes the sentences whose `subdoc` falls within `range`, both ends included, as a standalone
+/// LaTeX document to `output`. A sentence spanning several citations, e.g. `1.2-1.3`, must fall
+/// within it as a whole.
+pub(crate) fn typeset_sentences(
+    treebank: TreebankFile,
+    range: (Option<&str>, Option<&str>),
+    style: Style,
+    gloss: bool,
+    output: &str,
+) -> Result<()> {
+    let (from, to) = range;
+    let header = treebank.header().clone();
+    let mut sentences = vec![];
+    for sentence in treebank {
+        let sentence = sentence?;
+        let start = sentence.subdoc().split('-').next().unwrap_or_default();
+        let end = sentence.subdoc().rsplit('-').next().unwrap_or_default();
+        if from.is_some_and(|from| compare_citations(start, from) == Ordering::Less)
+            || to.is_some_and(|to| compare_citations(end, to) == Ordering::Greater)
+        {
+            continue;
+        }
+        sentences.push(sentence);
+    }
+    if sentences.is_empty() {
+        anyhow::bail!(
+            "no sentence from {} to {}",
+            from.unwrap_or("the start"),
+            to.unwrap_or("the end")
+        );
+    }
+    log::info!("Typesetting {} sentences in {output}", sentences.len());
+    let mut writer = BufWriter::new(File::create(output)?);
+    latex::write_document(&header, &sentences, style, gloss, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
 
-    Ok(Treebank::from_xml_str(&src)?)
+/// Orders citations such as `1.2` and `1.10` part by part, numerically where both parts are
+/// numbers.
+fn compare_citations(a: &str, b: &str) -> Ordering {
+    let key = |citation: &str| -> Vec<(Option<u64>, String)> {
+        citation
+            .split('.')
+            .map(|part| (part.parse().ok(), part.to_string()))
+            .collect()
+    };
+    key(a).cmp(&key(b))
 }
 
-pub(crate) fn check_unicode(treebank: &Treebank) -> Result<()> {
+pub(crate) fn check_unicode(mut treebank: TreebankFile) -> Result<()> {
     let mut tokens: Vec<String> = vec![];
     let mut report_string: String;
-    for sentence in treebank.sentences() {
+    let mut counts = Counts::default();
+    for sentence in treebank.by_ref() {
+        let sentence = sentence?;
+        counts.add(&sentence);
         for word in sentence.words() {
             let form_string = word.form().to_string();
             if word.is_word() {
@@ -169,7 +411,7 @@ pub(crate) fn check_unicode(treebank: &Treebank) -> Result<()> {
 
     let mut tokens: Vec<String> = vec![];
 
-    tokens.push(treebank.to_string());
+    tokens.push(describe(treebank.header(), &counts));
     tokens.push("NFKC frequency (tokens):".to_string());
 
     for i in hash_vec {